@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+
+use crate::ast::column::ColumnSpecification;
+use crate::ast::span::Span;
+use crate::ast::table::Table;
+use crate::ast::{Identifier, ObjectName, Separated};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlterStatement {
+    pub table: Table,
+    /// Applied in order, MySQL allows several separated by `,`
+    pub operations: Vec<AlterTableOperation>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlterTableOperation {
+    /// `ADD [COLUMN] spec`
+    AddColumn(ColumnSpecification),
+    /// `DROP [COLUMN] [IF EXISTS] name`
+    DropColumn { column: Identifier, if_exists: bool },
+    /// `RENAME COLUMN old TO new`
+    RenameColumn { from: Identifier, to: Identifier },
+    /// `RENAME TO name`
+    RenameTable(ObjectName),
+}
+
+impl Display for AlterTableOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlterTableOperation::AddColumn(spec) => write!(f, "ADD COLUMN {}", spec),
+            AlterTableOperation::DropColumn { column, if_exists } => {
+                write!(f, "DROP COLUMN ")?;
+                if *if_exists {
+                    write!(f, "IF EXISTS ")?;
+                }
+                write!(f, "{}", column)
+            }
+            AlterTableOperation::RenameColumn { from, to } => {
+                write!(f, "RENAME COLUMN {} TO {}", from, to)
+            }
+            AlterTableOperation::RenameTable(name) => write!(f, "RENAME TO {}", name),
+        }
+    }
+}
+
+impl Display for AlterStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ALTER TABLE {} {}",
+            self.table,
+            Separated(&self.operations, ", ")
+        )
+    }
+}
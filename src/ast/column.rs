@@ -1,24 +1,94 @@
-use crate::ast::Identifier;
+use std::fmt::{Display, Formatter};
+
+use crate::ast::expression::Expression;
+use crate::ast::{Identifier, NameResolution, ObjectName, ResolvedName};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Column {
     pub column: Identifier,
-    pub table: Option<Identifier>,
+    /// The qualifying table name e.g. `tbl` in `tbl.col` or `schema.tbl` in `schema.tbl.col`
+    pub table: Option<ObjectName>,
+}
+
+impl Column {
+    /// Interprets the qualifying table name following `rule`, see [`ObjectName::resolve`].
+    pub fn resolve_table(&self, rule: NameResolution) -> Option<ResolvedName> {
+        self.table.as_ref()?.resolve(rule)
+    }
 }
 
+impl Display for Column {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(table) = &self.table {
+            write!(f, "{}.", table)?;
+        }
+        write!(f, "{}", self.column)
+    }
+}
+
+/// A column definition of `CREATE TABLE` or `ALTER TABLE .. ADD COLUMN` e.g. `id INT NOT NULL`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColumnSpecification {
     pub column: Column,
-    pub sql_type: String, // change to proper type
+    /// The type as written e.g. `VARCHAR(20)` or `DOUBLE PRECISION`
+    pub sql_type: String,
     pub constraint: Vec<ColumnConstraint>,
+    /// The MySQL `COMMENT 'text'`
     pub comment: Option<String>,
 }
 
+impl ColumnSpecification {
+    pub fn name(&self) -> &Identifier {
+        &self.column.column
+    }
+
+    /// Whether the column is declared `NOT NULL`. Its table may still make it non-null with a
+    /// `PRIMARY KEY` constraint, see [`crate::catalog::TableSchema::is_nullable`].
+    pub fn is_not_null(&self) -> bool {
+        self.constraint.contains(&ColumnConstraint::NotNull)
+    }
+
+    pub fn is_primary_key(&self) -> bool {
+        self.constraint.contains(&ColumnConstraint::PrimaryKey)
+    }
+}
+
+impl Display for ColumnSpecification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.column, self.sql_type)?;
+        for constraint in &self.constraint {
+            write!(f, " {}", constraint)?;
+        }
+        if let Some(comment) = &self.comment {
+            write!(f, " COMMENT '{}'", comment.replace('\'', "''"))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColumnConstraint {
     Unique,
     PrimaryKey,
+    /// `AUTO_INCREMENT`, or `AUTOINCREMENT` in SQLite
     AutoIncrement,
     NotNull,
     Nullable,
+    Default(Expression),
+}
+
+impl Display for ColumnConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnConstraint::Unique => write!(f, "UNIQUE"),
+            ColumnConstraint::PrimaryKey => write!(f, "PRIMARY KEY"),
+            ColumnConstraint::AutoIncrement => write!(f, "AUTO_INCREMENT"),
+            ColumnConstraint::NotNull => write!(f, "NOT NULL"),
+            ColumnConstraint::Nullable => write!(f, "NULL"),
+            ColumnConstraint::Default(expr) => write!(f, "DEFAULT {}", expr),
+        }
+    }
 }
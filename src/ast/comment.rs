@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use crate::ast::span::Span;
+
+/// A comment or optimizer hint, kept as written so it can be printed back unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comment {
+    /// The comment as written, delimiters included e.g. `-- note` or `/*+ INDEX(t i) */`
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommentKind {
+    /// `-- ...` or MySQL's `# ...`, up to the end of the line
+    Line,
+    /// `/* ... */`, possibly nested
+    Block,
+    /// An optimizer hint `/*+ ... */`
+    Hint,
+}
+
+impl Comment {
+    pub fn new(text: impl Into<String>, span: Span) -> Self {
+        Comment {
+            text: text.into(),
+            span,
+        }
+    }
+
+    pub fn kind(&self) -> CommentKind {
+        if self.text.starts_with("/*+") {
+            CommentKind::Hint
+        } else if self.text.starts_with("/*") {
+            CommentKind::Block
+        } else {
+            CommentKind::Line
+        }
+    }
+}
+
+/// The comments of a script, each attached to the node it precedes or follows.
+///
+/// Comments are anchored to a token rather than a node: a leading comment belongs to whichever
+/// nodes start at the token after it, a trailing comment to those ending at the token before it.
+/// The nearest node is then the outermost one found, e.g. a comment before `a + 1` belongs to the
+/// binary expression rather than to `a`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comments {
+    leading: BTreeMap<usize, Vec<Comment>>,
+    trailing: BTreeMap<usize, Vec<Comment>>,
+}
+
+impl Comments {
+    /// Attaches `comments` to the surrounding `tokens`, both in source order.
+    ///
+    /// A comment on the same line as the token before it trails that token, any other comment
+    /// leads the token after it. Hints always lead, they apply to what follows them.
+    pub fn attach(comments: impl IntoIterator<Item = Comment>, tokens: &[Span]) -> Self {
+        let mut attached = Comments::default();
+        for comment in comments {
+            let next = tokens.partition_point(|token| token.start.offset < comment.span.end.offset);
+            let previous = next.checked_sub(1).map(|i| tokens[i]);
+            let next = tokens.get(next);
+
+            match (previous, next) {
+                (Some(previous), _)
+                    if previous.end.line == comment.span.start.line
+                        && comment.kind() != CommentKind::Hint =>
+                {
+                    attached.push_trailing(previous.end.offset, comment)
+                }
+                (_, Some(next)) => attached.push_leading(next.start.offset, comment),
+                (Some(previous), None) => attached.push_trailing(previous.end.offset, comment),
+                (None, None) => attached.push_leading(comment.span.end.offset, comment),
+            }
+        }
+        attached
+    }
+
+    fn push_leading(&mut self, offset: usize, comment: Comment) {
+        self.leading.entry(offset).or_default().push(comment)
+    }
+
+    fn push_trailing(&mut self, offset: usize, comment: Comment) {
+        self.trailing.entry(offset).or_default().push(comment)
+    }
+
+    /// The comments just before the node at `span`.
+    pub fn leading(&self, span: Span) -> &[Comment] {
+        self.leading
+            .get(&span.start.offset)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The comments just after the node at `span`, on the same line.
+    pub fn trailing(&self, span: Span) -> &[Comment] {
+        self.trailing
+            .get(&span.end.offset)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every comment, in source order.
+    pub fn iter(&self) -> impl Iterator<Item = &Comment> {
+        let mut comments = self
+            .leading
+            .values()
+            .chain(self.trailing.values())
+            .flatten()
+            .collect::<Vec<_>>();
+        comments.sort_by_key(|comment| comment.span.start.offset);
+        comments.into_iter()
+    }
+
+    /// The optimizer hints, in source order.
+    pub fn hints(&self) -> impl Iterator<Item = &Comment> {
+        self.iter()
+            .filter(|comment| comment.kind() == CommentKind::Hint)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
+    }
+}
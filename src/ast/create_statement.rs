@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+
+use crate::ast::column::ColumnSpecification;
+use crate::ast::span::Span;
+use crate::ast::table::Table;
+use crate::ast::{Identifier, Separated};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreateStatement {
+    pub table: Table,
+    pub if_not_exists: bool,
+    pub columns: Vec<ColumnSpecification>,
+    /// The constraints listed after the columns e.g. `PRIMARY KEY (a, b)`
+    pub constraints: Vec<TableConstraint>,
+    pub span: Span,
+}
+
+/// A constraint over one or more columns of a table.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TableConstraint {
+    PrimaryKey(Vec<Identifier>),
+    Unique(Vec<Identifier>),
+}
+
+impl TableConstraint {
+    pub fn columns(&self) -> &[Identifier] {
+        match self {
+            TableConstraint::PrimaryKey(columns) | TableConstraint::Unique(columns) => columns,
+        }
+    }
+
+    pub fn columns_mut(&mut self) -> &mut Vec<Identifier> {
+        match self {
+            TableConstraint::PrimaryKey(columns) | TableConstraint::Unique(columns) => columns,
+        }
+    }
+}
+
+impl Display for TableConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableConstraint::PrimaryKey(columns) => {
+                write!(f, "PRIMARY KEY ({})", Separated(columns, ", "))
+            }
+            TableConstraint::Unique(columns) => write!(f, "UNIQUE ({})", Separated(columns, ", ")),
+        }
+    }
+}
+
+impl Display for CreateStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CREATE TABLE ")?;
+        if self.if_not_exists {
+            write!(f, "IF NOT EXISTS ")?;
+        }
+        write!(f, "{} ({}", self.table, Separated(&self.columns, ", "))?;
+        for constraint in &self.constraints {
+            write!(f, ", {}", constraint)?;
+        }
+        write!(f, ")")
+    }
+}
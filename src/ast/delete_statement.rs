@@ -1,8 +1,23 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::expression::Expression;
+use crate::ast::span::Span;
 use crate::ast::table::Table;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteStatement {
     pub table: Table,
     pub where_clause: Option<Expression>,
+    pub span: Span,
+}
+
+impl Display for DeleteStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DELETE FROM {}", self.table)?;
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        Ok(())
+    }
 }
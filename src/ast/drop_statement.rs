@@ -1,7 +1,22 @@
+use std::fmt::{Display, Formatter};
+
+use crate::ast::span::Span;
 use crate::ast::table::Table;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DropStatement {
     pub table: Table,
     pub if_exists: bool,
+    pub span: Span,
+}
+
+impl Display for DropStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DROP TABLE ")?;
+        if self.if_exists {
+            write!(f, "IF EXISTS ")?;
+        }
+        write!(f, "{}", self.table)
+    }
 }
@@ -1,29 +1,51 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::column::Column;
-use crate::ast::Identifier;
+use crate::ast::span::{Span, Spanned};
+use crate::ast::{Identifier, ObjectName, Separated};
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
     /// A named reference to a qualified filed in a schema.
-    ColumnReference(ColumnReference),
+    ColumnReference(ColumnReference, Span),
     /// An expression with a specific name.
     Alias {
         expr: Box<Expression>,
         alias: Identifier,
+        span: Span,
     },
     /// A constant
-    Literal(Literal),
+    Literal(Literal, Span),
+    /// A placeholder bound when the statement is executed e.g. `?`, `$1` or `:name`
+    Parameter(Parameter, Span),
     /// A unary expression such as "-id"
-    UnaryExpr { op: UnaryOp, expr: Box<Expression> },
+    UnaryExpr {
+        op: UnaryOp,
+        expr: Box<Expression>,
+        span: Span,
+    },
     /// A binary expression e.g. "col = value"
     BinaryExpr {
         left: Box<Expression>,
         op: BinOp,
         right: Box<Expression>,
+        span: Span,
     },
     //SubQuery(Box<SelectStatement>),
     FunctionExpression {
         func: AggregationFunction,
         args: Vec<Expression>,
+        span: Span,
+    },
+    /// A function written without parentheses e.g. `CURRENT_TIMESTAMP`
+    Niladic(NiladicFunction, Span),
+    /// A call to any other function, e.g. `COALESCE(a, 0)` or `now()`, its name kept as written
+    Function {
+        name: ObjectName,
+        args: Vec<Expression>,
+        span: Span,
     },
     In {
         /// Left hand side expression
@@ -32,52 +54,282 @@ pub enum Expression {
         right: Vec<Expression>,
         /// this represents "IN" or "NOT IN" depending on the predicate
         not_in: bool,
+        span: Span,
     },
+    /// Input that couldn't be parsed, only produced by error recovery e.g. in [`crate::parse_script`]
+    Error(Span),
+}
+
+impl Expression {
+    /// The expression parsed from `span` instead, e.g. to cover the parentheses around it
+    pub fn with_span(mut self, span: Span) -> Self {
+        *self.span_mut() = span;
+        self
+    }
+
+    /// How tightly the outermost operator binds, see [`BinOp::precedence`]
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expression::Alias { .. } => 0,
+            Expression::In { .. } => 4,
+            Expression::UnaryExpr { op, .. } => op.precedence(),
+            Expression::BinaryExpr { op, .. } => op.precedence(),
+            _ => 8,
+        }
+    }
+
+    pub(crate) fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Expression::ColumnReference(_, span)
+            | Expression::Literal(_, span)
+            | Expression::Parameter(_, span)
+            | Expression::Niladic(_, span)
+            | Expression::Error(span)
+            | Expression::Alias { span, .. }
+            | Expression::UnaryExpr { span, .. }
+            | Expression::BinaryExpr { span, .. }
+            | Expression::FunctionExpression { span, .. }
+            | Expression::Function { span, .. }
+            | Expression::In { span, .. } => span,
+        }
+    }
+}
+
+impl Spanned for Expression {
+    fn span(&self) -> Span {
+        match self {
+            Expression::ColumnReference(_, span)
+            | Expression::Literal(_, span)
+            | Expression::Parameter(_, span)
+            | Expression::Niladic(_, span)
+            | Expression::Error(span)
+            | Expression::Alias { span, .. }
+            | Expression::UnaryExpr { span, .. }
+            | Expression::BinaryExpr { span, .. }
+            | Expression::FunctionExpression { span, .. }
+            | Expression::Function { span, .. }
+            | Expression::In { span, .. } => *span,
+        }
+    }
+}
+
+/// Prints `expr`, in parentheses when it binds looser than `precedence`.
+fn fmt_operand(f: &mut Formatter<'_>, expr: &Expression, precedence: u8) -> std::fmt::Result {
+    if expr.precedence() < precedence {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+/// Prints the expression on a single line with as few parentheses as parsing it back requires.
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::ColumnReference(column, _) => write!(f, "{}", column),
+            Expression::Alias { expr, alias, .. } => {
+                fmt_operand(f, expr, 1)?;
+                write!(f, " AS {}", alias)
+            }
+            Expression::Literal(literal, _) => write!(f, "{}", literal),
+            Expression::Parameter(parameter, _) => write!(f, "{}", parameter),
+            Expression::Niladic(function, _) => write!(f, "{}", function),
+            Expression::UnaryExpr { op, expr, .. } => {
+                write!(f, "{}", op)?;
+                // `--` would start a comment
+                if op == &UnaryOp::LogicalNot
+                    || matches!(
+                        **expr,
+                        Expression::UnaryExpr {
+                            op: UnaryOp::Minus,
+                            ..
+                        }
+                    )
+                {
+                    write!(f, " ")?;
+                }
+                fmt_operand(f, expr, op.precedence())
+            }
+            // operators are left associative, a right operand of the same precedence needs parentheses
+            Expression::BinaryExpr {
+                left, op, right, ..
+            } => {
+                fmt_operand(f, left, op.precedence())?;
+                write!(f, " {} ", op)?;
+                fmt_operand(f, right, op.precedence() + 1)
+            }
+            Expression::FunctionExpression { func, args, .. } => {
+                write!(f, "{}({})", func, Separated(args, ", "))
+            }
+            Expression::Function { name, args, .. } => {
+                write!(f, "{}({})", name, Separated(args, ", "))
+            }
+            Expression::In {
+                left,
+                right,
+                not_in,
+                ..
+            } => {
+                fmt_operand(f, left, self.precedence())?;
+                let not = if *not_in { " NOT" } else { "" };
+                write!(f, "{} IN ({})", not, Separated(right, ", "))
+            }
+            Expression::Error(_) => write!(f, "<error>"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColumnReference {
     /// e.g. `*`
     Wildcard,
-    /// e.g. `tbl.*`
-    QualifiedWildcard(Identifier),
+    /// e.g. `tbl.*` or `schema.tbl.*`
+    QualifiedWildcard(ObjectName),
     /// table column
     Column(Column),
 }
 
+impl Display for ColumnReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnReference::Wildcard => write!(f, "*"),
+            ColumnReference::QualifiedWildcard(table) => write!(f, "{}.*", table),
+            ColumnReference::Column(column) => write!(f, "{}", column),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     Null,
+    Boolean(bool),
     String(String),
     UnsignedInteger(i64),
     UnsignedFloat(f64),
     Date(String),
 }
 
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Null => write!(f, "NULL"),
+            Literal::Boolean(true) => write!(f, "TRUE"),
+            Literal::Boolean(false) => write!(f, "FALSE"),
+            Literal::String(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Literal::UnsignedInteger(n) => write!(f, "{}", n),
+            // `Debug` keeps the `.0` of a whole number, so it isn't read back as an integer
+            Literal::UnsignedFloat(n) => write!(f, "{:?}", n),
+            Literal::Date(date) => write!(f, "{}", date),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parameter {
+    /// e.g. `?`
+    Anonymous,
+    /// e.g. `$1`
+    Positional(u32),
+    /// e.g. `:name`
+    Named(String),
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Parameter::Anonymous => write!(f, "?"),
+            Parameter::Positional(n) => write!(f, "${}", n),
+            Parameter::Named(name) => write!(f, ":{}", name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinOp {
     Addition,
     Multiplication,
     Gt,
     Lt,
     Eq,
-    /* Subtraction,
+    Subtraction,
     Division,
     Mod,
     GtEq,
     LtEq,
+    /// `<>` or `!=`
     NotEq,
     And,
-    Or, */
+    Or,
+}
+
+impl BinOp {
+    /// How tightly the operator binds, higher binds tighter, matching [`crate::parser::expression::expression`]:
+    /// `OR` 1, `AND` 2, prefix `NOT` 3, comparisons and `IN` 4, `+ -` 5, `* / %` 6 and prefix `-` 7.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinOp::Or => 1,
+            BinOp::And => 2,
+            BinOp::Gt | BinOp::Lt | BinOp::Eq | BinOp::GtEq | BinOp::LtEq | BinOp::NotEq => 4,
+            BinOp::Addition | BinOp::Subtraction => 5,
+            BinOp::Multiplication | BinOp::Division | BinOp::Mod => 6,
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinOp::Addition => "+",
+            BinOp::Multiplication => "*",
+            BinOp::Gt => ">",
+            BinOp::Lt => "<",
+            BinOp::Eq => "=",
+            BinOp::Subtraction => "-",
+            BinOp::Division => "/",
+            BinOp::Mod => "%",
+            BinOp::GtEq => ">=",
+            BinOp::LtEq => "<=",
+            BinOp::NotEq => "<>",
+            BinOp::And => "AND",
+            BinOp::Or => "OR",
+        };
+        write!(f, "{}", op)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Minus,
+    /// `NOT` or `!`
     LogicalNot,
 }
 
+impl UnaryOp {
+    /// See [`BinOp::precedence`], `!` is printed as `NOT`
+    pub fn precedence(&self) -> u8 {
+        match self {
+            UnaryOp::LogicalNot => 3,
+            UnaryOp::Minus => 7,
+        }
+    }
+}
+
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOp::Minus => write!(f, "-"),
+            UnaryOp::LogicalNot => write!(f, "NOT"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AggregationFunction {
     Count,
     Sum,
@@ -85,3 +337,48 @@ pub enum AggregationFunction {
     Max,
     Min,
 }
+
+impl Display for AggregationFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AggregationFunction::Count => "COUNT",
+            AggregationFunction::Sum => "SUM",
+            AggregationFunction::Avg => "AVG",
+            AggregationFunction::Max => "MAX",
+            AggregationFunction::Min => "MIN",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A function standard SQL writes without parentheses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NiladicFunction {
+    CurrentDate,
+    CurrentTime,
+    CurrentTimestamp,
+}
+
+impl NiladicFunction {
+    /// The function named `name` in any letter case, e.g. `current_date`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "current_date" => Some(NiladicFunction::CurrentDate),
+            "current_time" => Some(NiladicFunction::CurrentTime),
+            "current_timestamp" => Some(NiladicFunction::CurrentTimestamp),
+            _ => None,
+        }
+    }
+}
+
+impl Display for NiladicFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            NiladicFunction::CurrentDate => "CURRENT_DATE",
+            NiladicFunction::CurrentTime => "CURRENT_TIME",
+            NiladicFunction::CurrentTimestamp => "CURRENT_TIMESTAMP",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -1,10 +1,34 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::column::Column;
-use crate::ast::expression::Literal;
+use crate::ast::expression::Expression;
+use crate::ast::span::Span;
 use crate::ast::table::Table;
+use crate::ast::Separated;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InsertStatement {
     pub table: Table,
     pub fields: Option<Vec<Column>>,
-    pub data: Vec<Vec<Literal>>,
+    /// One list of values per row e.g. `VALUES (1, 'a'), (2, ?)`
+    pub data: Vec<Vec<Expression>>,
+    pub span: Span,
+}
+
+impl Display for InsertStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "INSERT INTO {}", self.table)?;
+        if let Some(fields) = &self.fields {
+            write!(f, " ({})", Separated(fields, ", "))?;
+        }
+        write!(f, " VALUES ")?;
+        for (i, row) in self.data.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "({})", Separated(row, ", "))?;
+        }
+        Ok(())
+    }
 }
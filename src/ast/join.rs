@@ -1,7 +1,10 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::expression::Expression;
-use crate::ast::Identifier;
+use crate::ast::{Identifier, Separated};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JoinType {
     Inner,
     LeftJoin,
@@ -11,7 +14,31 @@ pub enum JoinType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JoinCondition {
     Using(Vec<Identifier>),
     On(Vec<Expression>),
 }
+
+impl Display for JoinType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let join = match self {
+            JoinType::Inner => "JOIN",
+            JoinType::LeftJoin => "LEFT JOIN",
+            JoinType::RightJoin => "RIGHT JOIN",
+            JoinType::FullOuterJoin => "FULL OUTER JOIN",
+            JoinType::CrossJoin => "CROSS JOIN",
+        };
+        write!(f, "{}", join)
+    }
+}
+
+impl Display for JoinCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoinCondition::Using(columns) => write!(f, "USING ({})", Separated(columns, ", ")),
+            JoinCondition::On(conditions) if conditions.is_empty() => write!(f, "ON"),
+            JoinCondition::On(conditions) => write!(f, "ON {}", Separated(conditions, ", ")),
+        }
+    }
+}
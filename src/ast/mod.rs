@@ -1,20 +1,311 @@
+//! The syntax tree produced by the parser.
+//!
+//! With the `serde` feature every type here implements `Serialize` and `Deserialize`. The JSON
+//! shape follows serde's defaults and is kept stable: structs are objects keyed by field name,
+//! enums are externally tagged, i.e. a unit variant is its name as a string e.g. `"Asc"` and any
+//! other variant an object with the variant name as its only key, e.g. `{"Literal": [{"UnsignedInteger": 1}, span]}`.
+//! Spans are `{"start": location, "end": location}` with a location being
+//! `{"offset": 0, "line": 1, "column": 1}`.
 use std::fmt::{Display, Formatter};
 
+use crate::ast::{
+    alter_statement::AlterStatement,
+    create_statement::CreateStatement,
+    delete_statement::DeleteStatement,
+    drop_statement::DropStatement,
+    insert_statement::InsertStatement,
+    select_statement::SelectStatement,
+    span::{Span, Spanned},
+    update_statement::UpdateStatement,
+};
+
+pub mod alter_statement;
 pub mod column;
+pub mod comment;
+pub mod create_statement;
 pub mod delete_statement;
 pub mod drop_statement;
 pub mod expression;
 pub mod insert_statement;
 pub mod join;
 pub mod select_statement;
+pub mod span;
 pub mod table;
 pub mod update_statement;
 
+/// A single SQL statement.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Identifier(pub String);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Statement {
+    Select(Box<SelectStatement>),
+    Insert(InsertStatement),
+    Update(UpdateStatement),
+    Delete(DeleteStatement),
+    Drop(DropStatement),
+    Create(CreateStatement),
+    Alter(AlterStatement),
+    /// A statement that couldn't be parsed, only produced by error recovery e.g. in [`crate::parse_script`]
+    Error(Span),
+}
+
+impl Spanned for Statement {
+    fn span(&self) -> Span {
+        match self {
+            Statement::Select(stmt) => stmt.span,
+            Statement::Insert(stmt) => stmt.span,
+            Statement::Update(stmt) => stmt.span,
+            Statement::Delete(stmt) => stmt.span,
+            Statement::Drop(stmt) => stmt.span,
+            Statement::Create(stmt) => stmt.span,
+            Statement::Alter(stmt) => stmt.span,
+            Statement::Error(span) => *span,
+        }
+    }
+}
+
+/// Prints the statement on a single line, without a trailing `;`.
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Statement::Select(stmt) => write!(f, "{}", stmt),
+            Statement::Insert(stmt) => write!(f, "{}", stmt),
+            Statement::Update(stmt) => write!(f, "{}", stmt),
+            Statement::Delete(stmt) => write!(f, "{}", stmt),
+            Statement::Drop(stmt) => write!(f, "{}", stmt),
+            Statement::Create(stmt) => write!(f, "{}", stmt),
+            Statement::Alter(stmt) => write!(f, "{}", stmt),
+            Statement::Error(_) => write!(f, "<error>"),
+        }
+    }
+}
+
+/// Prints the items of a list with `separator` between them e.g. `a, b, c`.
+pub(crate) struct Separated<'a, T>(pub &'a [T], pub &'static str);
+
+impl<T: Display> Display for Separated<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", self.1)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// A name such as a column, table or alias.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Identifier {
+    /// The name with any quotes removed and escapes resolved
+    pub value: String,
+    /// The opening quote character e.g. `` ` ``, `"` or `[`, `None` when unquoted
+    pub quote_style: Option<char>,
+    /// Includes the quotes
+    pub span: Span,
+}
+
+impl Identifier {
+    pub fn new<S: Into<String>>(value: S) -> Self {
+        Identifier {
+            value: value.into(),
+            quote_style: None,
+            span: Span::default(),
+        }
+    }
+
+    pub fn quoted<S: Into<String>>(quote: char, value: S) -> Self {
+        Identifier {
+            value: value.into(),
+            quote_style: Some(quote),
+            span: Span::default(),
+        }
+    }
+
+    /// The value used when comparing names, unquoted identifiers are case-insensitive
+    /// and are folded to lower case, quoted ones are kept verbatim.
+    pub fn normalized(&self) -> String {
+        match self.quote_style {
+            Some(_) => self.value.clone(),
+            None => self.value.to_lowercase(),
+        }
+    }
+
+    /// Whether both identifiers name the same object, following the quoting rules of [`Identifier::normalized`].
+    pub fn matches(&self, other: &Identifier) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    pub fn with_span(self, span: Span) -> Self {
+        Identifier { span, ..self }
+    }
+}
+
+impl Spanned for Identifier {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Returns the closing quote for an opening identifier quote.
+pub fn closing_quote(open: char) -> char {
+    match open {
+        '[' => ']',
+        c => c,
+    }
+}
 
 impl Display for Identifier {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self.quote_style {
+            Some(open) => {
+                let close = closing_quote(open);
+                let escaped = self.value.replace(close, &format!("{}{}", close, close));
+                write!(f, "{}{}{}", open, escaped, close)
+            }
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// A possibly qualified name made of dot separated parts e.g. `catalog.schema.table`, it has at
+/// least one part.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<Identifier>", into = "Vec<Identifier>")
+)]
+pub struct ObjectName(Vec<Identifier>);
+
+impl ObjectName {
+    /// The name made of `parts`, outermost first, `None` when there are none.
+    pub fn new<I: IntoIterator<Item = Identifier>>(parts: I) -> Option<Self> {
+        let parts = parts.into_iter().collect::<Vec<_>>();
+        (!parts.is_empty()).then_some(ObjectName(parts))
+    }
+
+    /// `name` qualified by `qualifier`, outermost first.
+    pub fn qualified<I: IntoIterator<Item = Identifier>>(qualifier: I, name: Identifier) -> Self {
+        let mut parts = qualifier.into_iter().collect::<Vec<_>>();
+        parts.push(name);
+        ObjectName(parts)
     }
+
+    /// The unqualified name i.e. the last part
+    pub fn name(&self) -> &Identifier {
+        self.split().0
+    }
+
+    /// Every part but the last, outermost first
+    pub fn qualifier(&self) -> &[Identifier] {
+        self.split().1
+    }
+
+    fn split(&self) -> (&Identifier, &[Identifier]) {
+        self.0
+            .split_last()
+            .expect("the constructors don't make empty names")
+    }
+
+    /// Every part, outermost first
+    pub fn parts(&self) -> &[Identifier] {
+        &self.0
+    }
+
+    pub fn parts_mut(&mut self) -> &mut [Identifier] {
+        &mut self.0
+    }
+
+    pub fn into_parts(self) -> Vec<Identifier> {
+        self.0
+    }
+
+    /// Whether both names have the same parts, see [`Identifier::matches`].
+    pub fn matches(&self, other: &ObjectName) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| a.matches(b))
+    }
+
+    /// Whether `suffix` names this object with fewer qualifiers, e.g. `t` or `db.t` for `db.t`.
+    pub fn ends_with(&self, suffix: &ObjectName) -> bool {
+        self.0.len() >= suffix.0.len()
+            && self.0[self.0.len() - suffix.0.len()..]
+                .iter()
+                .zip(&suffix.0)
+                .all(|(a, b)| a.matches(b))
+    }
+
+    /// Splits the name into catalog, schema and object following `rule`.
+    ///
+    /// Returns `None` when the name has more parts than the rule allows.
+    pub fn resolve(&self, rule: NameResolution) -> Option<ResolvedName> {
+        let (object, qualifier) = self.split();
+        let (catalog, schema) = match (rule, qualifier) {
+            (_, []) => (None, None),
+            (_, [schema]) => (None, Some(schema.clone())),
+            (NameResolution::CatalogSchemaObject, [catalog, schema]) => {
+                (Some(catalog.clone()), Some(schema.clone()))
+            }
+            _ => return None,
+        };
+        Some(ResolvedName {
+            catalog,
+            schema,
+            object: object.clone(),
+        })
+    }
+}
+
+impl Spanned for ObjectName {
+    fn span(&self) -> Span {
+        self.0
+            .iter()
+            .fold(Span::default(), |span, part| span.union(part.span))
+    }
+}
+
+impl From<Identifier> for ObjectName {
+    fn from(name: Identifier) -> Self {
+        ObjectName(vec![name])
+    }
+}
+
+impl TryFrom<Vec<Identifier>> for ObjectName {
+    type Error = &'static str;
+
+    fn try_from(parts: Vec<Identifier>) -> Result<Self, Self::Error> {
+        ObjectName::new(parts).ok_or("an object name needs at least one part")
+    }
+}
+
+impl From<ObjectName> for Vec<Identifier> {
+    fn from(name: ObjectName) -> Self {
+        name.into_parts()
+    }
+}
+
+impl Display for ObjectName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Separated(&self.0, "."))
+    }
+}
+
+/// How the parts of a qualified name map onto catalog, schema and object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NameResolution {
+    /// `catalog.schema.object` e.g. ANSI SQL and PostgreSQL
+    CatalogSchemaObject,
+    /// `database.object` e.g. MySQL and SQLite, the database is reported as the schema
+    SchemaObject,
+}
+
+/// A qualified name interpreted by [`ObjectName::resolve`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResolvedName {
+    pub catalog: Option<Identifier>,
+    pub schema: Option<Identifier>,
+    pub object: Identifier,
 }
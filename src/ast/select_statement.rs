@@ -1,50 +1,337 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::expression::Expression;
 use crate::ast::join::{JoinCondition, JoinType};
+use crate::ast::span::{Span, Spanned};
 use crate::ast::table::Table;
-use crate::ast::Identifier;
+use crate::ast::{Identifier, Separated};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectClause {
     pub distinct: bool,
     pub projection: Vec<Expression>,
     pub from: Option<TableReference>,
     pub where_exp: Option<Expression>,
-    pub group_by: Vec<Expression>,
+    pub group_by: Option<GroupBy>,
     pub having: Option<Expression>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupBy {
+    /// e.g. `GROUP BY ALL`, groups by every non-aggregated projection item
+    All,
+    /// e.g. `GROUP BY region, ROLLUP(city, street)`
+    Elements(Vec<GroupingElement>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupingElement {
+    /// A plain grouping expression e.g. `region`
+    Expr(Expression),
+    /// A 1-based reference to an item of the projection e.g. `GROUP BY 1`
+    Ordinal(u64, Span),
+    /// e.g. `ROLLUP(region, (city, street))`, each inner list is one composite column
+    Rollup(Vec<Vec<Expression>>),
+    /// e.g. `CUBE(a, b)`, each inner list is one composite column
+    Cube(Vec<Vec<Expression>>),
+    /// e.g. `GROUPING SETS ((a), (a, b), ())`, an empty list is the grand total
+    GroupingSets(Vec<Vec<Expression>>),
+}
+
+impl GroupingElement {
+    /// The expressions grouped by, none for an ordinal.
+    pub fn exprs(&self) -> Vec<&Expression> {
+        match self {
+            GroupingElement::Expr(expr) => vec![expr],
+            GroupingElement::Ordinal(..) => vec![],
+            GroupingElement::Rollup(sets)
+            | GroupingElement::Cube(sets)
+            | GroupingElement::GroupingSets(sets) => sets.iter().flatten().collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableReference {
-    BaseRelation(Table),
+    /// e.g. `orders`, `shop.orders AS o` or `orders o`
+    BaseRelation {
+        table: Table,
+        alias: Option<Identifier>,
+        span: Span,
+    },
     SubQuery {
         subquery: Box<SelectStatement>,
         alias: Option<Identifier>,
+        span: Span,
     },
     Join {
         left: Box<TableReference>,
         op: JoinType,
         right: Box<TableReference>,
         cond: JoinCondition,
+        span: Span,
     },
 }
 
+impl Spanned for TableReference {
+    fn span(&self) -> Span {
+        match self {
+            TableReference::BaseRelation { span, .. }
+            | TableReference::SubQuery { span, .. }
+            | TableReference::Join { span, .. } => *span,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectStatement {
+    /// The common table expressions of `WITH`, empty without one
+    pub with: Vec<CommonTableExpression>,
     pub body: SelectClause,
+    /// The clauses `UNION` combines with `body`, in order
+    pub unions: Vec<Union>,
     /// e.g. ORDER BY FirstName ASC, LastName DESC
     pub order_by: Vec<OrderBy>,
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+    /// e.g. `LIMIT 10` or `LIMIT ?`, MySQL's `LIMIT 5, 10` is stored as `LIMIT 10 OFFSET 5`
+    pub limit: Option<Expression>,
+    pub offset: Option<Expression>,
+    /// e.g. FETCH FIRST 10 ROWS ONLY
+    pub fetch: Option<Fetch>,
+    pub span: Span,
+}
+
+/// A select clause combined with the ones before it, e.g. `UNION ALL SELECT id FROM archive`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Union {
+    /// `UNION ALL`, which keeps duplicate rows
+    pub all: bool,
+    pub clause: SelectClause,
+    pub span: Span,
 }
 
+/// A query named by `WITH` for the statement that follows, e.g. `recent (id) AS (SELECT ...)`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommonTableExpression {
+    pub name: Identifier,
+    /// Names for the columns of the query, empty to keep its own
+    pub columns: Vec<Identifier>,
+    pub query: Box<SelectStatement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderBy {
-    pub expr: Vec<Expression>,
+    pub expr: Expression,
     pub direction: Option<Order>,
+    /// e.g. `NULLS LAST`
+    pub nulls: Option<NullsOrder>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Order {
     Asc,
     Desc,
 }
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fetch {
+    /// The number of rows, `FETCH FIRST ROW ONLY` has none
+    pub quantity: Option<Expression>,
+    /// `WITH TIES` instead of `ONLY`
+    pub with_ties: bool,
+}
+
+impl Display for SelectStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.with.is_empty() {
+            write!(f, "WITH {} ", Separated(&self.with, ", "))?;
+        }
+        write!(f, "{}", self.body)?;
+        for union in &self.unions {
+            write!(f, " {}", union)?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, " ORDER BY {}", Separated(&self.order_by, ", "))?;
+        }
+        if let Some(limit) = &self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, " OFFSET {}", offset)?;
+            // the standard form, `FETCH` follows `OFFSET n ROWS`
+            if self.fetch.is_some() {
+                write!(f, " ROWS")?;
+            }
+        }
+        if let Some(fetch) = &self.fetch {
+            write!(f, " {}", fetch)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CommonTableExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.columns.is_empty() {
+            write!(f, " ({})", Separated(&self.columns, ", "))?;
+        }
+        write!(f, " AS ({})", self.query)
+    }
+}
+
+impl Display for Union {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UNION ")?;
+        if self.all {
+            write!(f, "ALL ")?;
+        }
+        write!(f, "{}", self.clause)
+    }
+}
+
+impl Display for SelectClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SELECT")?;
+        if self.distinct {
+            write!(f, " DISTINCT")?;
+        }
+        if !self.projection.is_empty() {
+            write!(f, " {}", Separated(&self.projection, ", "))?;
+        }
+        if let Some(from) = &self.from {
+            write!(f, " FROM {}", from)?;
+        }
+        if let Some(where_exp) = &self.where_exp {
+            write!(f, " WHERE {}", where_exp)?;
+        }
+        if let Some(group_by) = &self.group_by {
+            write!(f, " GROUP BY {}", group_by)?;
+        }
+        if let Some(having) = &self.having {
+            write!(f, " HAVING {}", having)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for GroupBy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupBy::All => write!(f, "ALL"),
+            GroupBy::Elements(elements) => write!(f, "{}", Separated(elements, ", ")),
+        }
+    }
+}
+
+/// A composite column of a grouping set, a single expression is printed bare.
+struct GroupingSet<'a>(&'a [Expression]);
+
+impl Display for GroupingSet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            [expr] => write!(f, "{}", expr),
+            exprs => write!(f, "({})", Separated(exprs, ", ")),
+        }
+    }
+}
+
+impl Display for GroupingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (name, sets) = match self {
+            GroupingElement::Expr(expr) => return write!(f, "{}", expr),
+            GroupingElement::Ordinal(n, _) => return write!(f, "{}", n),
+            GroupingElement::Rollup(sets) => ("ROLLUP", sets),
+            GroupingElement::Cube(sets) => ("CUBE", sets),
+            GroupingElement::GroupingSets(sets) => ("GROUPING SETS ", sets),
+        };
+        let sets = sets.iter().map(|set| GroupingSet(set)).collect::<Vec<_>>();
+        write!(f, "{}({})", name, Separated(&sets, ", "))
+    }
+}
+
+impl Display for TableReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableReference::BaseRelation { table, alias, .. } => {
+                write!(f, "{}", table)?;
+                if let Some(alias) = alias {
+                    write!(f, " AS {}", alias)?;
+                }
+                Ok(())
+            }
+            TableReference::SubQuery {
+                subquery, alias, ..
+            } => {
+                write!(f, "({})", subquery)?;
+                if let Some(alias) = alias {
+                    write!(f, " AS {}", alias)?;
+                }
+                Ok(())
+            }
+            // joins are left associative, a join on the right needs parentheses
+            TableReference::Join {
+                left,
+                op,
+                right,
+                cond,
+                ..
+            } => {
+                write!(f, "{} {} ", left, op)?;
+                match **right {
+                    TableReference::Join { .. } => write!(f, "({})", right)?,
+                    _ => write!(f, "{}", right)?,
+                }
+                write!(f, " {}", cond)
+            }
+        }
+    }
+}
+
+impl Display for OrderBy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expr)?;
+        match self.direction {
+            Some(Order::Asc) => write!(f, " ASC")?,
+            Some(Order::Desc) => write!(f, " DESC")?,
+            None => {}
+        }
+        match self.nulls {
+            Some(NullsOrder::First) => write!(f, " NULLS FIRST"),
+            Some(NullsOrder::Last) => write!(f, " NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for Fetch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.quantity {
+            Some(quantity) => write!(f, "FETCH FIRST {} ROWS", quantity)?,
+            None => write!(f, "FETCH FIRST ROW")?,
+        }
+        if self.with_ties {
+            write!(f, " WITH TIES")
+        } else {
+            write!(f, " ONLY")
+        }
+    }
+}
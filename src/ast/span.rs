@@ -0,0 +1,126 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::visitor::{clear_spans, VisitMut};
+
+/// A position in the source text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    /// Byte offset from the start of the source
+    pub offset: usize,
+    /// 1-based line, `0` when the location is unknown
+    pub line: usize,
+    /// 1-based column in characters, not bytes
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(offset: usize, line: usize, column: usize) -> Self {
+        Location {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// The location of the byte `offset` within `source`.
+    pub fn of(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The part of the source a node was parsed from.
+///
+/// Nodes built by hand have an empty default span. Nodes only compare equal with `==` when their
+/// spans are equal too, see [`IgnoreSpans`] to compare a parsed tree with one built by hand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Self {
+        Span { start, end }
+    }
+
+    /// The byte range covered by the span
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// Whether the span was produced by the parser rather than defaulted
+    pub fn is_known(&self) -> bool {
+        self.start.line > 0
+    }
+
+    /// The smallest span covering both spans, unknown spans are ignored.
+    pub fn union(self, other: Span) -> Span {
+        match (self.is_known(), other.is_known()) {
+            (true, true) => Span {
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            },
+            (true, false) => self,
+            _ => other,
+        }
+    }
+}
+
+impl chumsky::Span for Span {
+    type Context = ();
+    type Offset = Location;
+
+    fn new((): Self::Context, range: Range<Self::Offset>) -> Self {
+        Span {
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    fn context(&self) -> Self::Context {}
+
+    fn start(&self) -> Self::Offset {
+        self.start
+    }
+
+    fn end(&self) -> Self::Offset {
+        self.end
+    }
+}
+
+/// A node that knows which part of the source it was parsed from.
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+/// Comparison of nodes regardless of where they were parsed from, e.g. `a = 1` and ` a  =  1`.
+pub trait IgnoreSpans: VisitMut + Clone + PartialEq {
+    /// A copy of the node with every span reset to the default, see [`clear_spans`]
+    fn without_spans(&self) -> Self {
+        let mut node = self.clone();
+        clear_spans(&mut node);
+        node
+    }
+
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.without_spans() == other.without_spans()
+    }
+}
+
+impl<N: VisitMut + Clone + PartialEq> IgnoreSpans for N {}
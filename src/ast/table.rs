@@ -1,7 +1,32 @@
-use crate::ast::Identifier;
+use std::fmt::{Display, Formatter};
+
+use crate::ast::span::Span;
+use crate::ast::{NameResolution, ObjectName, ResolvedName};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
-    pub name: Identifier,
-    pub database: Option<Identifier>,
+    /// e.g. `tbl`, `db.tbl` or `catalog.schema.tbl`
+    pub name: ObjectName,
+    pub span: Span,
+}
+
+impl Table {
+    pub fn new(name: ObjectName) -> Self {
+        Table {
+            name,
+            span: Span::default(),
+        }
+    }
+
+    /// Interprets the table name as `[catalog.][schema.]table` following `rule`.
+    pub fn resolve(&self, rule: NameResolution) -> Option<ResolvedName> {
+        self.name.resolve(rule)
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
 }
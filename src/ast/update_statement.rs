@@ -1,16 +1,45 @@
+use std::fmt::{Display, Formatter};
+
 use crate::ast::column::Column;
 use crate::ast::expression::Expression;
+use crate::ast::span::Span;
 use crate::ast::table::Table;
-use std::collections::HashMap;
+use crate::ast::Separated;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateStatement {
     pub table: Table,
-    pub fields: Vec<SetFields>,
+    pub fields: Vec<Assignment>,
     pub where_clause: Option<Expression>,
+    pub span: Span,
+}
+
+/// A single `column = value` of the `SET` clause
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
+    pub column: Column,
+    pub value: Expression,
+}
+
+impl Display for UpdateStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "UPDATE {} SET {}",
+            self.table,
+            Separated(&self.fields, ", ")
+        )?;
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct SetFields {
-    pub fields: HashMap<Column, Expression>,
+impl Display for Assignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.column, self.value)
+    }
 }
@@ -0,0 +1,994 @@
+//! Name resolution of queries against a [`Catalog`].
+//!
+//! [`bind`] resolves every column reference of a query, its subqueries included, to a column of
+//! a table in the catalog or to an item of a select list, and expands `*` and `tbl.*`. A query
+//! sees the tables and subqueries of its `FROM` clause, named by their alias or else by their name
+//! as written or a shorter suffix of it. `ORDER BY` may also refer to the aliases of the select
+//! list, and `GROUP BY` and `HAVING` too when no column of the `FROM` clause has the name. The
+//! queries of `UNION` are bound on their own, the `ORDER BY` after them sees only the result
+//! columns as named by the first one.
+//!
+//! The queries of `WITH` are bound in order, each one seeing those before it. An unqualified table
+//! name in `FROM` refers to the nearest of them with that name before the catalog, in the query and
+//! its subqueries.
+//!
+//! The result borrows the query, its bindings are looked up by the address of the column
+//! reference rather than its span as nodes built in code share the empty span.
+//!
+//! ```
+//! use sql_parser::ast::Statement;
+//! use sql_parser::binder::{bind, Binding};
+//! use sql_parser::catalog::Catalog;
+//! use sql_parser::{parse_statement, ParseOptions};
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.load_script("CREATE TABLE users (id INT, name TEXT)", &ParseOptions::default());
+//!
+//! let Statement::Select(query) = parse_statement("SELECT * FROM users WHERE nme = 'x'").unwrap()
+//! else { unreachable!() };
+//! let bound = bind(&query, &catalog);
+//! assert_eq!(bound.output(&query).len(), 2);
+//! assert_eq!(bound.diagnostics[0].message, "column `nme` doesn't exist in any table of the FROM clause");
+//!
+//! let Binding::Column { column, .. } = &bound.output(&query)[1].binding else { panic!() };
+//! assert_eq!(column.sql_type, "TEXT");
+//! ```
+use std::collections::HashMap;
+
+use crate::ast::column::{Column, ColumnSpecification};
+use crate::ast::expression::{AggregationFunction, ColumnReference, Expression};
+use crate::ast::join::{JoinCondition, JoinType};
+use crate::ast::select_statement::{
+    CommonTableExpression, GroupBy, GroupingElement, SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::Span;
+use crate::ast::{Identifier, ObjectName};
+use crate::catalog::Catalog;
+use crate::diagnostic::Diagnostic;
+
+/// What a column reference stands for.
+#[derive(Debug, Clone, PartialEq)]
+// most bindings are columns, boxing them would only add an allocation each
+#[allow(clippy::large_enum_variant)]
+pub enum Binding<'q> {
+    /// A column of a table in the catalog
+    Column {
+        /// The table as named in `FROM`
+        table: ObjectName,
+        /// The alias `FROM` gives the table
+        alias: Option<Identifier>,
+        /// The table, subquery or query of `WITH` of `FROM` the column is read through, by
+        /// address, so that two uses of the same table differ
+        relation: usize,
+        column: ColumnSpecification,
+        /// The schema lets the column hold `NULL`, see [`crate::catalog::TableSchema::is_nullable`]
+        nullable: bool,
+        /// The table is on the optional side of an outer join, so the column may be `NULL` anyway
+        outer: bool,
+    },
+    /// A computed item of a select list, seen through a subquery in `FROM` or named by its alias.
+    /// Its own column references are bound too.
+    Expression {
+        expr: &'q Expression,
+        /// As for a column, `None` for an alias of the select list
+        relation: Option<usize>,
+        outer: bool,
+    },
+}
+
+/// A column of the result of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputColumn<'q> {
+    /// The alias, or the name of the column it selects, `None` for other unnamed items
+    pub name: Option<Identifier>,
+    pub binding: Binding<'q>,
+    /// For a column a wildcard stands for, the table of `FROM` it's read from by the name the
+    /// query gives it. `None` for the columns of subqueries and queries of `WITH`, and for the
+    /// result columns of a query.
+    pub relation: Option<ObjectName>,
+}
+
+/// The result of [`bind`].
+#[derive(Debug, Clone, Default)]
+pub struct Bound<'q> {
+    columns: HashMap<usize, Binding<'q>>,
+    wildcards: HashMap<usize, Vec<OutputColumn<'q>>>,
+    outputs: HashMap<usize, Vec<OutputColumn<'q>>>,
+    common_tables: HashMap<usize, &'q CommonTableExpression>,
+    /// Unknown tables and columns and ambiguous names, in source order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'q> Bound<'q> {
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+
+    /// The binding of a column reference, `None` when it couldn't be resolved.
+    pub fn column(&self, expr: &Expression) -> Option<&Binding<'q>> {
+        self.columns.get(&address(expr))
+    }
+
+    /// The columns a `*` or `tbl.*` stands for.
+    pub fn wildcard(&self, expr: &Expression) -> Option<&[OutputColumn<'q>]> {
+        self.wildcards.get(&address(expr)).map(Vec::as_slice)
+    }
+
+    /// The result columns of `query` or one of its subqueries, with wildcards expanded.
+    pub fn output(&self, query: &SelectStatement) -> &[OutputColumn<'q>] {
+        self.outputs.get(&address(query)).map_or(&[], Vec::as_slice)
+    }
+
+    /// The query of `WITH` a table of `FROM` refers to, `None` for a table of the catalog.
+    pub fn common_table(&self, table_ref: &TableReference) -> Option<&'q CommonTableExpression> {
+        self.common_tables.get(&address(table_ref)).copied()
+    }
+}
+
+/// The key of a node of the query, its address.
+pub(crate) fn address<T>(node: &T) -> usize {
+    node as *const T as usize
+}
+
+/// Resolves the names of `query` against `catalog`.
+pub fn bind<'q>(query: &'q SelectStatement, catalog: &Catalog) -> Bound<'q> {
+    let mut binder = Binder {
+        catalog,
+        bound: Bound::default(),
+        common_tables: Vec::new(),
+    };
+    binder.query(query);
+    let mut bound = binder.bound;
+    bound
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.start);
+    bound
+}
+
+/// A table or subquery of a `FROM` clause.
+struct Relation<'q> {
+    /// The alias, or else the table as written
+    name: Option<ObjectName>,
+    /// `None` for a table missing from the catalog, its columns aren't known then
+    columns: Option<Vec<OutputColumn<'q>>>,
+}
+
+impl<'q> Relation<'q> {
+    fn column(&self, name: &Identifier) -> Option<&OutputColumn<'q>> {
+        self.columns
+            .iter()
+            .flatten()
+            .find(|column| column.name.as_ref().is_some_and(|n| n.matches(name)))
+    }
+
+    fn has_column(&self, name: &Identifier) -> bool {
+        self.columns.is_none() || self.column(name).is_some()
+    }
+}
+
+/// The relations a query sees.
+#[derive(Default)]
+struct Scope<'q> {
+    relations: Vec<Relation<'q>>,
+    /// The columns joined by `USING`, an unqualified one is the column of the left side
+    using: Vec<Identifier>,
+}
+
+/// Where an expression is, deciding whether select list aliases are visible.
+#[derive(Clone, Copy, PartialEq)]
+enum Clause {
+    /// Only the columns of the `FROM` clause e.g. in `WHERE`
+    From,
+    /// The columns of the `FROM` clause, else the aliases e.g. in `GROUP BY`
+    FromThenAliases,
+    /// The aliases, else the columns of the `FROM` clause i.e. in `ORDER BY`
+    AliasesThenFrom,
+    /// Only the result columns i.e. in the `ORDER BY` of a `UNION`
+    Union,
+}
+
+/// A query of `WITH` in scope.
+struct CommonTable<'q> {
+    cte: &'q CommonTableExpression,
+    columns: Vec<OutputColumn<'q>>,
+}
+
+struct Binder<'a, 'q> {
+    catalog: &'a Catalog,
+    bound: Bound<'q>,
+    /// The queries of `WITH` in scope, the nearest last
+    common_tables: Vec<CommonTable<'q>>,
+}
+
+impl<'q> Binder<'_, 'q> {
+    fn error(&mut self, span: Span, message: String) {
+        self.bound.diagnostics.push(Diagnostic::new(span, message));
+    }
+
+    /// Binds `query`, returning its result columns.
+    fn query(&mut self, query: &'q SelectStatement) -> Vec<OutputColumn<'q>> {
+        let outer_common_tables = self.common_tables.len();
+        for cte in &query.with {
+            self.common_table_expression(cte, outer_common_tables);
+        }
+
+        let errors = self.bound.diagnostics.len();
+        let (scope, output) = self.select_clause(&query.body);
+        let body_is_bound = self.bound.diagnostics.len() == errors;
+        for union in &query.unions {
+            let errors = self.bound.diagnostics.len();
+            let (_, columns) = self.select_clause(&union.clause);
+            // a column that isn't bound is missing from the output, the counts can't be compared
+            let is_bound = body_is_bound && self.bound.diagnostics.len() == errors;
+            if is_bound && columns.len() != output.len() {
+                self.error(
+                    union.span,
+                    format!(
+                        "each query of UNION must return the same number of columns, this one returns {} instead of {}",
+                        columns.len(),
+                        output.len()
+                    ),
+                );
+            }
+        }
+        if query.unions.is_empty() {
+            for order_by in &query.order_by {
+                self.expr(
+                    &order_by.expr,
+                    &scope,
+                    &query.body.projection,
+                    Clause::AliasesThenFrom,
+                );
+            }
+        } else {
+            // the rows of a UNION only have its result columns, named by the first query
+            let scope = Scope {
+                relations: vec![Relation {
+                    name: None,
+                    columns: Some(output.clone()),
+                }],
+                using: vec![],
+            };
+            for order_by in &query.order_by {
+                self.expr(&order_by.expr, &scope, &[], Clause::Union);
+            }
+        }
+
+        self.bound.outputs.insert(address(query), output.clone());
+        self.common_tables.truncate(outer_common_tables);
+        output
+    }
+
+    /// Binds a select clause, returning the relations of its `FROM` clause and its result columns.
+    fn select_clause(&mut self, body: &'q SelectClause) -> (Scope<'q>, Vec<OutputColumn<'q>>) {
+        let mut scope = Scope::default();
+        if let Some(from) = &body.from {
+            self.table_ref(from, false, &mut scope);
+        }
+
+        let mut output = Vec::new();
+        for item in &body.projection {
+            self.expr(item, &scope, &[], Clause::From);
+            output.extend(self.output_columns(item));
+        }
+
+        if let Some(where_exp) = &body.where_exp {
+            self.expr(where_exp, &scope, &[], Clause::From);
+        }
+        if let Some(GroupBy::Elements(elements)) = &body.group_by {
+            for element in elements {
+                if let GroupingElement::Ordinal(n, span) = element {
+                    if *n == 0 || *n as usize > body.projection.len() {
+                        self.error(
+                            *span,
+                            format!(
+                                "`GROUP BY {}` is out of range, the select list has {} items",
+                                n,
+                                body.projection.len()
+                            ),
+                        );
+                    }
+                }
+                for expr in element.exprs() {
+                    self.expr(expr, &scope, &body.projection, Clause::FromThenAliases);
+                }
+            }
+        }
+        if let Some(having) = &body.having {
+            self.expr(having, &scope, &body.projection, Clause::FromThenAliases);
+        }
+        (scope, output)
+    }
+
+    /// Binds a query of `WITH` and brings it in scope, `siblings` being the index of the first
+    /// query of the same `WITH`.
+    fn common_table_expression(&mut self, cte: &'q CommonTableExpression, siblings: usize) {
+        if self.common_tables[siblings..]
+            .iter()
+            .any(|common| common.cte.name.matches(&cte.name))
+        {
+            self.error(
+                cte.name.span,
+                format!("`{}` is defined more than once in WITH", cte.name),
+            );
+        }
+        let mut columns = self.query(&cte.query);
+        if !cte.columns.is_empty() {
+            if cte.columns.len() != columns.len() {
+                self.error(
+                    cte.span,
+                    format!(
+                        "`{}` names {} columns but its query returns {}",
+                        cte.name,
+                        cte.columns.len(),
+                        columns.len()
+                    ),
+                );
+            }
+            for (column, name) in columns.iter_mut().zip(&cte.columns) {
+                column.name = Some(name.clone());
+            }
+        }
+        self.common_tables.push(CommonTable { cte, columns });
+    }
+
+    /// The result columns of a select list item, once its column references are bound.
+    fn output_columns(&self, item: &'q Expression) -> Vec<OutputColumn<'q>> {
+        match item {
+            Expression::ColumnReference(ColumnReference::Column(column), _) => {
+                match self.bound.columns.get(&address(item)) {
+                    Some(binding) => vec![OutputColumn {
+                        name: Some(column.column.clone()),
+                        binding: binding.clone(),
+                        relation: None,
+                    }],
+                    None => vec![],
+                }
+            }
+            Expression::ColumnReference(_, _) => self
+                .bound
+                .wildcards
+                .get(&address(item))
+                .cloned()
+                .unwrap_or_default(),
+            Expression::Alias { expr, alias, .. } => {
+                vec![OutputColumn {
+                    name: Some(alias.clone()),
+                    binding: self.alias_binding(expr),
+                    relation: None,
+                }]
+            }
+            _ => vec![OutputColumn {
+                name: None,
+                binding: Binding::Expression {
+                    expr: item,
+                    relation: None,
+                    outer: false,
+                },
+                relation: None,
+            }],
+        }
+    }
+
+    /// Adds the relations of `table_ref` to `scope`, binding the join conditions.
+    fn table_ref(&mut self, table_ref: &'q TableReference, outer: bool, scope: &mut Scope<'q>) {
+        match table_ref {
+            TableReference::BaseRelation { table, alias, .. } => {
+                let common_table = match table.name.qualifier() {
+                    [] => self
+                        .common_tables
+                        .iter()
+                        .rev()
+                        .find(|common| common.cte.name.matches(table.name.name())),
+                    _ => None,
+                };
+                let name = alias
+                    .clone()
+                    .map_or_else(|| table.name.clone(), ObjectName::from);
+                let columns = if let Some(common) = common_table {
+                    self.bound
+                        .common_tables
+                        .insert(address(table_ref), common.cte);
+                    let columns = common.columns.iter().map(|column| OutputColumn {
+                        name: column.name.clone(),
+                        binding: read_through(column.binding.clone(), table_ref, outer),
+                        relation: None,
+                    });
+                    Some(columns.collect())
+                } else {
+                    match self.catalog.table(&table.name) {
+                        Some(schema) => Some(
+                            schema
+                                .columns
+                                .iter()
+                                .map(|spec| OutputColumn {
+                                    name: Some(spec.name().clone()),
+                                    binding: Binding::Column {
+                                        table: table.name.clone(),
+                                        alias: alias.clone(),
+                                        relation: address(table_ref),
+                                        column: spec.clone(),
+                                        nullable: schema.is_nullable(spec.name()),
+                                        outer,
+                                    },
+                                    relation: Some(name.clone()),
+                                })
+                                .collect(),
+                        ),
+                        None => {
+                            self.error(table.span, format!("table `{}` doesn't exist", table));
+                            None
+                        }
+                    }
+                };
+                scope.relations.push(Relation {
+                    name: Some(name),
+                    columns,
+                });
+            }
+            TableReference::SubQuery {
+                subquery, alias, ..
+            } => {
+                let columns = self
+                    .query(subquery)
+                    .into_iter()
+                    .map(|column| OutputColumn {
+                        binding: read_through(column.binding, table_ref, outer),
+                        relation: None,
+                        ..column
+                    })
+                    .collect();
+                scope.relations.push(Relation {
+                    name: alias.clone().map(ObjectName::from),
+                    columns: Some(columns),
+                });
+            }
+            TableReference::Join {
+                left,
+                op,
+                right,
+                cond,
+                ..
+            } => {
+                let left_outer =
+                    outer || matches!(op, JoinType::RightJoin | JoinType::FullOuterJoin);
+                let right_outer =
+                    outer || matches!(op, JoinType::LeftJoin | JoinType::FullOuterJoin);
+                self.table_ref(left, left_outer, scope);
+                let left_count = scope.relations.len();
+                self.table_ref(right, right_outer, scope);
+                match cond {
+                    JoinCondition::Using(names) => {
+                        for name in names {
+                            let (left, right) = scope.relations.split_at(left_count);
+                            let sides = [("left", left), ("right", right)];
+                            for (side, relations) in sides {
+                                if !relations.iter().any(|relation| relation.has_column(name)) {
+                                    self.error(
+                                        name.span,
+                                        format!(
+                                            "column `{}` doesn't exist on the {} side of the join",
+                                            name, side
+                                        ),
+                                    );
+                                }
+                            }
+                            scope.using.push(name.clone());
+                        }
+                    }
+                    JoinCondition::On(conditions) => {
+                        for condition in conditions {
+                            self.expr(condition, scope, &[], Clause::From);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn expr(
+        &mut self,
+        expr: &'q Expression,
+        scope: &Scope<'q>,
+        aliases: &'q [Expression],
+        clause: Clause,
+    ) {
+        match expr {
+            Expression::ColumnReference(ColumnReference::Column(column), span) => {
+                if let Some(binding) = self.column(column, *span, scope, aliases, clause) {
+                    self.bound.columns.insert(address(expr), binding);
+                }
+            }
+            Expression::ColumnReference(ColumnReference::Wildcard, span) => {
+                if scope.relations.is_empty() {
+                    self.error(*span, "`*` needs a FROM clause".to_string());
+                    return;
+                }
+                let columns = scope
+                    .relations
+                    .iter()
+                    .flat_map(|relation| relation.columns.iter().flatten().cloned())
+                    .collect();
+                self.bound.wildcards.insert(address(expr), columns);
+            }
+            Expression::ColumnReference(ColumnReference::QualifiedWildcard(qualifier), span) => {
+                if let Some(relation) = self.relation(qualifier, *span, scope) {
+                    let columns = relation.columns.iter().flatten().cloned().collect();
+                    self.bound.wildcards.insert(address(expr), columns);
+                }
+            }
+            // `COUNT(*)` counts rows, it doesn't select columns
+            Expression::FunctionExpression {
+                func: AggregationFunction::Count,
+                args,
+                ..
+            } if matches!(
+                args.as_slice(),
+                [Expression::ColumnReference(ColumnReference::Wildcard, _)]
+            ) => {}
+            Expression::Alias { expr, .. } | Expression::UnaryExpr { expr, .. } => {
+                self.expr(expr, scope, aliases, clause)
+            }
+            Expression::BinaryExpr { left, right, .. } => {
+                self.expr(left, scope, aliases, clause);
+                self.expr(right, scope, aliases, clause);
+            }
+            Expression::FunctionExpression { args, .. } | Expression::Function { args, .. } => {
+                for arg in args {
+                    self.expr(arg, scope, aliases, clause);
+                }
+            }
+            Expression::In { left, right, .. } => {
+                self.expr(left, scope, aliases, clause);
+                for expr in right {
+                    self.expr(expr, scope, aliases, clause);
+                }
+            }
+            Expression::Literal(..)
+            | Expression::Parameter(..)
+            | Expression::Niladic(..)
+            | Expression::Error(_) => {}
+        }
+    }
+
+    /// The relation of `scope` named by `qualifier`, reporting it when there's none or several.
+    fn relation<'s>(
+        &mut self,
+        qualifier: &ObjectName,
+        span: Span,
+        scope: &'s Scope<'q>,
+    ) -> Option<&'s Relation<'q>> {
+        let mut matching = scope.relations.iter().filter(|relation| {
+            relation
+                .name
+                .as_ref()
+                .is_some_and(|name| name.ends_with(qualifier))
+        });
+        match (matching.next(), matching.next()) {
+            (Some(relation), None) => Some(relation),
+            (Some(_), Some(_)) => {
+                self.error(
+                    span,
+                    format!(
+                        "`{}` is ambiguous, it names more than one relation of the FROM clause",
+                        qualifier
+                    ),
+                );
+                None
+            }
+            (None, _) => {
+                self.error(
+                    span,
+                    format!("`{}` isn't a table or alias of the FROM clause", qualifier),
+                );
+                None
+            }
+        }
+    }
+
+    fn column(
+        &mut self,
+        column: &Column,
+        span: Span,
+        scope: &Scope<'q>,
+        aliases: &'q [Expression],
+        clause: Clause,
+    ) -> Option<Binding<'q>> {
+        let name = &column.column;
+        if let Some(qualifier) = &column.table {
+            let relation = self.relation(qualifier, span, scope)?;
+            return match (&relation.columns, relation.column(name)) {
+                (_, Some(output)) => Some(output.binding.clone()),
+                // the table itself was reported
+                (None, None) => None,
+                (Some(_), None) => {
+                    self.error(
+                        span,
+                        format!("column `{}` doesn't exist in `{}`", name, qualifier),
+                    );
+                    None
+                }
+            };
+        }
+
+        let alias = aliases.iter().find_map(|item| match item {
+            Expression::Alias { expr, alias, .. } if alias.matches(name) => Some(expr),
+            _ => None,
+        });
+        if let (Some(expr), Clause::AliasesThenFrom) = (alias, clause) {
+            return Some(self.alias_binding(expr));
+        }
+
+        let candidates = scope
+            .relations
+            .iter()
+            .filter(|relation| relation.has_column(name))
+            .collect::<Vec<_>>();
+        let joined = scope.using.iter().any(|using| using.matches(name));
+        match candidates.as_slice() {
+            [] => match alias {
+                Some(expr) if clause != Clause::From => Some(self.alias_binding(expr)),
+                _ => {
+                    let message = match scope.relations.is_empty() {
+                        _ if clause == Clause::Union => {
+                            format!("column `{}` isn't a result column of the UNION", name)
+                        }
+                        true => format!("column `{}` doesn't exist", name),
+                        false => format!(
+                            "column `{}` doesn't exist in any table of the FROM clause",
+                            name
+                        ),
+                    };
+                    self.error(span, message);
+                    None
+                }
+            },
+            [relation, ..] if candidates.len() == 1 || joined => {
+                relation.column(name).map(|output| output.binding.clone())
+            }
+            _ => {
+                if candidates.iter().all(|relation| relation.columns.is_some()) {
+                    let names = candidates
+                        .iter()
+                        .map(|relation| match &relation.name {
+                            Some(name) => format!("`{}`", name),
+                            None => "a subquery".to_string(),
+                        })
+                        .collect::<Vec<_>>();
+                    self.error(
+                        span,
+                        format!(
+                            "column `{}` is ambiguous, it could belong to {}",
+                            name,
+                            names.join(" or ")
+                        ),
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// The binding of an aliased select list item, that of the column it selects if any.
+    fn alias_binding(&self, expr: &'q Expression) -> Binding<'q> {
+        let column = match expr {
+            Expression::ColumnReference(ColumnReference::Column(_), _) => {
+                self.bound.columns.get(&address(expr)).cloned()
+            }
+            _ => None,
+        };
+        column.unwrap_or(Binding::Expression {
+            expr,
+            relation: None,
+            outer: false,
+        })
+    }
+}
+
+/// The binding of a column of a subquery or query of `WITH` as seen from the query using it.
+fn read_through<'q>(binding: Binding<'q>, table_ref: &TableReference, outer: bool) -> Binding<'q> {
+    let relation = address(table_ref);
+    match binding {
+        Binding::Column {
+            table,
+            alias,
+            column,
+            nullable,
+            outer: inner,
+            ..
+        } => Binding::Column {
+            table,
+            alias,
+            relation,
+            column,
+            nullable,
+            outer: outer || inner,
+        },
+        Binding::Expression {
+            expr, outer: inner, ..
+        } => Binding::Expression {
+            expr,
+            relation: Some(relation),
+            outer: outer || inner,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::query;
+
+    fn catalog() -> Catalog {
+        crate::testing::catalog(
+            "CREATE TABLE users (id INT PRIMARY KEY, name TEXT, team_id INT);
+             CREATE TABLE teams (id INT PRIMARY KEY, name TEXT);
+             CREATE TABLE shop.orders (id INT, user_id INT, total DECIMAL(10, 2))",
+        )
+    }
+
+    fn errors(sql: &str) -> Vec<String> {
+        let query = query(sql);
+        let bound = bind(&query, &catalog());
+        bound.diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    /// The output columns as `name: table.column`, or `name: expr` for computed ones.
+    fn output(sql: &str) -> Vec<String> {
+        let query = query(sql);
+        let bound = bind(&query, &catalog());
+        assert!(!bound.has_errors(), "{:?}", bound.diagnostics);
+        bound
+            .output(&query)
+            .iter()
+            .map(|column| {
+                let name = column
+                    .name
+                    .as_ref()
+                    .map_or("?".to_string(), Identifier::to_string);
+                let source = match &column.binding {
+                    Binding::Column {
+                        table,
+                        alias,
+                        column,
+                        outer,
+                        ..
+                    } => {
+                        let outer = if *outer { " (outer)" } else { "" };
+                        let table = alias.as_ref().map_or(table.to_string(), |a| a.to_string());
+                        format!("{}.{}{}", table, column.name(), outer)
+                    }
+                    Binding::Expression { expr, .. } => expr.to_string(),
+                };
+                format!("{}: {}", name, source)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bind_columns() {
+        assert_eq!(
+            output(
+                "SELECT users.id, name, orders.total * 2 AS double \
+                 FROM users JOIN shop.orders ON user_id = users.id"
+            ),
+            [
+                "id: users.id",
+                "name: users.name",
+                "double: orders.total * 2"
+            ]
+        );
+        // a function written without parentheses, not a column
+        assert_eq!(
+            output("SELECT CURRENT_TIMESTAMP AS now FROM users"),
+            ["now: CURRENT_TIMESTAMP"]
+        );
+    }
+
+    #[test]
+    fn test_expand_wildcards() {
+        assert_eq!(
+            output("SELECT teams.*, * FROM teams LEFT JOIN users ON team_id = teams.id"),
+            [
+                "id: teams.id",
+                "name: teams.name",
+                "id: teams.id",
+                "name: teams.name",
+                "id: users.id (outer)",
+                "name: users.name (outer)",
+                "team_id: users.team_id (outer)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_subqueries() {
+        let sql = "SELECT s.n, total FROM (SELECT name AS n, COUNT(*) AS total FROM users GROUP BY name) AS s";
+        assert_eq!(output(sql), ["n: users.name", "total: COUNT(*)"]);
+
+        let query = query(sql);
+        let bound = bind(&query, &catalog());
+        let TableReference::SubQuery { subquery, .. } = query.body.from.as_ref().unwrap() else {
+            panic!("not a subquery")
+        };
+        assert_eq!(bound.output(subquery).len(), 2);
+    }
+
+    #[test]
+    fn test_bind_aliases() {
+        assert_eq!(
+            output("SELECT name AS id, COUNT(*) AS n FROM users GROUP BY name HAVING n > 1 ORDER BY id"),
+            ["id: users.name", "n: COUNT(*)"]
+        );
+        let query = query("SELECT name AS id FROM users GROUP BY id ORDER BY id");
+        let bound = bind(&query, &catalog());
+        let group_by = match &query.body.group_by {
+            Some(GroupBy::Elements(elements)) => match &elements[0] {
+                GroupingElement::Expr(expr) => expr,
+                _ => panic!("not an expression"),
+            },
+            _ => panic!("no GROUP BY"),
+        };
+        // `GROUP BY` prefers the column, `ORDER BY` the alias
+        let column_name = |binding: Option<&Binding>| match binding {
+            Some(Binding::Column { column, .. }) => column.name().to_string(),
+            binding => panic!("{:?}", binding),
+        };
+        assert_eq!(column_name(bound.column(group_by)), "id");
+        assert_eq!(column_name(bound.column(&query.order_by[0].expr)), "name");
+    }
+
+    #[test]
+    fn test_using_join() {
+        assert_eq!(
+            output("SELECT id FROM users JOIN teams USING (id)"),
+            ["id: users.id"]
+        );
+        assert_eq!(
+            errors("SELECT id FROM users JOIN shop.orders USING (total)"),
+            [
+                "column `id` is ambiguous, it could belong to `users` or `shop.orders`",
+                "column `total` doesn't exist on the left side of the join"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        // the columns of an unknown table aren't reported
+        assert_eq!(
+            errors("SELECT nope, x.id, teams.nope FROM missing JOIN teams ON missing.a = 1"),
+            [
+                "`x` isn't a table or alias of the FROM clause",
+                "column `nope` doesn't exist in `teams`",
+                "table `missing` doesn't exist"
+            ]
+        );
+        assert_eq!(
+            errors("SELECT name, id FROM users JOIN teams ON team_id = teams.id WHERE total > 1"),
+            [
+                "column `name` is ambiguous, it could belong to `users` or `teams`",
+                "column `id` is ambiguous, it could belong to `users` or `teams`",
+                "column `total` doesn't exist in any table of the FROM clause"
+            ]
+        );
+        assert_eq!(
+            errors("SELECT users.id, *, a FROM users JOIN users ON TRUE GROUP BY 4"),
+            [
+                "`users` is ambiguous, it names more than one relation of the FROM clause",
+                "column `a` doesn't exist in any table of the FROM clause",
+                "`GROUP BY 4` is out of range, the select list has 3 items"
+            ]
+        );
+        // reported at the ordinal itself
+        let sql = "SELECT id FROM users GROUP BY 1, 2";
+        let query = query(sql);
+        let bound = bind(&query, &catalog());
+        assert_eq!(&sql[bound.diagnostics[0].span.clone()], "2");
+        assert_eq!(
+            errors("SELECT *, name AS n FROM teams WHERE n = 'x'"),
+            ["column `n` doesn't exist in any table of the FROM clause"]
+        );
+        assert_eq!(
+            errors("SELECT *, a"),
+            ["`*` needs a FROM clause", "column `a` doesn't exist"]
+        );
+    }
+
+    #[test]
+    fn test_bind_unions() {
+        // the first query names the columns, `ORDER BY` sees only those
+        assert_eq!(
+            output(
+                "SELECT id, name AS n FROM users UNION ALL SELECT id, name FROM teams ORDER BY n, 1"
+            ),
+            ["id: users.id", "n: users.name"]
+        );
+        assert_eq!(
+            errors(
+                "SELECT id FROM users UNION SELECT id, name FROM teams UNION SELECT total FROM teams \
+                 ORDER BY name, users.id"
+            ),
+            [
+                "each query of UNION must return the same number of columns, this one returns 2 instead of 1",
+                "column `total` doesn't exist in any table of the FROM clause",
+                "column `name` isn't a result column of the UNION",
+                "`users` isn't a table or alias of the FROM clause"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_table_aliases() {
+        assert_eq!(
+            output("SELECT u.name, m.name AS manager FROM users u LEFT JOIN users AS m ON m.id = u.team_id"),
+            ["name: u.name", "manager: m.name (outer)"]
+        );
+        let query = query("SELECT o.total FROM shop.orders AS o");
+        let bound = bind(&query, &catalog());
+        let Binding::Column { table, alias, .. } = &bound.output(&query)[0].binding else {
+            panic!("not a column")
+        };
+        assert_eq!(table.to_string(), "shop.orders");
+        assert_eq!(alias.as_ref().unwrap().to_string(), "o");
+
+        // the alias hides the name of the table
+        assert_eq!(
+            errors("SELECT users.id FROM users AS u"),
+            ["`users` isn't a table or alias of the FROM clause"]
+        );
+    }
+
+    #[test]
+    fn test_bind_common_table_expressions() {
+        assert_eq!(
+            output(
+                "WITH named AS (SELECT id, name FROM users WHERE name <> ''), \
+                 counted (n, total) AS (SELECT name, COUNT(*) FROM named GROUP BY name) \
+                 SELECT c.n, total, named.id FROM counted c JOIN named ON named.name = c.n"
+            ),
+            ["n: users.name", "total: COUNT(*)", "id: users.id"]
+        );
+        // seen in subqueries, and before a table of the catalog with the same name
+        assert_eq!(
+            output(
+                "WITH teams AS (SELECT name AS label FROM users) \
+                 SELECT s.label FROM (SELECT * FROM teams) AS s"
+            ),
+            ["label: users.name"]
+        );
+
+        let query =
+            query("WITH t AS (SELECT id FROM users) SELECT * FROM t CROSS JOIN teams ON TRUE");
+        let bound = bind(&query, &catalog());
+        let Some(TableReference::Join { left, right, .. }) = &query.body.from else {
+            panic!("not a join")
+        };
+        assert_eq!(bound.common_table(left).unwrap().name.to_string(), "t");
+        assert!(bound.common_table(right).is_none());
+    }
+
+    #[test]
+    fn test_common_table_expression_errors() {
+        assert_eq!(
+            errors(
+                "WITH a (x, y) AS (SELECT id FROM users), a AS (SELECT 1) \
+                 SELECT * FROM b CROSS JOIN a ON TRUE"
+            ),
+            [
+                "`a` names 2 columns but its query returns 1",
+                "`a` is defined more than once in WITH",
+                "table `b` doesn't exist"
+            ]
+        );
+        // out of scope after its statement, or before it's defined
+        assert_eq!(
+            errors("SELECT * FROM (WITH a AS (SELECT 1 AS x) SELECT x FROM a) AS s CROSS JOIN a ON TRUE"),
+            ["table `a` doesn't exist"]
+        );
+        assert_eq!(
+            errors("WITH a AS (SELECT * FROM b), b AS (SELECT 1) SELECT * FROM a"),
+            ["table `b` doesn't exist"]
+        );
+    }
+}
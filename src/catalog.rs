@@ -0,0 +1,536 @@
+//! An in-memory schema built by applying `CREATE TABLE`, `ALTER TABLE` and `DROP TABLE`
+//! statements in order.
+//!
+//! Tables are grouped into databases by the qualifier of their name, e.g. `shop.orders` belongs
+//! to the database `shop` while unqualified names share the database named `None`. With the
+//! `serde` feature the catalog implements `Serialize` and `Deserialize` in the shape of the
+//! syntax tree, see [`crate::ast`].
+//!
+//! ```
+//! use sql_parser::ast::{Identifier, ObjectName};
+//! use sql_parser::catalog::Catalog;
+//! use sql_parser::ParseOptions;
+//!
+//! let mut catalog = Catalog::new();
+//! let diagnostics = catalog.load_script(
+//!     "CREATE TABLE shop.orders (id INT PRIMARY KEY, total DECIMAL(10, 2));
+//!      ALTER TABLE shop.orders ADD COLUMN note TEXT",
+//!     &ParseOptions::default(),
+//! );
+//! assert!(diagnostics.is_empty());
+//!
+//! let name = ObjectName::qualified([Identifier::new("shop")], Identifier::new("orders"));
+//! let orders = catalog.table(&name).unwrap();
+//! assert_eq!(orders.columns.len(), 3);
+//! assert!(!orders.is_nullable(&Identifier::new("id")));
+//! ```
+
+// a failed statement is reported by one diagnostic, as everywhere else, rather than a box of it
+#![allow(clippy::result_large_err)]
+use std::path::{Path, PathBuf};
+
+use crate::ast::alter_statement::{AlterStatement, AlterTableOperation};
+use crate::ast::column::ColumnSpecification;
+use crate::ast::create_statement::{CreateStatement, TableConstraint};
+use crate::ast::span::Spanned;
+use crate::ast::table::Table;
+use crate::ast::{Identifier, ObjectName, Statement};
+use crate::diagnostic::Diagnostic;
+use crate::{parse_script_with, ParseOptions};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Catalog {
+    /// In order of their first table
+    pub databases: Vec<Database>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Database {
+    /// The qualifier of its table names, `None` for unqualified names
+    pub name: Option<ObjectName>,
+    /// In order of creation
+    pub tables: Vec<TableSchema>,
+}
+
+/// A table as left by the statements applied so far.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableSchema {
+    pub name: Identifier,
+    pub columns: Vec<ColumnSpecification>,
+    pub constraints: Vec<TableConstraint>,
+}
+
+impl TableSchema {
+    pub fn column(&self, name: &Identifier) -> Option<&ColumnSpecification> {
+        self.columns.iter().find(|spec| spec.name().matches(name))
+    }
+
+    /// The columns of the primary key, declared either on a column or as a table constraint.
+    pub fn primary_key(&self) -> Vec<&Identifier> {
+        let columns = self
+            .columns
+            .iter()
+            .filter(|spec| spec.is_primary_key())
+            .map(ColumnSpecification::name);
+        let constraints = self
+            .constraints
+            .iter()
+            .filter(|constraint| matches!(constraint, TableConstraint::PrimaryKey(_)))
+            .flat_map(TableConstraint::columns);
+        columns.chain(constraints).collect()
+    }
+
+    /// Whether the column may hold `NULL`, i.e. it's neither `NOT NULL` nor part of the primary key.
+    pub fn is_nullable(&self, column: &Identifier) -> bool {
+        let not_null = self
+            .column(column)
+            .is_some_and(ColumnSpecification::is_not_null);
+        !not_null && !self.primary_key().iter().any(|key| key.matches(column))
+    }
+
+    fn column_index(&self, name: &Identifier) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|spec| spec.name().matches(name))
+    }
+
+    /// Checks that `spec` can be added, its name must be new.
+    fn check_new_column(&self, spec: &ColumnSpecification) -> Result<(), Diagnostic> {
+        match self.column(spec.name()) {
+            Some(_) => Err(Diagnostic::new(
+                spec.name().span,
+                format!("column `{}` already exists in `{}`", spec.name(), self.name),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn check_constraint(&self, constraint: &TableConstraint) -> Result<(), Diagnostic> {
+        for column in constraint.columns() {
+            if self.column(column).is_none() {
+                return Err(self.unknown_column(column));
+            }
+        }
+        Ok(())
+    }
+
+    fn unknown_column(&self, column: &Identifier) -> Diagnostic {
+        Diagnostic::new(
+            column.span,
+            format!("column `{}` doesn't exist in `{}`", column, self.name),
+        )
+    }
+
+    fn apply(&mut self, operation: &AlterTableOperation) -> Result<(), Diagnostic> {
+        match operation {
+            AlterTableOperation::AddColumn(spec) => {
+                self.check_new_column(spec)?;
+                self.columns.push(spec.clone());
+            }
+            AlterTableOperation::DropColumn { column, if_exists } => {
+                match self.column_index(column) {
+                    Some(index) => {
+                        self.columns.remove(index);
+                    }
+                    None if *if_exists => return Ok(()),
+                    None => return Err(self.unknown_column(column)),
+                }
+                // a key without one of its columns would be a different key
+                self.constraints.retain(|constraint| {
+                    !constraint.columns().iter().any(|name| name.matches(column))
+                });
+            }
+            AlterTableOperation::RenameColumn { from, to } => {
+                let index = self
+                    .column_index(from)
+                    .ok_or_else(|| self.unknown_column(from))?;
+                if self
+                    .column(to)
+                    .is_some_and(|spec| !spec.name().matches(from))
+                {
+                    return Err(Diagnostic::new(
+                        to.span,
+                        format!("column `{}` already exists in `{}`", to, self.name),
+                    ));
+                }
+                self.columns[index].column.column = to.clone();
+                for constraint in &mut self.constraints {
+                    for name in constraint.columns_mut() {
+                        if name.matches(from) {
+                            *name = to.clone();
+                        }
+                    }
+                }
+            }
+            // moving the table is up to the catalog
+            AlterTableOperation::RenameTable(_) => {}
+        }
+        Ok(())
+    }
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// The database of the tables qualified by `name`, `None` for unqualified tables.
+    pub fn database(&self, name: Option<&ObjectName>) -> Option<&Database> {
+        self.databases
+            .iter()
+            .find(|database| same_database(database.name.as_ref(), name))
+    }
+
+    /// Looks up a table by its name as written e.g. `shop.orders`.
+    pub fn table(&self, name: &ObjectName) -> Option<&TableSchema> {
+        let (database, table) = split(name);
+        self.database(database.as_ref())?
+            .tables
+            .iter()
+            .find(|schema| schema.name.matches(table))
+    }
+
+    /// Applies a `CREATE TABLE`, `ALTER TABLE` or `DROP TABLE`, any other statement is ignored.
+    ///
+    /// A statement that can't be applied, e.g. creating a table that exists, leaves the catalog
+    /// unchanged.
+    pub fn apply(&mut self, stmt: &Statement) -> Result<(), Diagnostic> {
+        match stmt {
+            Statement::Create(create) => self.create(create),
+            Statement::Alter(alter) => self.alter(alter),
+            Statement::Drop(drop) => match self.remove(&drop.table) {
+                Some(_) => Ok(()),
+                None if drop.if_exists => Ok(()),
+                None => Err(unknown_table(&drop.table)),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses `sql` and applies its statements in order, returning every problem found.
+    pub fn load_script(&mut self, sql: &str, options: &ParseOptions) -> Vec<Diagnostic> {
+        let script = parse_script_with(sql, options);
+        let mut diagnostics = script.diagnostics;
+        for stmt in &script.statements {
+            if let Err(diagnostic) = self.apply(stmt) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+        diagnostics
+    }
+
+    /// Loads the `.sql` files under `dir` in order of their path, see [`Catalog::load_script`].
+    ///
+    /// Returns the problems found by file, leaving out the files without any.
+    pub fn load_dir(
+        &mut self,
+        dir: &Path,
+        options: &ParseOptions,
+    ) -> std::io::Result<Vec<(PathBuf, Vec<Diagnostic>)>> {
+        let mut files = Vec::new();
+        sql_files(dir, &mut files)?;
+        let mut problems = Vec::new();
+        for path in files {
+            let sql = std::fs::read_to_string(&path)?;
+            let diagnostics = self.load_script(&sql, options);
+            if !diagnostics.is_empty() {
+                problems.push((path, diagnostics));
+            }
+        }
+        Ok(problems)
+    }
+
+    fn create(&mut self, create: &CreateStatement) -> Result<(), Diagnostic> {
+        if self.table(&create.table.name).is_some() {
+            return match create.if_not_exists {
+                true => Ok(()),
+                false => Err(Diagnostic::new(
+                    create.table.span,
+                    format!("table `{}` already exists", create.table),
+                )),
+            };
+        }
+
+        let (_, name) = split(&create.table.name);
+        let mut schema = TableSchema {
+            name: name.clone(),
+            columns: Vec::new(),
+            constraints: Vec::new(),
+        };
+        for spec in &create.columns {
+            schema.check_new_column(spec)?;
+            schema.columns.push(spec.clone());
+        }
+        for constraint in &create.constraints {
+            schema.check_constraint(constraint)?;
+        }
+        schema.constraints = create.constraints.clone();
+        self.insert(&create.table.name, schema);
+        Ok(())
+    }
+
+    fn alter(&mut self, alter: &AlterStatement) -> Result<(), Diagnostic> {
+        let table = self
+            .table(&alter.table.name)
+            .ok_or_else(|| unknown_table(&alter.table))?;
+        let mut schema = table.clone();
+        let (database, _) = split(&alter.table.name);
+        let mut name = alter.table.name.clone();
+        for operation in &alter.operations {
+            schema.apply(operation)?;
+            if let AlterTableOperation::RenameTable(new_name) = operation {
+                // an unqualified name keeps the table in its database
+                name = match (new_name.qualifier(), &database) {
+                    ([], Some(database)) => {
+                        ObjectName::qualified(database.parts().to_vec(), new_name.name().clone())
+                    }
+                    _ => new_name.clone(),
+                };
+                // renaming a table to its own name changes nothing
+                if self
+                    .table(&name)
+                    .is_some_and(|existing| !std::ptr::eq(existing, table))
+                {
+                    return Err(Diagnostic::new(
+                        new_name.span(),
+                        format!("table `{}` already exists", new_name),
+                    ));
+                }
+                schema.name = new_name.name().clone();
+            }
+        }
+        self.remove(&alter.table);
+        self.insert(&name, schema);
+        Ok(())
+    }
+
+    fn insert(&mut self, name: &ObjectName, schema: TableSchema) {
+        let (database, _) = split(name);
+        let index = match self
+            .databases
+            .iter()
+            .position(|db| same_database(db.name.as_ref(), database.as_ref()))
+        {
+            Some(index) => index,
+            None => {
+                self.databases.push(Database {
+                    name: database,
+                    tables: Vec::new(),
+                });
+                self.databases.len() - 1
+            }
+        };
+        self.databases[index].tables.push(schema);
+    }
+
+    /// Removes a table, and its database once empty.
+    fn remove(&mut self, table: &Table) -> Option<TableSchema> {
+        let (database, name) = split(&table.name);
+        let index = self
+            .databases
+            .iter()
+            .position(|db| same_database(db.name.as_ref(), database.as_ref()))?;
+        let tables = &mut self.databases[index].tables;
+        let schema = tables.remove(tables.iter().position(|schema| schema.name.matches(name))?);
+        if tables.is_empty() {
+            self.databases.remove(index);
+        }
+        Some(schema)
+    }
+}
+
+/// Splits a table name into the name of its database and its own.
+fn split(name: &ObjectName) -> (Option<ObjectName>, &Identifier) {
+    (
+        ObjectName::new(name.qualifier().iter().cloned()),
+        name.name(),
+    )
+}
+
+fn same_database(a: Option<&ObjectName>, b: Option<&ObjectName>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.matches(b),
+        _ => false,
+    }
+}
+
+fn unknown_table(table: &Table) -> Diagnostic {
+    Diagnostic::new(table.span, format!("table `{}` doesn't exist", table))
+}
+
+/// Adds the `.sql` files under `dir` to `files`, walking entries in order of their name.
+fn sql_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            sql_files(&path, files)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "sql") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+
+    fn load(sql: &str) -> (Catalog, Vec<String>) {
+        let mut catalog = Catalog::new();
+        let diagnostics = catalog.load_script(sql, &ParseOptions::default());
+        let messages = diagnostics.into_iter().map(|d| d.message).collect();
+        (catalog, messages)
+    }
+
+    fn name(name: &str) -> ObjectName {
+        ObjectName::new(name.split('.').map(Identifier::new)).unwrap()
+    }
+
+    fn columns(catalog: &Catalog, table: &str) -> Vec<String> {
+        let schema = catalog.table(&name(table)).unwrap();
+        schema.columns.iter().map(|spec| spec.to_string()).collect()
+    }
+
+    #[test]
+    fn test_create_tables() {
+        let (catalog, messages) = load(
+            "CREATE TABLE users (id INT NOT NULL, name TEXT, PRIMARY KEY (id));
+             CREATE TABLE shop.orders (id INT PRIMARY KEY, user_id INT);
+             SELECT * FROM users;
+             CREATE TABLE Shop.Items (id INT)",
+        );
+        assert!(messages.is_empty());
+        assert_eq!(catalog.databases.len(), 2);
+        assert_eq!(catalog.databases[0].name, None);
+        assert_eq!(catalog.databases[1].tables.len(), 2);
+
+        let users = catalog.table(&name("USERS")).unwrap();
+        let primary_key = users.primary_key().into_iter().cloned().collect::<Vec<_>>();
+        assert_eq!(primary_key.without_spans(), [Identifier::new("id")]);
+        assert!(users.is_nullable(&Identifier::new("name")));
+        // by the table constraint alone
+        assert!(!users.is_nullable(&Identifier::new("id")));
+        assert!(catalog.table(&name("shop.items")).is_some());
+        assert!(catalog.table(&name("orders")).is_none());
+    }
+
+    #[test]
+    fn test_alter_tables() {
+        let (catalog, messages) = load(
+            "CREATE TABLE t (a INT, b TEXT, UNIQUE (a), UNIQUE (a, b));
+             ALTER TABLE t ADD COLUMN c DATE NOT NULL, DROP COLUMN b, RENAME COLUMN a TO x;
+             ALTER TABLE t RENAME TO t;
+             ALTER TABLE t RENAME TO u;
+             CREATE TABLE db.v (a INT);
+             ALTER TABLE db.v RENAME TO w",
+        );
+        assert!(messages.is_empty());
+        assert!(catalog.table(&name("t")).is_none());
+        assert_eq!(columns(&catalog, "u"), ["x INT", "c DATE NOT NULL"]);
+        assert_eq!(
+            catalog
+                .table(&name("u"))
+                .unwrap()
+                .constraints
+                .without_spans(),
+            [TableConstraint::Unique(vec![Identifier::new("x")])]
+        );
+        assert!(catalog.table(&name("db.w")).is_some());
+    }
+
+    #[test]
+    fn test_drop_column_of_composite_key() {
+        let (catalog, messages) = load(
+            "CREATE TABLE t (a INT, b INT, c INT NOT NULL, PRIMARY KEY (a, b), UNIQUE (c));
+             ALTER TABLE t DROP COLUMN b",
+        );
+        assert!(messages.is_empty());
+        let t = catalog.table(&name("t")).unwrap();
+        // the rest of the key is neither unique nor NOT NULL on its own
+        assert!(t.primary_key().is_empty());
+        assert!(t.is_nullable(&Identifier::new("a")));
+        assert_eq!(
+            t.constraints.without_spans(),
+            [TableConstraint::Unique(vec![Identifier::new("c")])]
+        );
+    }
+
+    #[test]
+    fn test_drop_tables() {
+        let (catalog, messages) = load(
+            "CREATE TABLE db.t (a INT); CREATE TABLE u (a INT);
+             DROP TABLE db.t; DROP TABLE IF EXISTS db.t",
+        );
+        assert!(messages.is_empty());
+        assert_eq!(catalog.databases.len(), 1);
+        assert!(catalog.database(Some(&name("db"))).is_none());
+    }
+
+    #[test]
+    fn test_invalid_ddl() {
+        let (catalog, messages) = load(
+            "CREATE TABLE t (a INT, a TEXT);
+             CREATE TABLE t (a INT, PRIMARY KEY (b));
+             CREATE TABLE t (a INT);
+             CREATE TABLE T (b INT);
+             CREATE TABLE IF NOT EXISTS t (b INT);
+             ALTER TABLE t ADD COLUMN b INT, ADD COLUMN A INT;
+             ALTER TABLE t DROP COLUMN x;
+             ALTER TABLE missing RENAME TO t;
+             DROP TABLE missing",
+        );
+        assert_eq!(
+            messages,
+            [
+                "column `a` already exists in `t`",
+                "column `b` doesn't exist in `t`",
+                "table `T` already exists",
+                "column `A` already exists in `t`",
+                "column `x` doesn't exist in `t`",
+                "table `missing` doesn't exist",
+                "table `missing` doesn't exist",
+            ]
+        );
+        // a failed statement is applied all or nothing
+        assert_eq!(columns(&catalog, "t"), ["a INT"]);
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("sql-parser-catalog-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("2")).unwrap();
+        std::fs::write(dir.join("1.sql"), "CREATE TABLE t (a INT)").unwrap();
+        std::fs::write(
+            dir.join("2/alter.sql"),
+            "ALTER TABLE t ADD b INT; DROP TABLE x",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "DROP TABLE t").unwrap();
+
+        let mut catalog = Catalog::new();
+        let problems = catalog.load_dir(&dir, &ParseOptions::default()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(columns(&catalog, "t"), ["a INT", "b INT"]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].0, dir.join("2/alter.sql"));
+        assert_eq!(problems[0].1[0].message, "table `x` doesn't exist");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let (catalog, _) = load("CREATE TABLE db.t (a INT NOT NULL DEFAULT 1, PRIMARY KEY (a))");
+        let json = serde_json::to_string(&catalog).unwrap();
+        assert_eq!(serde_json::from_str::<Catalog>(&json).unwrap(), catalog);
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+pub use crate::ast::span::Location;
+use crate::ast::span::Span;
+use crate::parser::{SyntaxError, SyntaxErrorReason};
+
+/// A message attached to a part of the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// A problem found in the SQL text, with enough context to point the user at it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// The byte range of the offending input
+    pub span: Range<usize>,
+    /// Where `span` starts
+    pub location: Location,
+    /// Descriptions of the input that would have been accepted instead
+    pub expected: Vec<String>,
+    /// The offending input, `None` at the end of input
+    pub found: Option<String>,
+    /// The first label points at `span`, any others give extra context
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    /// A diagnostic for a problem found in a parsed tree rather than by the parser.
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Diagnostic {
+            location: span.start,
+            span: span.range(),
+            expected: Vec::new(),
+            found: None,
+            labels: vec![Label {
+                span: span.range(),
+                message: message.clone(),
+            }],
+            message,
+        }
+    }
+
+    pub fn from_syntax_error(error: &SyntaxError) -> Self {
+        let location = error.span().start;
+        let span = error.span().range();
+        let found = error.found().map(str::to_string);
+        let found_description = match &found {
+            Some(found) => format!("`{}`", found),
+            None => "end of input".to_string(),
+        };
+
+        // keep `end of input` last, it reads better after the tokens
+        let (mut expected, end): (Vec<_>, Vec<_>) = error
+            .expected()
+            .map(str::to_string)
+            .partition(|e| e != "end of input");
+        expected.extend(end);
+
+        let (message, labels) = match error.reason() {
+            SyntaxErrorReason::Custom(message) => (
+                message.clone(),
+                vec![Label {
+                    span: span.clone(),
+                    message: message.clone(),
+                }],
+            ),
+            SyntaxErrorReason::Unclosed {
+                span: unclosed,
+                delimiter,
+            } => (
+                format!("unclosed delimiter `{}`", delimiter),
+                vec![
+                    Label {
+                        span: span.clone(),
+                        message: format!("expected {}", one_of(&expected)),
+                    },
+                    Label {
+                        span: unclosed.range(),
+                        message: "unclosed delimiter".to_string(),
+                    },
+                ],
+            ),
+            SyntaxErrorReason::Unexpected => {
+                let message = if expected.is_empty() {
+                    format!("unexpected {}", found_description)
+                } else {
+                    format!(
+                        "unexpected {}, expected {}",
+                        found_description,
+                        one_of(&expected)
+                    )
+                };
+                let label = Label {
+                    span: span.clone(),
+                    message: format!("unexpected {}", found_description),
+                };
+                (message, vec![label])
+            }
+        };
+
+        Diagnostic {
+            message,
+            location,
+            span,
+            expected,
+            found,
+            labels,
+        }
+    }
+
+    /// Renders the diagnostic with the offending lines of `source` and carets under each label.
+    ///
+    /// ```text
+    /// error: unexpected `where`, expected table name
+    ///  --> 1:15
+    ///   |
+    /// 1 | SELECT a FROM where
+    ///   |               ^^^^^ unexpected `where`
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        self.render_source(None, source)
+    }
+
+    /// Renders the diagnostic like [`Diagnostic::render`], with the location prefixed by the
+    /// `name` of the source e.g. `--> queries/report.sql:1:15`.
+    pub fn render_named(&self, name: &str, source: &str) -> String {
+        self.render_source(Some(name), source)
+    }
+
+    fn render_source(&self, name: Option<&str>, source: &str) -> String {
+        let mut labels = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| (Location::of(source, label.span.start), i == 0, label))
+            .collect::<Vec<_>>();
+        labels.sort_by_key(|(location, _, _)| *location);
+
+        let gutter = labels
+            .iter()
+            .map(|(location, _, _)| location.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let blank = " ".repeat(gutter);
+
+        let origin = match name {
+            Some(name) => format!("{}:{}", name, self.location),
+            None => self.location.to_string(),
+        };
+        let mut out = format!(
+            "error: {}\n{}--> {}\n{} |\n",
+            self.message, blank, origin, blank
+        );
+        let mut printed_line = None;
+        for (location, primary, label) in labels {
+            let line_start = source[..label.span.start.min(source.len())]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            let line = source[line_start..].lines().next().unwrap_or("");
+
+            if printed_line != Some(location.line) {
+                out.push_str(&format!("{:>gutter$} | {}\n", location.line, line));
+                printed_line = Some(location.line);
+            }
+
+            // keep tabs so the carets line up with the source
+            let indent = line
+                .chars()
+                .take(location.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            let line_end = line_start + line.len();
+            let width = source
+                .get(label.span.start..label.span.end.min(line_end))
+                .map_or(0, |s| s.chars().count())
+                .max(1);
+            let marker = if primary { "^" } else { "-" };
+            out.push_str(&format!(
+                "{} | {}{} {}\n",
+                blank,
+                indent,
+                marker.repeat(width),
+                label.message
+            ));
+        }
+        out
+    }
+}
+
+fn one_of(expected: &[String]) -> String {
+    match expected {
+        [] => "nothing".to_string(),
+        [one] => one.clone(),
+        [init @ .., last] => format!("one of {} or {}", init.join(", "), last),
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_statement;
+
+    #[test]
+    fn test_location() {
+        let source = "SELECT a\nFROM  é, b";
+        assert_eq!(Location::of(source, 0), Location::new(0, 1, 1));
+        assert_eq!(Location::of(source, 9), Location::new(9, 2, 1));
+        // `é` is two bytes but one column
+        assert_eq!(Location::of(source, 17), Location::new(17, 2, 8));
+    }
+
+    #[test]
+    fn test_render_reserved_word() {
+        let source = "SELECT a\nFROM where";
+        let error = parse_statement(source).unwrap_err();
+        let expected = "\
+error: `where` is a reserved keyword and cannot be used as an identifier unless quoted
+ --> 2:6
+  |
+2 | FROM where
+  |      ^^^^^ `where` is a reserved keyword and cannot be used as an identifier unless quoted
+";
+        assert_eq!(error.diagnostics()[0].render(source), expected);
+
+        let named = error.diagnostics()[0].render_named("report.sql", source);
+        assert!(named.contains(" --> report.sql:2:6\n"));
+    }
+
+    #[test]
+    fn test_expected_and_found() {
+        let error = parse_statement("SELECT a +").unwrap_err();
+        let diagnostic = &error.diagnostics()[0];
+        assert_eq!(
+            diagnostic.message,
+            "unexpected end of input, expected expression"
+        );
+        assert_eq!(diagnostic.location, Location::new(10, 1, 11));
+        assert_eq!(diagnostic.found, None);
+
+        let error = parse_statement("SELECT a FROM t ORDER a").unwrap_err();
+        let diagnostic = &error.diagnostics()[0];
+        assert_eq!(diagnostic.expected, vec!["BY".to_string()]);
+        assert_eq!(diagnostic.found.as_deref(), Some("a"));
+        assert_eq!(diagnostic.span, 22..23);
+    }
+
+    #[test]
+    fn test_render_multiline_span() {
+        let source = "DROP TABLE
+";
+        let rendered = parse_statement(source).unwrap_err().render(source);
+        assert_eq!(
+            rendered,
+            "\
+error: unexpected end of input, expected one of IF or table name
+ --> 2:1
+  |
+2 | 
+  | ^ unexpected end of input
+"
+        );
+    }
+}
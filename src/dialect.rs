@@ -0,0 +1,411 @@
+//! The SQL flavours the lexer and parsers understand.
+//!
+//! A [`Dialect`] decides how identifiers and strings are quoted, which words are reserved and which
+//! operators, row limiting clauses and statements are accepted. [`GenericDialect`] accepts the union
+//! of them all and is the default, pick another through [`crate::ParseOptions::dialect`].
+//!
+//! ```
+//! use sql_parser::dialect::MySqlDialect;
+//! use sql_parser::{parse_script_with, ParseOptions};
+//!
+//! let options = ParseOptions { dialect: &MySqlDialect, ..ParseOptions::default() };
+//! let script = parse_script_with(r"SELECT `name` FROM t WHERE a = 'it\'s' LIMIT 10, 5", &options);
+//! assert!(!script.has_errors());
+//! ```
+use std::fmt::Debug;
+
+use crate::ast::Statement;
+use crate::parser::is_reserved_word;
+
+/// The rules of an SQL flavour, every hook but [`Dialect::name`] and
+/// [`Dialect::is_identifier_quote`] defaults to what [`GenericDialect`] accepts.
+pub trait Dialect: Debug + Send + Sync {
+    /// The name used in error messages e.g. `MySQL`
+    fn name(&self) -> &'static str;
+
+    /// Whether `quote` opens a quoted identifier, `quote` is one of `` ` ``, `"` or `[`
+    fn is_identifier_quote(&self, quote: char) -> bool;
+
+    /// Whether `quote` opens a string literal, `quote` is one of `'` or `"`
+    fn is_string_quote(&self, quote: char) -> bool {
+        quote == '\''
+    }
+
+    /// Whether a backslash escapes the next character of a string e.g. `'it\'s'`, doubling the
+    /// quote escapes it either way
+    fn backslash_escapes(&self) -> bool {
+        false
+    }
+
+    /// Whether `#` starts a comment running to the end of the line, as `--` does
+    fn hash_comments(&self) -> bool {
+        false
+    }
+
+    /// Whether `word`, in any letter case, can only be used as an identifier when quoted
+    fn is_reserved_word(&self, word: &str) -> bool {
+        is_reserved_word(word)
+    }
+
+    /// Whether `op` e.g. `!=` or `%` is an operator
+    fn supports_operator(&self, _op: &str) -> bool {
+        true
+    }
+
+    /// Whether `LIMIT count [OFFSET offset]` is accepted
+    fn supports_limit(&self) -> bool {
+        true
+    }
+
+    /// Whether `LIMIT offset, count` is accepted
+    fn supports_limit_comma(&self) -> bool {
+        true
+    }
+
+    /// Whether `OFFSET` is accepted without a `LIMIT` or `FETCH` to go with it
+    fn supports_offset_without_limit(&self) -> bool {
+        true
+    }
+
+    /// Whether `FETCH {FIRST | NEXT} ...` is accepted
+    fn supports_fetch(&self) -> bool {
+        true
+    }
+
+    /// Whether the parsed `stmt` is accepted, e.g. `DROP TABLE IF EXISTS` isn't standard
+    fn supports_statement(&self, _stmt: &Statement) -> bool {
+        true
+    }
+
+    /// Whether the built-in function `name`, in lower case, exists e.g. `ifnull` in MySQL. Calls
+    /// are parsed whatever the name, this guides [`crate::transpile`]
+    fn supports_function(&self, _name: &str) -> bool {
+        true
+    }
+
+    /// Whether an integer is read as a boolean where a condition is expected e.g. `WHERE 1`
+    fn integer_booleans(&self) -> bool {
+        true
+    }
+}
+
+/// Accepts every quoting style, operator and clause the parsers know.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenericDialect;
+
+impl Dialect for GenericDialect {
+    fn name(&self) -> &'static str {
+        "generic SQL"
+    }
+
+    fn is_identifier_quote(&self, _quote: char) -> bool {
+        true
+    }
+}
+
+/// Standard SQL: `"` quoted identifiers, `OFFSET`/`FETCH` rather than `LIMIT` and `<>` rather than `!=`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiDialect;
+
+impl Dialect for AnsiDialect {
+    fn name(&self) -> &'static str {
+        "ANSI SQL"
+    }
+
+    fn is_identifier_quote(&self, quote: char) -> bool {
+        quote == '"'
+    }
+
+    fn supports_operator(&self, op: &str) -> bool {
+        !matches!(op, "!=" | "!" | "%")
+    }
+
+    fn supports_limit(&self) -> bool {
+        false
+    }
+
+    fn supports_limit_comma(&self) -> bool {
+        false
+    }
+
+    fn supports_statement(&self, stmt: &Statement) -> bool {
+        match stmt {
+            Statement::Drop(drop) => !drop.if_exists,
+            Statement::Create(create) => !create.if_not_exists,
+            _ => true,
+        }
+    }
+
+    fn supports_function(&self, name: &str) -> bool {
+        !matches!(
+            name,
+            "getdate" | "ifnull" | "now" | "nvl" | "rand" | "random"
+        )
+    }
+
+    fn integer_booleans(&self) -> bool {
+        false
+    }
+}
+
+/// PostgreSQL: `"` quoted identifiers and `LIMIT count OFFSET offset` as well as `FETCH`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgreSqlDialect;
+
+impl Dialect for PostgreSqlDialect {
+    fn name(&self) -> &'static str {
+        "PostgreSQL"
+    }
+
+    fn is_identifier_quote(&self, quote: char) -> bool {
+        quote == '"'
+    }
+
+    fn is_reserved_word(&self, word: &str) -> bool {
+        let word = word.to_ascii_lowercase();
+        match word.as_str() {
+            "if" | "index" | "merge" | "rename" | "truncate" | "exists" => false,
+            "analyse" | "analyze" | "any" | "array" | "both" | "cast" | "check" | "collate"
+            | "column" | "constraint" | "default" | "do" | "foreign" | "grant" | "lateral"
+            | "leading" | "only" | "primary" | "references" | "returning" | "some" | "trailing"
+            | "unique" | "user" | "window" => true,
+            word => is_reserved_word(word),
+        }
+    }
+
+    fn supports_operator(&self, op: &str) -> bool {
+        op != "!"
+    }
+
+    fn supports_limit_comma(&self) -> bool {
+        false
+    }
+
+    fn supports_function(&self, name: &str) -> bool {
+        !matches!(name, "getdate" | "ifnull" | "nvl" | "rand")
+    }
+
+    fn integer_booleans(&self) -> bool {
+        false
+    }
+}
+
+/// MySQL: `` ` `` quoted identifiers, `"` quoted strings with backslash escapes, `#` comments and
+/// `LIMIT offset, count`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MySqlDialect;
+
+impl Dialect for MySqlDialect {
+    fn name(&self) -> &'static str {
+        "MySQL"
+    }
+
+    fn is_identifier_quote(&self, quote: char) -> bool {
+        quote == '`'
+    }
+
+    fn is_string_quote(&self, quote: char) -> bool {
+        quote == '\'' || quote == '"'
+    }
+
+    fn backslash_escapes(&self) -> bool {
+        true
+    }
+
+    fn hash_comments(&self) -> bool {
+        true
+    }
+
+    fn is_reserved_word(&self, word: &str) -> bool {
+        let word = word.to_ascii_lowercase();
+        match word.as_str() {
+            "full" | "merge" | "offset" | "truncate" => false,
+            "check" | "column" | "constraint" | "database" | "default" | "div" | "foreign"
+            | "ignore" | "interval" | "key" | "mod" | "primary" | "references" | "regexp"
+            | "replace" | "rlike" | "schema" | "show" | "unique" | "use" | "xor" => true,
+            word => is_reserved_word(word),
+        }
+    }
+
+    fn supports_fetch(&self) -> bool {
+        false
+    }
+
+    fn supports_offset_without_limit(&self) -> bool {
+        false
+    }
+
+    fn supports_function(&self, name: &str) -> bool {
+        !matches!(name, "getdate" | "nvl" | "random")
+    }
+}
+
+/// SQLite: every quoting style and `LIMIT offset, count`, but no `FETCH`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteDialect;
+
+impl Dialect for SqliteDialect {
+    fn name(&self) -> &'static str {
+        "SQLite"
+    }
+
+    fn is_identifier_quote(&self, _quote: char) -> bool {
+        true
+    }
+
+    fn is_reserved_word(&self, word: &str) -> bool {
+        let word = word.to_ascii_lowercase();
+        match word.as_str() {
+            "fetch" | "merge" | "truncate" => false,
+            "check" | "collate" | "constraint" | "default" | "foreign" | "glob" | "primary"
+            | "references" | "unique" => true,
+            word => is_reserved_word(word),
+        }
+    }
+
+    fn supports_operator(&self, op: &str) -> bool {
+        op != "!"
+    }
+
+    fn supports_fetch(&self) -> bool {
+        false
+    }
+
+    fn supports_offset_without_limit(&self) -> bool {
+        false
+    }
+
+    fn supports_function(&self, name: &str) -> bool {
+        !matches!(name, "getdate" | "now" | "nvl" | "rand")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::expression::{Expression, Literal};
+    use crate::{parse_script_with, ParseOptions};
+
+    fn parse(sql: &str, dialect: &'static dyn Dialect) -> Result<Vec<Statement>, Vec<String>> {
+        let options = ParseOptions {
+            dialect,
+            ..ParseOptions::default()
+        };
+        parse_script_with(sql, &options)
+            .into_result()
+            .map_err(|error| {
+                error
+                    .diagnostics()
+                    .iter()
+                    .map(|diagnostic| diagnostic.message.clone())
+                    .collect()
+            })
+    }
+
+    #[test]
+    fn test_identifier_quotes() {
+        assert!(parse("SELECT `a` FROM t", &MySqlDialect).is_ok());
+        assert!(parse("SELECT `a` FROM t", &PostgreSqlDialect).is_err());
+        assert!(parse("SELECT \"a\" FROM t", &AnsiDialect).is_ok());
+        assert!(parse("SELECT [a] FROM t", &AnsiDialect).is_err());
+        assert!(parse("SELECT [a], `b`, \"c\" FROM t", &SqliteDialect).is_ok());
+
+        // MySQL reads double quotes as a string
+        let stmts = parse("SELECT \"a\" FROM t", &MySqlDialect).unwrap();
+        let Statement::Select(select) = &stmts[0] else {
+            panic!("expected a select")
+        };
+        assert!(matches!(
+            &select.body.projection[0],
+            Expression::Literal(Literal::String(value), _) if value == "a"
+        ));
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let stmts = parse(r"SELECT 'it\'s\n' FROM t", &MySqlDialect).unwrap();
+        assert_eq!(stmts[0].to_string(), "SELECT 'it''s\n' FROM t");
+
+        let stmts = parse(r"SELECT 'C:\path' FROM t", &PostgreSqlDialect).unwrap();
+        assert_eq!(stmts[0].to_string(), r"SELECT 'C:\path' FROM t");
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        assert!(parse("SELECT offset FROM t", &MySqlDialect).is_ok());
+        assert!(parse("SELECT offset FROM t", &PostgreSqlDialect).is_err());
+        assert!(parse("SELECT returning FROM t", &MySqlDialect).is_ok());
+        assert!(parse("SELECT returning FROM t", &PostgreSqlDialect).is_err());
+        assert!(parse("SELECT `key` FROM t", &MySqlDialect).is_ok());
+        assert!(parse("SELECT key FROM t", &MySqlDialect).is_err());
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(parse("SELECT a FROM t WHERE a != 1", &MySqlDialect).is_ok());
+        assert_eq!(
+            parse("SELECT a FROM t WHERE a != 1", &AnsiDialect),
+            Err(vec!["`!=` isn't supported by ANSI SQL".to_string()])
+        );
+        assert!(parse("SELECT a % 2 FROM t", &PostgreSqlDialect).is_ok());
+        assert!(parse("SELECT !a FROM t", &PostgreSqlDialect).is_err());
+    }
+
+    #[test]
+    fn test_limit_syntax() {
+        let sql = "SELECT a FROM t LIMIT 5, 10";
+        assert!(parse(sql, &MySqlDialect).is_ok());
+        assert!(parse(sql, &SqliteDialect).is_ok());
+        assert_eq!(
+            parse(sql, &PostgreSqlDialect),
+            Err(vec![
+                "`LIMIT offset, count` isn't supported by PostgreSQL".to_string()
+            ])
+        );
+
+        let sql = "SELECT a FROM t LIMIT 10 OFFSET 5";
+        assert!(parse(sql, &PostgreSqlDialect).is_ok());
+        assert_eq!(
+            parse(sql, &AnsiDialect),
+            Err(vec!["`LIMIT` isn't supported by ANSI SQL".to_string()])
+        );
+
+        // `offset` isn't reserved in MySQL but isn't taken for an alias either
+        let sql = "SELECT a FROM t OFFSET 5";
+        assert!(parse(sql, &PostgreSqlDialect).is_ok());
+        assert_eq!(
+            parse(sql, &MySqlDialect),
+            Err(vec![
+                "`OFFSET` without `LIMIT` isn't supported by MySQL".to_string()
+            ])
+        );
+        assert!(parse("SELECT a FROM t LIMIT 1 OFFSET 5", &MySqlDialect).is_ok());
+        assert!(parse("SELECT a FROM t `offset` LIMIT 1", &MySqlDialect).is_ok());
+
+        let sql = "SELECT a FROM t OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY";
+        assert!(parse(sql, &AnsiDialect).is_ok());
+        assert!(parse(sql, &PostgreSqlDialect).is_ok());
+        assert!(parse(sql, &MySqlDialect).is_err());
+    }
+
+    #[test]
+    fn test_statements() {
+        assert!(parse("DROP TABLE IF EXISTS t", &MySqlDialect).is_ok());
+        assert!(parse("DROP TABLE t", &AnsiDialect).is_ok());
+        assert_eq!(
+            parse("DROP TABLE IF EXISTS t", &AnsiDialect),
+            Err(vec![
+                "this statement isn't supported by ANSI SQL".to_string()
+            ])
+        );
+        assert!(parse("CREATE TABLE t (a INT)", &AnsiDialect).is_ok());
+        assert!(parse("CREATE TABLE IF NOT EXISTS t (a INT)", &AnsiDialect).is_err());
+    }
+
+    #[test]
+    fn test_generic_accepts_everything() {
+        let sql = "SELECT `a`, \"b\", [c] FROM t WHERE a != 1 AND !b LIMIT 1, 2; \
+                   SELECT a FROM t FETCH FIRST ROW ONLY; DROP TABLE IF EXISTS t";
+        assert!(parse(sql, &GenericDialect).is_ok());
+    }
+}
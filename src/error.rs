@@ -0,0 +1,66 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::diagnostic::Diagnostic;
+use crate::parser::SyntaxError;
+
+/// The error returned when SQL text cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ParseError {
+    pub(crate) fn new(mut errors: Vec<SyntaxError>) -> Self {
+        errors.sort_by_key(|error| error.span().start);
+        ParseError {
+            diagnostics: errors.iter().map(Diagnostic::from_syntax_error).collect(),
+        }
+    }
+
+    pub(crate) fn from_diagnostics(diagnostics: Vec<Diagnostic>) -> Self {
+        ParseError { diagnostics }
+    }
+
+    pub(crate) fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// A human readable description of the first problem
+    pub fn message(&self) -> &str {
+        &self.first().message
+    }
+
+    /// The byte range of the input where the first problem was found
+    pub fn span(&self) -> Range<usize> {
+        self.first().span.clone()
+    }
+
+    /// Every problem found, in source order
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Renders every diagnostic against `source`, see [`Diagnostic::render`].
+    pub fn render(&self, source: &str) -> String {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(source))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn first(&self) -> &Diagnostic {
+        self.diagnostics
+            .first()
+            .expect("a parse error has at least one diagnostic")
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.first())
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,1159 @@
+//! A configurable SQL formatter in the spirit of pg_format and sqlfmt.
+//!
+//! Every clause starts a line of its own, and a clause that doesn't fit in
+//! [`FormatOptions::line_width`] is wrapped one item per line, conditions at their top level
+//! `AND` or `OR`.
+//!
+//! ```
+//! use sql_parser::format::{format, FormatOptions};
+//!
+//! let sql = format("select a, b from t where a = 1", &FormatOptions::default()).unwrap();
+//! assert_eq!(sql, "SELECT a, b\nFROM t\nWHERE a = 1;\n");
+//! ```
+use std::collections::HashSet;
+
+use crate::ast::alter_statement::{AlterStatement, AlterTableOperation};
+use crate::ast::column::{ColumnConstraint, ColumnSpecification};
+use crate::ast::comment::{Comment, CommentKind, Comments};
+use crate::ast::create_statement::{CreateStatement, TableConstraint};
+use crate::ast::delete_statement::DeleteStatement;
+use crate::ast::drop_statement::DropStatement;
+use crate::ast::expression::{BinOp, Expression, Literal, UnaryOp};
+use crate::ast::insert_statement::InsertStatement;
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{
+    CommonTableExpression, Fetch, GroupBy, GroupingElement, NullsOrder, Order, OrderBy,
+    SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::{Span, Spanned};
+use crate::ast::update_statement::{Assignment, UpdateStatement};
+use crate::ast::Statement;
+use crate::dialect::{Dialect, GenericDialect};
+use crate::{parse_script_with, ParseError, ParseOptions, Script};
+
+/// The letter case keywords are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+}
+
+/// Where the comma goes when a list is wrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommaStyle {
+    /// `a,` then `b` on the next line
+    Trailing,
+    /// `a` then `, b` on the next line
+    Leading,
+}
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// The case of keywords, function names and `NULL`, `TRUE` and `FALSE`
+    pub keyword_case: KeywordCase,
+    /// Spaces per level of indentation
+    pub indent: usize,
+    /// A clause longer than this is wrapped
+    pub line_width: usize,
+    pub commas: CommaStyle,
+    /// Line up the `AS` of aliased select items when the list is wrapped
+    pub align_aliases: bool,
+    /// Line up the `ON` and `USING` of joins when the `FROM` clause is wrapped
+    pub align_join_conditions: bool,
+    /// Keep the comments and optimizer hints of the source in [`format`]
+    pub preserve_comments: bool,
+    /// The SQL flavour [`format`] parses
+    pub dialect: &'static dyn Dialect,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            keyword_case: KeywordCase::Upper,
+            indent: 4,
+            line_width: 80,
+            commas: CommaStyle::Trailing,
+            align_aliases: false,
+            align_join_conditions: false,
+            preserve_comments: true,
+            dialect: &GenericDialect,
+        }
+    }
+}
+
+/// Formats a script of `;` separated statements, failing if it doesn't parse.
+///
+/// Each statement is terminated by `;` and separated from the next by a blank line. Comments are
+/// kept next to the node they were attached to, see [`Comments`], those that can't be placed
+/// there, e.g. inside an expression, are moved up to follow the comments before their statement.
+pub fn format(sql: &str, options: &FormatOptions) -> Result<String, ParseError> {
+    let parse_options = ParseOptions {
+        preserve_comments: options.preserve_comments,
+        dialect: options.dialect,
+    };
+    let Script {
+        statements,
+        diagnostics,
+        comments,
+    } = parse_script_with(sql, &parse_options);
+    if !diagnostics.is_empty() {
+        return Err(ParseError::from_diagnostics(diagnostics));
+    }
+
+    let mut printer = Printer::new(options, &comments);
+    let mut out = Vec::new();
+    for stmt in &statements {
+        printer.leading(0, stmt.span());
+        let leading = std::mem::take(&mut printer.lines);
+        printer.statement(stmt, 0);
+        let mut lines = std::mem::take(&mut printer.lines);
+        if let Some(last) = lines.last_mut() {
+            last.text.push(';');
+            let trailing = printer.trailing(stmt.span());
+            last.comment.push_str(&trailing);
+        }
+        // whatever couldn't be placed goes between the comments before the statement and the
+        // statement, in source order
+        printer.orphans(stmt.span().end.offset);
+        let orphans = std::mem::take(&mut printer.lines);
+        out.push(render(leading.into_iter().chain(orphans).chain(lines)));
+    }
+    printer.orphans(usize::MAX);
+    if !printer.lines.is_empty() {
+        out.push(render(printer.lines.drain(..)));
+    }
+
+    Ok(out.join("\n\n") + "\n")
+}
+
+/// Formats a single statement, without a trailing `;`.
+pub fn format_statement(stmt: &Statement, options: &FormatOptions) -> String {
+    let comments = Comments::default();
+    let mut printer = Printer::new(options, &comments);
+    printer.statement(stmt, 0);
+    render(printer.lines.drain(..))
+}
+
+fn render(lines: impl Iterator<Item = Line>) -> String {
+    lines
+        .map(|line| line.text + &line.comment)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A line of output, its trailing comments are kept apart so a `,` or `;` can still be added.
+struct Line {
+    text: String,
+    comment: String,
+}
+
+/// An entry of a wrapped list, e.g. a select item, with its alias kept apart for alignment.
+struct Item {
+    text: String,
+    alias: Option<String>,
+    /// Where the comments of the item are looked up
+    span: Span,
+}
+
+impl Item {
+    fn new(text: String, span: Span) -> Self {
+        Item {
+            text,
+            alias: None,
+            span,
+        }
+    }
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    comments: &'a Comments,
+    /// The start offsets of the comments already printed
+    printed: HashSet<usize>,
+    lines: Vec<Line>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(options: &'a FormatOptions, comments: &'a Comments) -> Self {
+        Printer {
+            options,
+            comments,
+            printed: HashSet::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    fn kw(&self, keyword: &str) -> String {
+        match self.options.keyword_case {
+            KeywordCase::Upper => keyword.to_uppercase(),
+            KeywordCase::Lower => keyword.to_lowercase(),
+        }
+    }
+
+    fn line(&mut self, depth: usize, text: impl AsRef<str>) {
+        self.lines.push(Line {
+            text: format!("{}{}", self.indentation(depth), text.as_ref()),
+            comment: String::new(),
+        })
+    }
+
+    fn indentation(&self, depth: usize) -> String {
+        " ".repeat(depth * self.options.indent)
+    }
+
+    /// Whether `text` fits on a line at `depth`.
+    fn fits(&self, depth: usize, text: &str) -> bool {
+        depth * self.options.indent + text.chars().count() <= self.options.line_width
+    }
+
+    /// Whether there are comments to print around the node at `span`, it can't share a line then.
+    fn commented(&self, span: Span) -> bool {
+        let unprinted = |comments: &[Comment]| {
+            comments
+                .iter()
+                .any(|comment| !self.printed.contains(&comment.span.start.offset))
+        };
+        unprinted(self.comments.leading(span)) || unprinted(self.comments.trailing(span))
+    }
+
+    /// Prints the comments before the node at `span` on lines of their own.
+    fn leading(&mut self, depth: usize, span: Span) {
+        for comment in self.comments.leading(span) {
+            if self.printed.insert(comment.span.start.offset) {
+                self.line(depth, &comment.text);
+            }
+        }
+    }
+
+    /// The comments after the node at `span`, to go at the end of its line.
+    fn trailing(&mut self, span: Span) -> String {
+        let mut text = String::new();
+        for comment in self.comments.trailing(span) {
+            if self.printed.insert(comment.span.start.offset) {
+                text.push(' ');
+                text.push_str(&comment.text);
+            }
+        }
+        text
+    }
+
+    /// Prints the comments before `offset` that haven't been printed yet.
+    fn orphans(&mut self, offset: usize) {
+        let orphans = self
+            .comments
+            .iter()
+            .filter(|comment| {
+                comment.span.start.offset < offset
+                    && !self.printed.contains(&comment.span.start.offset)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for comment in orphans {
+            self.printed.insert(comment.span.start.offset);
+            self.line(0, &comment.text);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement, depth: usize) {
+        self.leading(depth, stmt.span());
+        match stmt {
+            Statement::Select(stmt) => self.select(stmt, depth),
+            Statement::Insert(stmt) => self.insert(stmt, depth),
+            Statement::Update(stmt) => self.update(stmt, depth),
+            Statement::Delete(stmt) => self.delete(stmt, depth),
+            Statement::Drop(stmt) => self.drop(stmt, depth),
+            Statement::Create(stmt) => self.create(stmt, depth),
+            Statement::Alter(stmt) => self.alter(stmt, depth),
+            Statement::Error(_) => self.line(depth, stmt.to_string()),
+        }
+    }
+
+    fn select(&mut self, stmt: &SelectStatement, depth: usize) {
+        for (i, cte) in stmt.with.iter().enumerate() {
+            self.common_table_expression(depth, cte, i == 0, i + 1 == stmt.with.len());
+        }
+        self.select_clause(depth, &stmt.body);
+        for union in &stmt.unions {
+            let keyword = if union.all { "UNION ALL" } else { "UNION" };
+            self.line(depth, self.kw(keyword));
+            self.select_clause(depth, &union.clause);
+        }
+
+        if !stmt.order_by.is_empty() {
+            let items = stmt
+                .order_by
+                .iter()
+                .map(|order_by| Item::new(self.order_by(order_by), order_by.expr.span()))
+                .collect();
+            self.list(depth, self.kw("ORDER BY"), items, false);
+        }
+        if let Some(limit) = &stmt.limit {
+            self.line(depth, format!("{} {}", self.kw("LIMIT"), self.expr(limit)));
+        }
+        if let Some(offset) = &stmt.offset {
+            let mut line = format!("{} {}", self.kw("OFFSET"), self.expr(offset));
+            if stmt.fetch.is_some() {
+                line = format!("{} {}", line, self.kw("ROWS"));
+            }
+            self.line(depth, line);
+        }
+        if let Some(fetch) = &stmt.fetch {
+            self.line(depth, self.fetch(fetch));
+        }
+    }
+
+    fn select_clause(&mut self, depth: usize, body: &SelectClause) {
+        let mut select = self.kw(if body.distinct {
+            "SELECT DISTINCT"
+        } else {
+            "SELECT"
+        });
+        // hints stay right after the keyword they apply to
+        if let Some(first) = body.projection.first() {
+            for hint in self.comments.leading(first.span()) {
+                if hint.kind() == CommentKind::Hint && self.printed.insert(hint.span.start.offset) {
+                    select = format!("{} {}", select, hint.text);
+                }
+            }
+        }
+        let items = body.projection.iter().map(|e| self.item(e)).collect();
+        self.list(depth, select, items, self.options.align_aliases);
+
+        if let Some(from) = &body.from {
+            self.from(depth, from);
+        }
+        if let Some(where_exp) = &body.where_exp {
+            self.condition(depth, "WHERE", where_exp);
+        }
+        match &body.group_by {
+            Some(GroupBy::All) => self.line(depth, self.kw("GROUP BY ALL")),
+            Some(GroupBy::Elements(elements)) => {
+                let items = elements
+                    .iter()
+                    .map(|element| {
+                        Item::new(self.grouping_element(element), grouping_span(element))
+                    })
+                    .collect();
+                self.list(depth, self.kw("GROUP BY"), items, false);
+            }
+            None => {}
+        }
+        if let Some(having) = &body.having {
+            self.condition(depth, "HAVING", having);
+        }
+    }
+
+    /// Prints a query of `WITH` on a line, or as a block when it's too long or commented.
+    fn common_table_expression(
+        &mut self,
+        depth: usize,
+        cte: &CommonTableExpression,
+        first: bool,
+        last: bool,
+    ) {
+        let mut head = match first {
+            true => format!("{} {}", self.kw("WITH"), cte.name),
+            false => cte.name.to_string(),
+        };
+        if !cte.columns.is_empty() {
+            let columns = cte
+                .columns
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>();
+            head = format!("{} ({})", head, columns.join(", "));
+        }
+        let head = format!("{} {} (", head, self.kw("AS"));
+        let comma = if last { "" } else { "," };
+        let inline = format!("{}{}){}", head, self.select_inline(&cte.query), comma);
+        let commented = self
+            .comments
+            .iter()
+            .any(|comment| cte.query.span.range().contains(&comment.span.start.offset));
+        if !commented && self.fits(depth, &inline) {
+            self.line(depth, inline);
+        } else {
+            self.line(depth, head);
+            self.select(&cte.query, depth + 1);
+            self.line(depth, format!("){}", comma));
+        }
+    }
+
+    fn insert(&mut self, stmt: &InsertStatement, depth: usize) {
+        let mut into = format!("{} {}", self.kw("INSERT INTO"), stmt.table);
+        if let Some(fields) = &stmt.fields {
+            let fields = fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+            into = format!("{} ({})", into, fields.join(", "));
+        }
+        self.line(depth, into);
+
+        let rows = stmt
+            .data
+            .iter()
+            .map(|row| {
+                let span = row
+                    .iter()
+                    .fold(Span::default(), |span, value| span.union(value.span()));
+                Item::new(format!("({})", self.exprs(row)), span)
+            })
+            .collect();
+        self.list(depth, self.kw("VALUES"), rows, false);
+    }
+
+    fn update(&mut self, stmt: &UpdateStatement, depth: usize) {
+        self.line(depth, format!("{} {}", self.kw("UPDATE"), stmt.table));
+        let assignments = stmt
+            .fields
+            .iter()
+            .map(|assignment| {
+                let text = format!("{} = {}", assignment.column, self.expr(&assignment.value));
+                Item::new(text, assignment_span(assignment))
+            })
+            .collect();
+        self.list(depth, self.kw("SET"), assignments, false);
+        if let Some(where_clause) = &stmt.where_clause {
+            self.condition(depth, "WHERE", where_clause);
+        }
+    }
+
+    fn delete(&mut self, stmt: &DeleteStatement, depth: usize) {
+        self.line(depth, format!("{} {}", self.kw("DELETE FROM"), stmt.table));
+        if let Some(where_clause) = &stmt.where_clause {
+            self.condition(depth, "WHERE", where_clause);
+        }
+    }
+
+    fn drop(&mut self, stmt: &DropStatement, depth: usize) {
+        let drop = if stmt.if_exists {
+            "DROP TABLE IF EXISTS"
+        } else {
+            "DROP TABLE"
+        };
+        self.line(depth, format!("{} {}", self.kw(drop), stmt.table));
+    }
+
+    /// Prints the columns and constraints one per line.
+    fn create(&mut self, stmt: &CreateStatement, depth: usize) {
+        let create = if stmt.if_not_exists {
+            "CREATE TABLE IF NOT EXISTS"
+        } else {
+            "CREATE TABLE"
+        };
+        self.line(depth, format!("{} {} (", self.kw(create), stmt.table));
+
+        let columns = stmt
+            .columns
+            .iter()
+            .map(|spec| (self.column_spec(spec), spec.name().span));
+        let constraints = stmt
+            .constraints
+            .iter()
+            .map(|constraint| (self.table_constraint(constraint), Span::default()));
+        let elements = columns.chain(constraints).collect::<Vec<_>>();
+        let count = elements.len();
+        for (i, (mut text, span)) in elements.into_iter().enumerate() {
+            self.leading(depth + 1, span);
+            match self.options.commas {
+                CommaStyle::Leading if i > 0 => text = format!(", {}", text),
+                CommaStyle::Trailing if i + 1 < count => text.push(','),
+                _ => {}
+            }
+            self.line(depth + 1, text);
+            let comment = self.trailing(span);
+            self.last_line().comment = comment;
+        }
+        self.line(depth, ")");
+    }
+
+    fn alter(&mut self, stmt: &AlterStatement, depth: usize) {
+        let operations = stmt
+            .operations
+            .iter()
+            .map(|operation| Item::new(self.alter_operation(operation), Span::default()))
+            .collect();
+        let keyword = format!("{} {}", self.kw("ALTER TABLE"), stmt.table);
+        self.list(depth, keyword, operations, false);
+    }
+
+    fn alter_operation(&self, operation: &AlterTableOperation) -> String {
+        match operation {
+            AlterTableOperation::AddColumn(spec) => {
+                format!("{} {}", self.kw("ADD COLUMN"), self.column_spec(spec))
+            }
+            AlterTableOperation::DropColumn { column, if_exists } => {
+                let drop = if *if_exists {
+                    "DROP COLUMN IF EXISTS"
+                } else {
+                    "DROP COLUMN"
+                };
+                format!("{} {}", self.kw(drop), column)
+            }
+            AlterTableOperation::RenameColumn { from, to } => format!(
+                "{} {} {} {}",
+                self.kw("RENAME COLUMN"),
+                from,
+                self.kw("TO"),
+                to
+            ),
+            AlterTableOperation::RenameTable(name) => {
+                format!("{} {}", self.kw("RENAME TO"), name)
+            }
+        }
+    }
+
+    /// The column type is kept as written, it isn't a keyword.
+    fn column_spec(&self, spec: &ColumnSpecification) -> String {
+        let mut text = format!("{} {}", spec.column, spec.sql_type);
+        for constraint in &spec.constraint {
+            let constraint = match constraint {
+                ColumnConstraint::Default(expr) => {
+                    format!("{} {}", self.kw("DEFAULT"), self.expr(expr))
+                }
+                constraint => self.kw(&constraint.to_string()),
+            };
+            text = format!("{} {}", text, constraint);
+        }
+        if let Some(comment) = &spec.comment {
+            let comment = Literal::String(comment.clone());
+            text = format!("{} {} {}", text, self.kw("COMMENT"), comment);
+        }
+        text
+    }
+
+    fn table_constraint(&self, constraint: &TableConstraint) -> String {
+        let keyword = match constraint {
+            TableConstraint::PrimaryKey(_) => "PRIMARY KEY",
+            TableConstraint::Unique(_) => "UNIQUE",
+        };
+        let columns = constraint
+            .columns()
+            .iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<_>>();
+        format!("{} ({})", self.kw(keyword), columns.join(", "))
+    }
+
+    /// Prints `keyword` followed by `items` on one line if they fit, or else one item per line.
+    fn list(&mut self, depth: usize, keyword: String, items: Vec<Item>, align: bool) {
+        let inline = items
+            .iter()
+            .map(|item| match &item.alias {
+                Some(alias) => format!("{} {} {}", item.text, self.kw("AS"), alias),
+                None => item.text.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let commented = items.iter().any(|item| self.commented(item.span));
+        if items.is_empty() || (!commented && self.fits(depth, &format!("{} {}", keyword, inline)))
+        {
+            self.line(depth, format!("{} {}", keyword, inline).trim_end());
+            return;
+        }
+
+        self.line(depth, keyword);
+        let texts = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match self.options.commas {
+                CommaStyle::Leading if i > 0 => format!(", {}", item.text),
+                _ => item.text.clone(),
+            })
+            .collect::<Vec<_>>();
+        let width = if align {
+            items
+                .iter()
+                .zip(&texts)
+                .filter(|(item, _)| item.alias.is_some())
+                .map(|(_, text)| text.chars().count())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        for (i, (item, text)) in items.iter().zip(texts).enumerate() {
+            self.leading(depth + 1, item.span);
+            let mut text = match &item.alias {
+                Some(alias) => format!("{:width$} {} {}", text, self.kw("AS"), alias),
+                None => text,
+            };
+            if self.options.commas == CommaStyle::Trailing && i + 1 < items.len() {
+                text.push(',');
+            }
+            self.line(depth + 1, text);
+            let comment = self.trailing(item.span);
+            self.last_line().comment = comment;
+        }
+    }
+
+    fn last_line(&mut self) -> &mut Line {
+        self.lines.last_mut().expect("a line was printed")
+    }
+
+    /// Prints a `WHERE` or `HAVING` condition, wrapped at its top level `AND` or `OR`.
+    fn condition(&mut self, depth: usize, keyword: &str, condition: &Expression) {
+        let keyword = self.kw(keyword);
+        let inline = format!("{} {}", keyword, self.expr(condition));
+        if !self.commented(condition.span()) && self.fits(depth, &inline) {
+            self.line(depth, inline);
+            return;
+        }
+
+        self.line(depth, keyword);
+        let (op, operands) = match condition {
+            Expression::BinaryExpr {
+                op: op @ (BinOp::And | BinOp::Or),
+                ..
+            } => (Some(op), flatten(condition, op)),
+            _ => (None, vec![condition]),
+        };
+        self.leading(depth + 1, condition.span());
+        for (i, operand) in operands.into_iter().enumerate() {
+            self.leading(depth + 1, operand.span());
+            let text = match op {
+                Some(op) if i > 0 => {
+                    format!(
+                        "{} {}",
+                        self.kw(&op.to_string()),
+                        self.operand(operand, op.precedence() + 1)
+                    )
+                }
+                Some(op) => self.operand(operand, op.precedence()),
+                None => self.expr(operand),
+            };
+            self.line(depth + 1, text);
+            let comment = self.trailing(operand.span());
+            self.last_line().comment = comment;
+        }
+        let comment = self.trailing(condition.span());
+        self.last_line().comment.push_str(&comment);
+    }
+
+    /// Prints the `FROM` clause, one join per line when wrapped.
+    fn from(&mut self, depth: usize, from: &TableReference) {
+        let inline = format!("{} {}", self.kw("FROM"), self.table_ref(from));
+        if !self.commented(from.span()) && self.fits(depth, &inline) {
+            self.line(depth, inline);
+            return;
+        }
+
+        self.line(depth, self.kw("FROM"));
+        // joins are left associative, `t1 JOIN t2 JOIN t3` is a chain on the left
+        let mut joins = Vec::new();
+        let mut relation = from;
+        while let TableReference::Join {
+            left,
+            op,
+            right,
+            cond,
+            span,
+        } = relation
+        {
+            joins.push((
+                format!("{} ", self.kw(&op.to_string())),
+                &**right,
+                cond,
+                *span,
+            ));
+            relation = left;
+        }
+        joins.reverse();
+
+        self.leading(depth + 1, relation.span());
+        self.relation(depth + 1, String::new(), relation, String::new());
+        let comment = self.trailing(relation.span());
+        self.last_line().comment = comment;
+
+        let heads = joins
+            .iter()
+            .map(|(op, right, _, _)| format!("{}{}", op, self.relation_inline(right)))
+            .collect::<Vec<_>>();
+        let width = if self.options.align_join_conditions {
+            heads
+                .iter()
+                .map(|head| head.chars().count())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        for ((op, right, cond, span), head) in joins.into_iter().zip(heads) {
+            self.leading(depth + 1, right.span());
+            let cond = self.join_condition(cond);
+            if self.is_block(depth + 1, right) {
+                self.relation(depth + 1, op, right, format!(" {}", cond));
+            } else {
+                self.line(depth + 1, format!("{:width$} {}", head, cond));
+            }
+            let comment = self.trailing(span);
+            self.last_line().comment = comment;
+        }
+    }
+
+    /// Whether `relation` is a subquery too long, or too commented, for a line of its own.
+    fn is_block(&self, depth: usize, relation: &TableReference) -> bool {
+        match relation {
+            TableReference::SubQuery { subquery, .. } => {
+                !self.fits(depth, &self.relation_inline(relation))
+                    || self
+                        .comments
+                        .iter()
+                        .any(|comment| subquery.span.range().contains(&comment.span.start.offset))
+            }
+            _ => false,
+        }
+    }
+
+    /// Prints a relation between `prefix` and `suffix`, a long subquery spans several lines.
+    fn relation(
+        &mut self,
+        depth: usize,
+        prefix: String,
+        relation: &TableReference,
+        suffix: String,
+    ) {
+        match relation {
+            TableReference::SubQuery {
+                subquery, alias, ..
+            } if self.is_block(depth, relation) => {
+                self.line(depth, format!("{}(", prefix));
+                self.select(subquery, depth + 1);
+                let alias = match alias {
+                    Some(alias) => format!(" {} {}", self.kw("AS"), alias),
+                    None => String::new(),
+                };
+                self.line(depth, format!("){}{}", alias, suffix));
+            }
+            _ => self.line(
+                depth,
+                format!("{}{}{}", prefix, self.relation_inline(relation), suffix),
+            ),
+        }
+    }
+
+    fn relation_inline(&self, relation: &TableReference) -> String {
+        match relation {
+            TableReference::Join { .. } => format!("({})", self.table_ref(relation)),
+            _ => self.table_ref(relation),
+        }
+    }
+
+    fn table_ref(&self, table_ref: &TableReference) -> String {
+        match table_ref {
+            TableReference::BaseRelation { table, alias, .. } => match alias {
+                Some(alias) => format!("{} {} {}", table, self.kw("AS"), alias),
+                None => table.to_string(),
+            },
+            TableReference::SubQuery {
+                subquery, alias, ..
+            } => match alias {
+                Some(alias) => format!(
+                    "({}) {} {}",
+                    self.select_inline(subquery),
+                    self.kw("AS"),
+                    alias
+                ),
+                None => format!("({})", self.select_inline(subquery)),
+            },
+            TableReference::Join {
+                left,
+                op,
+                right,
+                cond,
+                ..
+            } => format!(
+                "{} {} {} {}",
+                self.table_ref(left),
+                self.kw(&op.to_string()),
+                self.relation_inline(right),
+                self.join_condition(cond)
+            ),
+        }
+    }
+
+    fn join_condition(&self, cond: &JoinCondition) -> String {
+        match cond {
+            JoinCondition::Using(columns) => {
+                let columns = columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+                format!("{} ({})", self.kw("USING"), columns.join(", "))
+            }
+            JoinCondition::On(conditions) if conditions.is_empty() => self.kw("ON"),
+            JoinCondition::On(conditions) => {
+                format!("{} {}", self.kw("ON"), self.exprs(conditions))
+            }
+        }
+    }
+
+    /// The select statement on a single line, e.g. for a short subquery.
+    fn select_inline(&self, stmt: &SelectStatement) -> String {
+        let options = FormatOptions {
+            line_width: usize::MAX,
+            ..self.options.clone()
+        };
+        let comments = Comments::default();
+        let mut printer = Printer::new(&options, &comments);
+        printer.select(stmt, 0);
+        printer
+            .lines
+            .into_iter()
+            .map(|line| line.text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn item(&self, expr: &Expression) -> Item {
+        match expr {
+            Expression::Alias { expr, alias, span } => Item {
+                text: self.operand(expr, 1),
+                alias: Some(alias.to_string()),
+                span: *span,
+            },
+            expr => Item::new(self.expr(expr), expr.span()),
+        }
+    }
+
+    fn grouping_element(&self, element: &GroupingElement) -> String {
+        let (name, sets) = match element {
+            GroupingElement::Expr(expr) => return self.expr(expr),
+            GroupingElement::Ordinal(n, _) => return n.to_string(),
+            GroupingElement::Rollup(sets) => (self.kw("ROLLUP"), sets),
+            GroupingElement::Cube(sets) => (self.kw("CUBE"), sets),
+            GroupingElement::GroupingSets(sets) => (self.kw("GROUPING SETS "), sets),
+        };
+        let sets = sets
+            .iter()
+            .map(|set| match set.as_slice() {
+                [expr] => self.expr(expr),
+                exprs => format!("({})", self.exprs(exprs)),
+            })
+            .collect::<Vec<_>>();
+        format!("{}({})", name, sets.join(", "))
+    }
+
+    fn order_by(&self, order_by: &OrderBy) -> String {
+        let mut text = self.expr(&order_by.expr);
+        match order_by.direction {
+            Some(Order::Asc) => text = format!("{} {}", text, self.kw("ASC")),
+            Some(Order::Desc) => text = format!("{} {}", text, self.kw("DESC")),
+            None => {}
+        }
+        match order_by.nulls {
+            Some(NullsOrder::First) => format!("{} {}", text, self.kw("NULLS FIRST")),
+            Some(NullsOrder::Last) => format!("{} {}", text, self.kw("NULLS LAST")),
+            None => text,
+        }
+    }
+
+    fn fetch(&self, fetch: &Fetch) -> String {
+        let rows = match &fetch.quantity {
+            Some(quantity) => format!(
+                "{} {} {}",
+                self.kw("FETCH FIRST"),
+                self.expr(quantity),
+                self.kw("ROWS")
+            ),
+            None => self.kw("FETCH FIRST ROW"),
+        };
+        let only = self.kw(if fetch.with_ties { "WITH TIES" } else { "ONLY" });
+        format!("{} {}", rows, only)
+    }
+
+    fn exprs(&self, exprs: &[Expression]) -> String {
+        exprs
+            .iter()
+            .map(|expr| self.expr(expr))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Prints `expr` like its `Display` implementation, with keywords in the configured case.
+    fn expr(&self, expr: &Expression) -> String {
+        match expr {
+            Expression::Alias { expr, alias, .. } => {
+                format!("{} {} {}", self.operand(expr, 1), self.kw("AS"), alias)
+            }
+            Expression::Literal(literal @ (Literal::Null | Literal::Boolean(_)), _) => {
+                self.kw(&literal.to_string())
+            }
+            Expression::UnaryExpr { op, expr, .. } => {
+                let space = match (op, &**expr) {
+                    (UnaryOp::LogicalNot, _)
+                    | (
+                        UnaryOp::Minus,
+                        Expression::UnaryExpr {
+                            op: UnaryOp::Minus, ..
+                        },
+                    ) => " ",
+                    _ => "",
+                };
+                format!(
+                    "{}{}{}",
+                    self.kw(&op.to_string()),
+                    space,
+                    self.operand(expr, op.precedence())
+                )
+            }
+            Expression::BinaryExpr {
+                left, op, right, ..
+            } => format!(
+                "{} {} {}",
+                self.operand(left, op.precedence()),
+                self.kw(&op.to_string()),
+                self.operand(right, op.precedence() + 1)
+            ),
+            Expression::FunctionExpression { func, args, .. } => {
+                format!("{}({})", self.kw(&func.to_string()), self.exprs(args))
+            }
+            Expression::Function { name, args, .. } => format!("{}({})", name, self.exprs(args)),
+            Expression::Niladic(function, _) => self.kw(&function.to_string()),
+            Expression::In {
+                left,
+                right,
+                not_in,
+                ..
+            } => {
+                let in_kw = self.kw(if *not_in { "NOT IN" } else { "IN" });
+                format!(
+                    "{} {} ({})",
+                    self.operand(left, expr.precedence()),
+                    in_kw,
+                    self.exprs(right)
+                )
+            }
+            expr => expr.to_string(),
+        }
+    }
+
+    /// Prints `expr`, in parentheses when it binds looser than `precedence`.
+    fn operand(&self, expr: &Expression, precedence: u8) -> String {
+        if expr.precedence() < precedence {
+            format!("({})", self.expr(expr))
+        } else {
+            self.expr(expr)
+        }
+    }
+}
+
+/// The operands of a chain of `op`, e.g. `a`, `b` and `c` of `a AND b AND c`.
+fn flatten<'e>(expr: &'e Expression, op: &BinOp) -> Vec<&'e Expression> {
+    match expr {
+        Expression::BinaryExpr {
+            left,
+            op: inner,
+            right,
+            ..
+        } if inner == op => {
+            let mut operands = flatten(left, op);
+            operands.push(right);
+            operands
+        }
+        expr => vec![expr],
+    }
+}
+
+fn grouping_span(element: &GroupingElement) -> Span {
+    match element {
+        GroupingElement::Expr(expr) => expr.span(),
+        GroupingElement::Ordinal(_, span) => *span,
+        GroupingElement::Rollup(sets)
+        | GroupingElement::Cube(sets)
+        | GroupingElement::GroupingSets(sets) => sets
+            .iter()
+            .flatten()
+            .fold(Span::default(), |span, expr| span.union(expr.span())),
+    }
+}
+
+fn assignment_span(assignment: &Assignment) -> Span {
+    let column = &assignment.column;
+    let table = column
+        .table
+        .as_ref()
+        .map_or(Span::default(), |table| table.span());
+    table
+        .union(column.column.span)
+        .union(assignment.value.span())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+    use crate::parse_statements;
+
+    fn options() -> FormatOptions {
+        FormatOptions {
+            line_width: 40,
+            ..FormatOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_format_wraps_long_clauses() {
+        let sql = "select a, sum(b) as total from orders join users on orders.user_id = users.id \
+                   where a = 1 and (b > 2 or c < 3) and d in (1, 2) group by a order by total desc limit 10";
+        let expected = "\
+SELECT a, SUM(b) AS total
+FROM
+    orders
+    JOIN users ON orders.user_id = users.id
+WHERE
+    a = 1
+    AND (b > 2 OR c < 3)
+    AND d IN (1, 2)
+GROUP BY a
+ORDER BY total DESC
+LIMIT 10;
+";
+        let formatted = format(sql, &options()).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            parse_statements(&formatted).unwrap().without_spans(),
+            parse_statements(sql).unwrap().without_spans()
+        );
+    }
+
+    #[test]
+    fn test_format_options() {
+        let sql =
+            "SELECT id AS user_id, first_name, birth_date AS born, NULL AS nothing FROM users";
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Lower,
+            indent: 2,
+            commas: CommaStyle::Leading,
+            align_aliases: true,
+            ..options()
+        };
+        let expected = "\
+select
+  id           as user_id
+  , first_name
+  , birth_date as born
+  , null       as nothing
+from users;
+";
+        assert_eq!(format(sql, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_aligns_join_conditions() {
+        let sql = "SELECT * FROM a JOIN bb USING (id) LEFT JOIN c ON a.x = c.x";
+        let options = FormatOptions {
+            align_join_conditions: true,
+            line_width: 30,
+            ..options()
+        };
+        let expected = "\
+SELECT *
+FROM
+    a
+    JOIN bb     USING (id)
+    LEFT JOIN c ON a.x = c.x;
+";
+        assert_eq!(format(sql, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_subquery() {
+        let sql = "SELECT s.a FROM (SELECT a FROM t WHERE a > 1) AS s; SELECT x FROM (SELECT x, y, z FROM some_table WHERE x > 10) AS q";
+        let expected = "\
+SELECT s.a
+FROM (SELECT a FROM t WHERE a > 1) AS s;
+
+SELECT x
+FROM
+    (
+        SELECT x, y, z
+        FROM some_table
+        WHERE x > 10
+    ) AS q;
+";
+        assert_eq!(format(sql, &options()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_with() {
+        let sql = "WITH a AS (SELECT x FROM t), b (y) AS (SELECT x FROM a WHERE x > 10 AND x < 20 AND x <> 15) SELECT y FROM b AS c";
+        let expected = "\
+WITH a AS (SELECT x FROM t),
+b (y) AS (
+    SELECT x
+    FROM a
+    WHERE x > 10 AND x < 20 AND x <> 15
+)
+SELECT y
+FROM b AS c;
+";
+        assert_eq!(format(sql, &options()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_union() {
+        let sql = "select a from t union all select b from u where b > 1 union select 1 order by 1";
+        let expected = "\
+SELECT a
+FROM t
+UNION ALL
+SELECT b
+FROM u
+WHERE b > 1
+UNION
+SELECT 1
+ORDER BY 1;
+";
+        assert_eq!(format(sql, &options()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_preserves_comments() {
+        let sql = "-- totals\nSELECT /*+ FULL(t) */ a, -- first\n b /* second */ FROM t WHERE a = /* odd */ 1;\n\
+                   INSERT INTO t VALUES (1), (2); -- done";
+        let expected = "\
+-- totals
+/* odd */
+SELECT /*+ FULL(t) */
+    a, -- first
+    b /* second */
+FROM t
+WHERE a = 1;
+
+INSERT INTO t
+VALUES (1), (2); -- done
+";
+        assert_eq!(format(sql, &options()).unwrap(), expected);
+
+        let options = FormatOptions {
+            preserve_comments: false,
+            ..options()
+        };
+        assert_eq!(
+            format(sql, &options).unwrap(),
+            "SELECT a, b\nFROM t\nWHERE a = 1;\n\nINSERT INTO t\nVALUES (1), (2);\n"
+        );
+    }
+
+    #[test]
+    fn test_format_statement() {
+        let stmt = crate::parse_statement("update t set a = 1, b = 'x' where id = ?").unwrap();
+        assert_eq!(
+            format_statement(&stmt, &FormatOptions::default()),
+            "UPDATE t\nSET a = 1, b = 'x'\nWHERE id = ?"
+        );
+        assert!(format("SELECT FROM WHERE", &options()).is_err());
+    }
+
+    #[test]
+    fn test_format_ddl() {
+        let sql = "create table if not exists t (id int primary key, name varchar(20) not null default 'x', \
+                   unique (name)); alter table t add column age int, rename column name to full_name";
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Upper,
+            indent: 2,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format(sql, &options).unwrap(),
+            "CREATE TABLE IF NOT EXISTS t (\n  id int PRIMARY KEY,\n  name varchar(20) NOT NULL DEFAULT 'x',\n  \
+             UNIQUE (name)\n);\n\nALTER TABLE t ADD COLUMN age int, RENAME COLUMN name TO full_name;\n"
+        );
+    }
+}
@@ -0,0 +1,560 @@
+//! Checks the use of aggregates and `GROUP BY` in a bound query, see [`crate::binder`].
+//!
+//! In a grouped query, one with a `GROUP BY`, a `HAVING` or an aggregate in its select list or
+//! `ORDER BY`, each column outside an aggregate must be grouped, or belong to a table whose whole
+//! primary key is grouped as it then has a single value per group. Aggregates can't be used in
+//! `WHERE`, `ON` or `GROUP BY`, nor nested in another aggregate.
+//!
+//! ```
+//! use sql_parser::ast::Statement;
+//! use sql_parser::binder::bind;
+//! use sql_parser::catalog::Catalog;
+//! use sql_parser::grouping::validate;
+//! use sql_parser::{parse_statement, ParseOptions};
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.load_script("CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT)", &ParseOptions::default());
+//!
+//! let Statement::Select(query) = parse_statement("SELECT a, b, SUM(id) FROM t GROUP BY a").unwrap()
+//! else { unreachable!() };
+//! let diagnostics = validate(&query, &bind(&query, &catalog), &catalog);
+//! assert_eq!(
+//!     diagnostics[0].message,
+//!     "column `b` must appear in GROUP BY or be used in an aggregate"
+//! );
+//! ```
+use crate::ast::expression::{ColumnReference, Expression};
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{
+    GroupBy, GroupingElement, OrderBy, SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::{IgnoreSpans, Spanned};
+use crate::ast::{Identifier, ObjectName};
+use crate::binder::{address, Binding, Bound};
+use crate::catalog::Catalog;
+use crate::diagnostic::Diagnostic;
+use crate::visitor::{walk_expressions, walk_expressions_pruned};
+
+/// Checks the aggregates of `query` and its subqueries, `bound` being its bindings.
+pub fn validate(query: &SelectStatement, bound: &Bound<'_>, catalog: &Catalog) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        bound,
+        catalog,
+        diagnostics: vec![],
+    };
+    validator.query(query);
+    validator.finish()
+}
+
+/// Checks the aggregates of a single select clause, without the `ORDER BY` of its query.
+pub fn validate_clause(
+    clause: &SelectClause,
+    bound: &Bound<'_>,
+    catalog: &Catalog,
+) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        bound,
+        catalog,
+        diagnostics: vec![],
+    };
+    validator.clause(clause, &[]);
+    validator.finish()
+}
+
+/// What a grouped column stands for, so that `a` and `t.a` are the same.
+#[derive(Debug, PartialEq)]
+enum Key {
+    /// A column by the relation of `FROM` it's read through and the table it belongs to in there
+    Column {
+        relation: usize,
+        table: String,
+        column: String,
+    },
+    /// A computed item of a select list by address, and the relation it's read through if any
+    Expression {
+        relation: Option<usize>,
+        expr: usize,
+    },
+}
+
+/// The grouping of a grouped select clause.
+struct Grouping<'a> {
+    exprs: Vec<&'a Expression>,
+    keys: Vec<Key>,
+    /// The items of the select list without their alias, which may be used anywhere
+    projection: Vec<&'a Expression>,
+}
+
+struct Validator<'a> {
+    bound: &'a Bound<'a>,
+    catalog: &'a Catalog,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn finish(mut self) -> Vec<Diagnostic> {
+        self.diagnostics
+            .sort_by_key(|diagnostic| diagnostic.span.start);
+        self.diagnostics
+    }
+
+    fn error(&mut self, expr: &Expression, message: String) {
+        self.diagnostics.push(Diagnostic::new(expr.span(), message));
+    }
+
+    fn query(&mut self, query: &SelectStatement) {
+        for cte in &query.with {
+            self.query(&cte.query);
+        }
+        if query.unions.is_empty() {
+            self.clause(&query.body, &query.order_by);
+            return;
+        }
+        self.clause(&query.body, &[]);
+        for union in &query.unions {
+            self.clause(&union.clause, &[]);
+        }
+        // it sorts the combined rows, which have nothing left to aggregate
+        for order_by in &query.order_by {
+            self.no_aggregate(&order_by.expr, "the ORDER BY of a UNION");
+        }
+    }
+
+    fn clause(&mut self, clause: &SelectClause, order_by: &[OrderBy]) {
+        if let Some(from) = &clause.from {
+            self.table_ref(from);
+        }
+        if let Some(where_exp) = &clause.where_exp {
+            self.no_aggregate(where_exp, "WHERE");
+        }
+        if let Some(GroupBy::Elements(elements)) = &clause.group_by {
+            for expr in elements.iter().flat_map(GroupingElement::exprs) {
+                self.no_aggregate(expr, "GROUP BY");
+            }
+        }
+        let items = clause
+            .projection
+            .iter()
+            .chain(&clause.having)
+            .chain(order_by.iter().map(|order_by| &order_by.expr))
+            .collect::<Vec<_>>();
+        for item in &items {
+            self.nesting(item);
+        }
+
+        let grouped = match &clause.group_by {
+            Some(GroupBy::All) => false,
+            Some(GroupBy::Elements(_)) => true,
+            None => clause.having.is_some() || items.iter().any(|item| has_aggregate(item)),
+        };
+        if grouped {
+            let grouping = self.grouping(clause);
+            for item in items {
+                self.grouped(item, &grouping);
+            }
+        }
+    }
+
+    fn table_ref(&mut self, table_ref: &TableReference) {
+        match table_ref {
+            TableReference::BaseRelation { .. } => {}
+            TableReference::SubQuery { subquery, .. } => self.query(subquery),
+            TableReference::Join {
+                left, right, cond, ..
+            } => {
+                self.table_ref(left);
+                self.table_ref(right);
+                if let JoinCondition::On(conditions) = cond {
+                    for condition in conditions {
+                        self.no_aggregate(condition, "ON");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reports the aggregates of `expr`, used in `clause`.
+    fn no_aggregate(&mut self, expr: &Expression, clause: &str) {
+        walk_expressions_pruned(expr, |expr| match expr {
+            Expression::FunctionExpression { func, .. } => {
+                self.error(
+                    expr,
+                    format!("aggregate `{}` isn't allowed in {}", func, clause),
+                );
+                false
+            }
+            _ => true,
+        });
+    }
+
+    /// Reports the aggregates nested in another.
+    fn nesting(&mut self, expr: &Expression) {
+        walk_expressions(expr, |outer| {
+            let Expression::FunctionExpression {
+                func: outer, args, ..
+            } = outer
+            else {
+                return;
+            };
+            // the aggregates nested deeper are reported against the one they're in
+            walk_expressions_pruned(args, |expr| match expr {
+                Expression::FunctionExpression { func, .. } => {
+                    self.error(
+                        expr,
+                        format!("aggregate `{}` can't be nested in `{}`", func, outer),
+                    );
+                    false
+                }
+                _ => true,
+            });
+        });
+    }
+
+    fn grouping<'a>(&self, clause: &'a SelectClause) -> Grouping<'a> {
+        let mut exprs = vec![];
+        if let Some(GroupBy::Elements(elements)) = &clause.group_by {
+            for element in elements {
+                match element {
+                    GroupingElement::Ordinal(ordinal, _) => {
+                        // out of range ordinals are reported by the binder
+                        let index = (*ordinal as usize).checked_sub(1);
+                        if let Some(item) = index.and_then(|index| clause.projection.get(index)) {
+                            exprs.push(unalias(item));
+                        }
+                    }
+                    element => exprs.extend(element.exprs()),
+                }
+            }
+        }
+        let keys = exprs.iter().filter_map(|expr| self.key(expr)).collect();
+        let projection = clause.projection.iter().map(unalias).collect();
+        Grouping {
+            exprs,
+            keys,
+            projection,
+        }
+    }
+
+    fn key(&self, expr: &Expression) -> Option<Key> {
+        match self.bound.column(expr)? {
+            Binding::Column {
+                table,
+                alias,
+                relation,
+                column,
+                ..
+            } => Some(Key::Column {
+                relation: *relation,
+                table: table_key(table, alias),
+                column: column.name().normalized(),
+            }),
+            Binding::Expression { expr, relation, .. } => Some(Key::Expression {
+                relation: *relation,
+                expr: address(*expr),
+            }),
+        }
+    }
+
+    /// Reports the columns of `expr` outside an aggregate that don't have a value per group.
+    fn grouped(&mut self, expr: &Expression, grouping: &Grouping) {
+        let mut diagnostics = vec![];
+        walk_expressions_pruned(expr, |expr| {
+            if grouping
+                .exprs
+                .iter()
+                .any(|grouped| grouped.eq_ignore_spans(expr))
+            {
+                return false;
+            }
+            match expr {
+                Expression::FunctionExpression { .. } => false,
+                Expression::ColumnReference(ColumnReference::Column(_), _) => {
+                    if let Some(binding) = self.bound.column(expr) {
+                        if !self.is_grouped(binding, grouping) {
+                            diagnostics.push(Diagnostic::new(
+                                expr.span(),
+                                format!(
+                                    "column `{}` must appear in GROUP BY or be used in an aggregate",
+                                    expr
+                                ),
+                            ));
+                        }
+                    }
+                    false
+                }
+                Expression::ColumnReference(_, _) => {
+                    let columns = self.bound.wildcard(expr).unwrap_or_default();
+                    let ungrouped = columns
+                        .iter()
+                        .find(|column| !self.is_grouped(&column.binding, grouping));
+                    if let Some(column) = ungrouped {
+                        let name = match &column.name {
+                            Some(name) => name.to_string(),
+                            None => "?".to_string(),
+                        };
+                        diagnostics.push(Diagnostic::new(
+                            expr.span(),
+                            format!(
+                                "`{}` selects column `{}`, which must appear in GROUP BY or be used in an aggregate",
+                                expr, name
+                            ),
+                        ));
+                    }
+                    false
+                }
+                _ => true,
+            }
+        });
+        self.diagnostics.extend(diagnostics);
+    }
+
+    fn is_grouped(&self, binding: &Binding, grouping: &Grouping) -> bool {
+        match binding {
+            Binding::Expression { expr, relation, .. } => {
+                grouping
+                    .projection
+                    .iter()
+                    .any(|item| std::ptr::eq(*item, *expr))
+                    || grouping.keys.contains(&Key::Expression {
+                        relation: *relation,
+                        expr: address(*expr),
+                    })
+            }
+            Binding::Column {
+                table,
+                alias,
+                relation,
+                column,
+                ..
+            } => {
+                let key = |column: &Identifier| Key::Column {
+                    relation: *relation,
+                    table: table_key(table, alias),
+                    column: column.normalized(),
+                };
+                if grouping.keys.contains(&key(column.name())) {
+                    return true;
+                }
+                // a grouped primary key determines the other columns of its table
+                let primary_key = self
+                    .catalog
+                    .table(table)
+                    .map(|schema| schema.primary_key())
+                    .unwrap_or_default();
+                !primary_key.is_empty()
+                    && primary_key
+                        .into_iter()
+                        .all(|column| grouping.keys.contains(&key(column)))
+            }
+        }
+    }
+}
+
+/// A table by the name the query gives it, so that the two sides of a self join differ.
+fn table_key(table: &ObjectName, alias: &Option<Identifier>) -> String {
+    match alias {
+        Some(alias) => alias.to_string(),
+        None => table.to_string(),
+    }
+}
+
+/// A select list item without its alias.
+pub(crate) fn unalias(expr: &Expression) -> &Expression {
+    match expr {
+        Expression::Alias { expr, .. } => expr,
+        expr => expr,
+    }
+}
+
+pub(crate) fn has_aggregate(expr: &Expression) -> bool {
+    let mut found = false;
+    walk_expressions(expr, |expr| {
+        found |= matches!(expr, Expression::FunctionExpression { .. })
+    });
+    found
+}
+
+/// Adds the aggregates of `expr` missing from `aggregates`.
+pub(crate) fn collect_aggregates(expr: &Expression, aggregates: &mut Vec<Expression>) {
+    walk_expressions_pruned(expr, |expr| match expr {
+        Expression::FunctionExpression { .. } => {
+            if !aggregates
+                .iter()
+                .any(|aggregate| aggregate.eq_ignore_spans(expr))
+            {
+                aggregates.push(expr.clone());
+            }
+            false
+        }
+        _ => true,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binder::bind;
+    use crate::testing::query;
+
+    fn catalog() -> Catalog {
+        crate::testing::catalog(
+            "CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT);
+             CREATE TABLE u (x INT, y INT, PRIMARY KEY (x, y));
+             CREATE TABLE v (t_id INT, c INT)",
+        )
+    }
+
+    fn errors(sql: &str) -> Vec<String> {
+        let catalog = catalog();
+        let query = query(sql);
+        let bound = bind(&query, &catalog);
+        assert!(!bound.has_errors(), "{:?}", bound.diagnostics);
+        validate(&query, &bound, &catalog)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_grouping() {
+        for sql in [
+            "SELECT a, COUNT(*) FROM t GROUP BY a",
+            "SELECT t.a, SUM(b) FROM t GROUP BY a HAVING SUM(b) > 1 ORDER BY a",
+            "SELECT a + b, COUNT(*) FROM t GROUP BY a + b",
+            "SELECT a + 1 AS s, COUNT(*) FROM t GROUP BY 1 ORDER BY s",
+            "SELECT COUNT(*), MAX(a) FROM t",
+            "SELECT a, b FROM t GROUP BY ALL",
+            "SELECT a FROM t GROUP BY ROLLUP(a, b)",
+            "SELECT s.a FROM (SELECT a, SUM(b) AS total FROM t GROUP BY a) AS s WHERE s.total > 1",
+        ] {
+            assert!(errors(sql).is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_primary_key_determines_columns() {
+        assert!(
+            errors("SELECT id, a, b, SUM(c) FROM t JOIN v ON v.t_id = t.id GROUP BY id").is_empty()
+        );
+        assert!(errors("SELECT * FROM t GROUP BY id").is_empty());
+        assert_eq!(
+            errors("SELECT x, y FROM u GROUP BY x"),
+            ["column `y` must appear in GROUP BY or be used in an aggregate"]
+        );
+        assert_eq!(
+            errors("SELECT id, c FROM t JOIN v ON v.t_id = t.id GROUP BY id"),
+            ["column `c` must appear in GROUP BY or be used in an aggregate"]
+        );
+        // the key of one side of a self join doesn't determine the other side
+        assert!(errors(
+            "SELECT p.id, p.a, COUNT(*) FROM t AS p JOIN t c ON c.b = p.id GROUP BY p.id"
+        )
+        .is_empty());
+        assert_eq!(
+            errors("SELECT p.id, c.a FROM t AS p JOIN t c ON c.b = p.id GROUP BY p.id"),
+            ["column `c.a` must appear in GROUP BY or be used in an aggregate"]
+        );
+    }
+
+    #[test]
+    fn test_common_table_expressions() {
+        assert!(errors(
+            "WITH s AS (SELECT a, SUM(b) AS total FROM t GROUP BY a) SELECT a, total FROM s"
+        )
+        .is_empty());
+        assert_eq!(
+            errors("WITH s AS (SELECT a, b FROM t GROUP BY a) SELECT a FROM s"),
+            ["column `b` must appear in GROUP BY or be used in an aggregate"]
+        );
+    }
+
+    #[test]
+    fn test_ungrouped_columns() {
+        assert_eq!(
+            errors("SELECT a, b + 1, COUNT(*) FROM t GROUP BY a HAVING b > 1 ORDER BY t.b"),
+            [
+                "column `b` must appear in GROUP BY or be used in an aggregate",
+                "column `b` must appear in GROUP BY or be used in an aggregate",
+                "column `t.b` must appear in GROUP BY or be used in an aggregate"
+            ]
+        );
+        assert_eq!(
+            errors("SELECT a, COUNT(*) FROM t"),
+            ["column `a` must appear in GROUP BY or be used in an aggregate"]
+        );
+        assert_eq!(
+            errors("SELECT * FROM v GROUP BY t_id"),
+            ["`*` selects column `c`, which must appear in GROUP BY or be used in an aggregate"]
+        );
+    }
+
+    #[test]
+    fn test_unions() {
+        assert!(
+            errors("SELECT a, COUNT(*) FROM t GROUP BY a UNION SELECT t_id, c FROM v").is_empty()
+        );
+        assert_eq!(
+            errors("SELECT a FROM t UNION SELECT t_id FROM v GROUP BY c ORDER BY MAX(a)"),
+            [
+                "column `t_id` must appear in GROUP BY or be used in an aggregate",
+                "aggregate `MAX` isn't allowed in the ORDER BY of a UNION"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relations_over_the_same_table() {
+        // the key of a subquery determines its own columns only
+        assert!(
+            errors("SELECT s.id, s.a FROM (SELECT id, a FROM t) AS s GROUP BY s.id").is_empty()
+        );
+        assert_eq!(
+            errors(
+                "SELECT l.id, r.a FROM (SELECT id, a FROM t) AS l \
+                 JOIN (SELECT id, a FROM t) AS r ON l.id = r.id GROUP BY l.id"
+            ),
+            ["column `r.a` must appear in GROUP BY or be used in an aggregate"]
+        );
+        assert_eq!(
+            errors(
+                "WITH s AS (SELECT id, a FROM t) \
+                 SELECT l.id, r.a FROM s AS l JOIN s AS r ON l.id = r.id GROUP BY l.id"
+            ),
+            ["column `r.a` must appear in GROUP BY or be used in an aggregate"]
+        );
+    }
+
+    #[test]
+    fn test_validate_clause() {
+        let catalog = catalog();
+        let query = query("SELECT a, b FROM t UNION SELECT t_id, COUNT(*) FROM v ORDER BY MAX(a)");
+        let bound = bind(&query, &catalog);
+        let messages = |clause| {
+            validate_clause(clause, &bound, &catalog)
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect::<Vec<_>>()
+        };
+        // the ORDER BY of the query is left out
+        assert!(messages(&query.body).is_empty());
+        assert_eq!(
+            messages(&query.unions[0].clause),
+            ["column `t_id` must appear in GROUP BY or be used in an aggregate"]
+        );
+    }
+
+    #[test]
+    fn test_misplaced_aggregates() {
+        assert_eq!(
+            errors(
+                "SELECT SUM(COUNT(a)) FROM t JOIN v ON MAX(c) = t.id WHERE SUM(b) > 1 GROUP BY COUNT(b)"
+            ),
+            [
+                "aggregate `COUNT` can't be nested in `SUM`",
+                "aggregate `MAX` isn't allowed in ON",
+                "aggregate `SUM` isn't allowed in WHERE",
+                "aggregate `COUNT` isn't allowed in GROUP BY"
+            ]
+        );
+    }
+}
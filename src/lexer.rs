@@ -0,0 +1,456 @@
+//! Splits SQL text into the [`Token`]s the parsers in [`crate::parser`] run on.
+use std::fmt::{Display, Formatter};
+
+use chumsky::{prelude::*, Stream};
+
+use crate::ast::expression::Parameter;
+use crate::ast::span::{Location, Span};
+use crate::ast::{closing_quote, Identifier};
+use crate::dialect::{Dialect, GenericDialect};
+use crate::parser::{Input, SyntaxError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// A reserved word as written e.g. `SELECT` or `from`, see [`Dialect::is_reserved_word`]
+    Keyword(String),
+    /// Any other bare word e.g. a column name or a non-reserved keyword such as `ROLLUP`
+    Ident(String),
+    /// An identifier in `` ` ``, `"` or `[]` quotes, those the dialect allows, with the escapes resolved
+    QuotedIdent(char, String),
+    /// An unsigned number as written e.g. `1_000` or `2.5e3`
+    Number(String),
+    /// A quoted string with the escapes resolved, single quoted unless the dialect allows `"`
+    String(String),
+    /// e.g. `2024-01-13`
+    Date(String),
+    Parameter(Parameter),
+    /// e.g. `+`, `<=` or `||`
+    Op(&'static str),
+    /// One of `(`, `)`, `,`, `;` or `.`
+    Punct(char),
+    Whitespace,
+    /// A `--`, `#` or `/* */` comment as written, delimiters included
+    Comment(String),
+    /// A character that doesn't start any token
+    Unknown(char),
+    /// Malformed input such as an unterminated string, reported by the parser as `reason`
+    Invalid {
+        text: String,
+        reason: String,
+    },
+}
+
+impl Token {
+    /// Whitespace and comments, they only separate the other tokens
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::Whitespace | Token::Comment(_))
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Keyword(word) | Token::Ident(word) => write!(f, "{}", word),
+            Token::QuotedIdent(quote, value) => {
+                write!(f, "{}", Identifier::quoted(*quote, value.as_str()))
+            }
+            Token::Number(number) | Token::Date(number) => write!(f, "{}", number),
+            Token::String(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Token::Parameter(parameter) => write!(f, "{}", parameter),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::Punct(c) | Token::Unknown(c) => write!(f, "{}", c),
+            Token::Whitespace => write!(f, " "),
+            Token::Comment(text) | Token::Invalid { text, .. } => write!(f, "{}", text),
+        }
+    }
+}
+
+impl Input for Token {
+    fn error(&self) -> Option<String> {
+        match self {
+            Token::Invalid { reason, .. } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Splits `source` into tokens, whitespace and comments included.
+///
+/// Tokenizing never fails, input that can't be tokenized becomes a [`Token::Unknown`] or
+/// [`Token::Invalid`] for the parser to report.
+pub fn tokenize(source: &str) -> Vec<(Token, Span)> {
+    tokenize_with(source, &GenericDialect)
+}
+
+/// Splits `source` into tokens like [`tokenize`], following the quoting and reserved words of `dialect`.
+pub fn tokenize_with(source: &str, dialect: &'static dyn Dialect) -> Vec<(Token, Span)> {
+    lexer(dialect)
+        .parse(chars(source))
+        .expect("every character starts a token")
+}
+
+/// Feeds `source` to the lexer with a [`Span`] for every character.
+fn chars(source: &str) -> Stream<'_, char, Span, impl Iterator<Item = (char, Span)> + '_> {
+    let eoi = Location::of(source, source.len());
+    let mut location = Location::new(0, 1, 1);
+    let chars = source.chars().map(move |c| {
+        let start = location;
+        location = if c == '\n' {
+            Location::new(start.offset + 1, start.line + 1, 1)
+        } else {
+            Location::new(start.offset + c.len_utf8(), start.line, start.column + 1)
+        };
+        (
+            c,
+            Span::new(
+                start,
+                Location::new(location.offset, start.line, start.column + 1),
+            ),
+        )
+    });
+    Stream::from_iter(Span::new(eoi, eoi), chars)
+}
+
+/// A run of letters, digits and underscores not starting with a digit. Letters may be any Unicode letter.
+fn word() -> impl Parser<char, String, Error = SyntaxError> + Clone {
+    filter(|c: &char| c.is_alphabetic() || *c == '_')
+        .chain(filter(|c: &char| c.is_alphanumeric() || *c == '_').repeated())
+        .collect()
+}
+
+/// Parses text delimited by `open` and its closing quote, where a doubled closing quote is an
+/// escaped quote, as is any character after a backslash with `backslash_escapes`.
+/// Yields the unescaped text and whether the closing quote was found.
+fn quoted(
+    open: char,
+    backslash_escapes: bool,
+) -> impl Parser<char, (String, bool), Error = SyntaxError> + Clone {
+    let close = closing_quote(open);
+    let escaped_quote = just(close).then(just(close)).to(close.to_string());
+    // MySQL's escapes, `\%` and `\_` keep the backslash for `LIKE` patterns
+    let backslash_escape = filter(move |c: &char| backslash_escapes && *c == '\\')
+        .ignore_then(any())
+        .map(|c| match c {
+            '0' => "\0".to_string(),
+            'b' => "\u{8}".to_string(),
+            'n' => "\n".to_string(),
+            'r' => "\r".to_string(),
+            't' => "\t".to_string(),
+            'Z' => "\u{1a}".to_string(),
+            '%' | '_' => format!("\\{}", c),
+            c => c.to_string(),
+        });
+
+    just(open)
+        .ignore_then(
+            escaped_quote
+                .or(backslash_escape)
+                .or(filter(move |c: &char| *c != close).map(String::from))
+                .repeated()
+                .collect::<Vec<_>>()
+                .map(|parts| parts.concat()),
+        )
+        .then(just(close).or_not().map(|close| close.is_some()))
+}
+
+pub fn lexer(
+    dialect: &'static dyn Dialect,
+) -> impl Parser<char, Vec<(Token, Span)>, Error = SyntaxError> {
+    let digits = |n| filter(|c: &char| c.is_ascii_digit()).repeated().exactly(n);
+    let date = digits(4)
+        .chain::<char, _, _>(just('-'))
+        .chain::<char, Vec<char>, _>(digits(2))
+        .chain::<char, _, _>(just('-'))
+        .chain::<char, Vec<char>, _>(digits(2))
+        .collect::<String>()
+        .map(Token::Date);
+
+    let integer = filter(|c: &char| c.is_ascii_digit() && *c != '0')
+        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated())
+        .or(just('0').map(|c| vec![c]));
+    let frac = just('.')
+        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit()))
+        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated());
+    let exp = one_of("eE").chain(one_of("+-").or_not().chain::<char, _, _>(text::digits(10)));
+    let number = integer
+        .chain::<char, _, _>(frac.or_not().flatten())
+        .chain::<char, _, _>(exp.or_not().flatten())
+        .collect::<String>()
+        .map(|number| {
+            // integers too large for an `i64` are read as floats, whose range is checked here
+            match number.replace('_', "").parse::<f64>() {
+                Ok(value) if !value.is_finite() => Token::Invalid {
+                    text: number,
+                    reason: "numeric literal is out of range".to_string(),
+                },
+                _ => Token::Number(number),
+            }
+        });
+
+    // strings use single quotes with `''` as an escaped quote, double quotes delimit identifiers
+    // unless the dialect says otherwise
+    let backslash_escapes = dialect.backslash_escapes();
+    let string = filter(move |c: &char| (*c == '\'' || *c == '"') && dialect.is_string_quote(*c))
+        .rewind()
+        .then(quoted('\'', backslash_escapes).or(quoted('"', backslash_escapes)))
+        .map(|(open, (value, closed))| {
+            if closed {
+                Token::String(value)
+            } else {
+                Token::Invalid {
+                    text: format!("{}{}", open, value),
+                    reason: "unterminated string literal".to_string(),
+                }
+            }
+        });
+
+    let quoted_ident =
+        filter(move |c: &char| "`\"[".contains(*c) && dialect.is_identifier_quote(*c))
+            .rewind()
+            .then(choice((
+                quoted('`', false),
+                quoted('"', false),
+                quoted('[', false),
+            )))
+            .map(|(open, (value, closed))| match (closed, value.is_empty()) {
+                (true, false) => Token::QuotedIdent(open, value),
+                (true, true) => Token::Invalid {
+                    text: format!("{}{}", open, closing_quote(open)),
+                    reason: "quoted identifiers can't be empty".to_string(),
+                },
+                (false, _) => Token::Invalid {
+                    text: format!("{}{}", open, value),
+                    reason: "unterminated quoted identifier".to_string(),
+                },
+            });
+
+    let word = word().map(move |word| {
+        if dialect.is_reserved_word(&word) {
+            Token::Keyword(word)
+        } else {
+            Token::Ident(word)
+        }
+    });
+
+    let parameter = choice((
+        just('?').to(Token::Parameter(Parameter::Anonymous)),
+        just('$').ignore_then(text::int(10)).map(|n: String| {
+            n.parse::<u32>()
+                .map(|n| Token::Parameter(Parameter::Positional(n)))
+                .unwrap_or_else(|_| Token::Invalid {
+                    text: format!("${}", n),
+                    reason: "parameter index is out of range".to_string(),
+                })
+        }),
+        just(':')
+            .ignore_then(text::ident())
+            .map(|name| Token::Parameter(Parameter::Named(name))),
+    ));
+
+    let hash_comments = dialect.hash_comments();
+    let line_comment = just("--")
+        .or(filter(move |c: &char| *c == '#' && hash_comments).to("#"))
+        .then(filter(|c: &char| *c != '\n').repeated().collect::<String>())
+        .map(|(start, text)| Token::Comment(format!("{}{}", start, text)));
+    // block comments nest, yields the comment as written and whether it was closed
+    let block_comment = recursive(|comment| {
+        just("/*")
+            .ignore_then(
+                comment
+                    .map(|(text, _)| text)
+                    .or(just("*/").not().map(String::from))
+                    .repeated(),
+            )
+            .then(just("*/").or_not())
+            .map(|(parts, close): (Vec<String>, _)| {
+                let close = close.unwrap_or_default();
+                (format!("/*{}{}", parts.concat(), close), !close.is_empty())
+            })
+    })
+    .map(|(text, closed)| {
+        if closed {
+            Token::Comment(text)
+        } else {
+            Token::Invalid {
+                text,
+                reason: "unterminated comment".to_string(),
+            }
+        }
+    });
+
+    // longest operators first so `<=` isn't read as `<` followed by `=`
+    let op = choice((
+        just("<=").to("<="),
+        just(">=").to(">="),
+        just("<>").to("<>"),
+        just("!=").to("!="),
+        just("||").to("||"),
+        one_of("+-*/%=<>!").map(|c| match c {
+            '+' => "+",
+            '-' => "-",
+            '*' => "*",
+            '/' => "/",
+            '%' => "%",
+            '=' => "=",
+            '<' => "<",
+            '>' => ">",
+            _ => "!",
+        }),
+    ))
+    .map(Token::Op);
+
+    let whitespace = filter(|c: &char| c.is_whitespace())
+        .repeated()
+        .at_least(1)
+        .to(Token::Whitespace);
+
+    let token = choice((
+        whitespace,
+        line_comment,
+        block_comment,
+        date,
+        number,
+        string,
+        quoted_ident,
+        word,
+        parameter,
+        op,
+        one_of("(),;.").map(Token::Punct),
+        any().map(Token::Unknown),
+    ));
+
+    token
+        .map_with_span(|token, span| (token, span))
+        .repeated()
+        .then_ignore(end())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dialect::{MySqlDialect, PostgreSqlDialect};
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .into_iter()
+            .map(|(token, _)| token)
+            .filter(|token| !token.is_trivia())
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let actual = tokens("SELECT a, `b c` FROM t WHERE x <= 2.5e3 AND y = 'it''s' OR z <> ?");
+        let expected = vec![
+            Token::Keyword("SELECT".to_string()),
+            Token::Ident("a".to_string()),
+            Token::Punct(','),
+            Token::QuotedIdent('`', "b c".to_string()),
+            Token::Keyword("FROM".to_string()),
+            Token::Ident("t".to_string()),
+            Token::Keyword("WHERE".to_string()),
+            Token::Ident("x".to_string()),
+            Token::Op("<="),
+            Token::Number("2.5e3".to_string()),
+            Token::Keyword("AND".to_string()),
+            Token::Ident("y".to_string()),
+            Token::Op("="),
+            Token::String("it's".to_string()),
+            Token::Keyword("OR".to_string()),
+            Token::Ident("z".to_string()),
+            Token::Op("<>"),
+            Token::Parameter(Parameter::Anonymous),
+        ];
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let source = "a\n  é.b";
+        let spans = tokenize(source)
+            .into_iter()
+            .filter(|(token, _)| !token.is_trivia())
+            .map(|(_, span)| (span.range(), span.start.line, span.start.column))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![(0..1, 1, 1), (4..6, 2, 3), (6..7, 2, 4), (7..8, 2, 5)]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let actual = tokenize("-- this is a comment\n/*this is a comment*/");
+        let comments = actual
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec![
+                Token::Comment("-- this is a comment".to_string()),
+                Token::Whitespace,
+                Token::Comment("/*this is a comment*/".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn test_mysql_and_nested_comments() {
+        let actual = tokenize_with(
+            "a # note\n/* outer /* inner */ still */b /*+ HINT */",
+            &MySqlDialect,
+        );
+        let comments = actual
+            .into_iter()
+            .filter_map(|(token, _)| match token {
+                Token::Comment(text) => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec!["# note", "/* outer /* inner */ still */", "/*+ HINT */"]
+        );
+
+        assert!(matches!(
+            &tokens("a /* /* */")[1],
+            Token::Invalid { reason, .. } if reason == "unterminated comment"
+        ));
+    }
+
+    #[test]
+    fn test_hash_isnt_a_comment_in_postgres() {
+        let actual = tokenize_with("a # b", &PostgreSqlDialect)
+            .into_iter()
+            .map(|(token, _)| token)
+            .filter(|token| !token.is_trivia())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                Token::Ident("a".to_string()),
+                Token::Unknown('#'),
+                Token::Ident("b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_tokens() {
+        assert_eq!(tokens("a @ b")[1], Token::Unknown('@'));
+        assert!(matches!(
+            &tokens("SELECT 'abc")[1],
+            Token::Invalid { reason, .. } if reason == "unterminated string literal"
+        ));
+        assert!(matches!(
+            &tokens("$99999999999")[0],
+            Token::Invalid { reason, .. } if reason == "parameter index is out of range"
+        ));
+        assert!(matches!(
+            &tokens("1.5e400")[0],
+            Token::Invalid { reason, .. } if reason == "numeric literal is out of range"
+        ));
+        assert_eq!(tokens("1.5e300")[0], Token::Number("1.5e300".to_string()));
+    }
+}
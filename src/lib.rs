@@ -0,0 +1,507 @@
+//! An SQL parser written using [chumsky](https://github.com/zesterer/chumsky).
+//!
+//! ```
+//! use sql_parser::{ast::Statement, parse_statement};
+//!
+//! let stmt = parse_statement("SELECT name FROM users WHERE id = 1").unwrap();
+//! assert!(matches!(stmt, Statement::Select(_)));
+//! ```
+use chumsky::prelude::*;
+
+pub mod ast;
+pub mod binder;
+pub mod catalog;
+pub mod diagnostic;
+pub mod dialect;
+mod error;
+pub mod format;
+pub mod grouping;
+pub mod lexer;
+pub mod parser;
+pub mod plan;
+pub mod references;
+#[cfg(test)]
+mod testing;
+pub mod transpile;
+pub mod types;
+pub mod visitor;
+
+pub use diagnostic::Diagnostic;
+pub use error::ParseError;
+
+use dialect::{Dialect, GenericDialect};
+
+use ast::{
+    comment::{Comment, Comments},
+    expression::Expression,
+    span::Span,
+    Statement,
+};
+
+/// Parses a single statement, optionally terminated by `;`.
+pub fn parse_statement(sql: &str) -> Result<Statement, ParseError> {
+    parser::statement(&GenericDialect)
+        .then_ignore(parser::punct(';').or_not())
+        .then_ignore(end())
+        .parse(parser::stream(sql))
+        .map_err(ParseError::new)
+}
+
+/// Parses a script of `;` separated statements, failing if any of them is invalid.
+///
+/// Every problem in the script is reported, see [`parse_script`].
+pub fn parse_statements(sql: &str) -> Result<Vec<Statement>, ParseError> {
+    parse_script(sql).into_result()
+}
+
+/// Parses a script of `;` separated statements, recovering from errors so that every problem is
+/// reported rather than only the first.
+///
+/// A statement that can't be parsed at all becomes a [`Statement::Error`] spanning up to the next
+/// `;`. Within a statement a broken expression becomes an [`Expression::Error`], so the rest of
+/// the statement is still parsed e.g. an invalid `WHERE` condition skips to the next clause.
+///
+/// ```
+/// use sql_parser::{ast::Statement, parse_script};
+///
+/// let script = parse_script("SELECT a FROM t; DELETE t; SELECT b FROM WHERE a = 1");
+/// assert_eq!(script.statements.len(), 3);
+/// assert!(matches!(script.statements[1], Statement::Error(_)));
+/// assert_eq!(script.diagnostics.len(), 2);
+/// ```
+pub fn parse_script(sql: &str) -> Script {
+    parse_script_with(sql, &ParseOptions::default())
+}
+
+/// Settings for [`parse_script_with`].
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Collect comments and optimizer hints into [`Script::comments`]
+    pub preserve_comments: bool,
+    /// The SQL flavour to parse, [`GenericDialect`] by default
+    pub dialect: &'static dyn Dialect,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            preserve_comments: false,
+            dialect: &GenericDialect,
+        }
+    }
+}
+
+/// Parses a script like [`parse_script`], with the given options.
+///
+/// ```
+/// use sql_parser::{ast::span::Spanned, parse_script_with, ParseOptions};
+///
+/// let options = ParseOptions { preserve_comments: true, ..ParseOptions::default() };
+/// let script = parse_script_with("-- all users\nSELECT /*+ FULL(u) */ * FROM u", &options);
+/// let leading = script.comments.leading(script.statements[0].span());
+/// assert_eq!(leading[0].text, "-- all users");
+/// assert_eq!(script.comments.hints().count(), 1);
+/// ```
+pub fn parse_script_with(sql: &str, options: &ParseOptions) -> Script {
+    let tokens = lexer::tokenize_with(sql, options.dialect);
+    let comments = if options.preserve_comments {
+        comments(&tokens)
+    } else {
+        Comments::default()
+    };
+
+    let (statements, errors) =
+        parser::recovery::recover_statement(parser::statement(options.dialect))
+            .separated_by(parser::punct(';').repeated().at_least(1))
+            .allow_leading()
+            .allow_trailing()
+            .then_ignore(end())
+            .parse_recovery(parser::token_stream(sql, tokens));
+
+    Script {
+        statements: statements.unwrap_or_default(),
+        diagnostics: ParseError::new(errors).into_diagnostics(),
+        comments,
+    }
+}
+
+/// Attaches the comments among `tokens` to the tokens around them.
+///
+/// Separators are skipped so a comment after `a,` trails `a` rather than the `,`.
+fn comments(tokens: &[(lexer::Token, Span)]) -> Comments {
+    let comments = tokens.iter().filter_map(|(token, span)| match token {
+        lexer::Token::Comment(text) => Some(Comment::new(text.as_str(), *span)),
+        _ => None,
+    });
+    let anchors = tokens
+        .iter()
+        .filter(|(token, _)| {
+            !token.is_trivia()
+                && !matches!(token, lexer::Token::Punct(',') | lexer::Token::Punct(';'))
+        })
+        .map(|(_, span)| *span)
+        .collect::<Vec<_>>();
+    Comments::attach(comments, &anchors)
+}
+
+/// The result of [`parse_script`], the statements parsed along with the problems found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    /// The statements in source order, including [`Statement::Error`] for those that couldn't be parsed
+    pub statements: Vec<Statement>,
+    /// Every problem found, in source order
+    pub diagnostics: Vec<Diagnostic>,
+    /// The comments, attached to the nodes around them when [`ParseOptions::preserve_comments`] is set
+    pub comments: Comments,
+}
+
+impl Script {
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+
+    /// The statements when the script is free of errors.
+    pub fn into_result(self) -> Result<Vec<Statement>, ParseError> {
+        if self.has_errors() {
+            Err(ParseError::from_diagnostics(self.diagnostics))
+        } else {
+            Ok(self.statements)
+        }
+    }
+}
+
+/// Parses a standalone expression e.g. `price * 2 > 10`.
+pub fn parse_expression(sql: &str) -> Result<Expression, ParseError> {
+    parser::expression::expression(&GenericDialect)
+        .then_ignore(end())
+        .parse(parser::stream(sql))
+        .map_err(ParseError::new)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::comment::CommentKind;
+    use crate::ast::expression::{BinOp, Literal};
+    use crate::ast::span::{IgnoreSpans, Location, Spanned};
+    use crate::ast::{Identifier, ObjectName};
+    use crate::dialect::MySqlDialect;
+
+    #[test]
+    fn test_parse_statement() {
+        let stmt = parse_statement("select a from t;").unwrap();
+        assert!(matches!(stmt, Statement::Select(_)));
+
+        let stmt = parse_statement("DELETE FROM t WHERE a = 1").unwrap();
+        assert!(matches!(stmt, Statement::Delete(_)));
+    }
+
+    #[test]
+    fn test_comments_between_tokens() {
+        let stmt = parse_statement("SELECT a -- note\nFROM t /* c */ WHERE a = 1").unwrap();
+        let Statement::Select(select) = stmt else {
+            panic!("expected a select statement")
+        };
+        assert!(select.body.where_exp.is_some())
+    }
+
+    #[test]
+    fn test_mysql_and_nested_comments() {
+        let sql = "SELECT a # note\nFROM t /* a /* nested */ comment */ WHERE a = 1";
+        let options = ParseOptions {
+            dialect: &MySqlDialect,
+            ..ParseOptions::default()
+        };
+        let script = parse_script_with(sql, &options);
+        assert!(!script.has_errors());
+        assert!(matches!(script.statements[..], [Statement::Select(_)]));
+
+        // `#` is only a comment in MySQL
+        assert!(parse_statement(sql).is_err());
+    }
+
+    #[test]
+    fn test_preserve_comments() {
+        let sql = "-- leading\nSELECT /*+ INDEX(t i) */ a, -- after a\n  b + 1 /* after b */\nFROM t # mysql\n;";
+        let options = ParseOptions {
+            dialect: &MySqlDialect,
+            ..ParseOptions::default()
+        };
+        let script = parse_script_with(sql, &options);
+        assert!(script.comments.is_empty());
+
+        let options = ParseOptions {
+            preserve_comments: true,
+            ..options
+        };
+        let script = parse_script_with(sql, &options);
+        assert!(!script.has_errors());
+        let Statement::Select(select) = &script.statements[0] else {
+            panic!("expected a select statement")
+        };
+        fn text(comments: &[Comment]) -> Vec<&str> {
+            comments
+                .iter()
+                .map(|comment| comment.text.as_str())
+                .collect()
+        }
+
+        let comments = &script.comments;
+        assert_eq!(text(comments.leading(select.span)), vec!["-- leading"]);
+        let projection = &select.body.projection;
+        assert_eq!(
+            text(comments.leading(projection[0].span())),
+            vec!["/*+ INDEX(t i) */"]
+        );
+        assert_eq!(
+            text(comments.trailing(projection[0].span())),
+            vec!["-- after a"]
+        );
+        assert_eq!(
+            text(comments.trailing(projection[1].span())),
+            vec!["/* after b */"]
+        );
+        let from = select.body.from.as_ref().unwrap().span();
+        assert_eq!(text(comments.trailing(from)), vec!["# mysql"]);
+
+        let kinds = comments.iter().map(Comment::kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                CommentKind::Line,
+                CommentKind::Hint,
+                CommentKind::Line,
+                CommentKind::Block,
+                CommentKind::Line
+            ]
+        );
+        assert_eq!(
+            text(&comments.hints().cloned().collect::<Vec<_>>()),
+            vec!["/*+ INDEX(t i) */"]
+        );
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let stmts =
+            parse_statements("INSERT INTO t VALUES (1);\n UPDATE t SET a = 2;\n DROP TABLE t;")
+                .unwrap();
+        assert_eq!(stmts.len(), 3);
+        assert!(matches!(stmts[2], Statement::Drop(_)));
+    }
+
+    #[test]
+    fn test_parse_script_recovers() {
+        let sql =
+            "SELECT a +, b FROM t WHERE = 1 ORDER BY b;\nDROP t;\nINSERT INTO t VALUES (1, , 2)";
+        let script = parse_script(sql);
+
+        assert_eq!(script.statements.len(), 3);
+        let Statement::Select(select) = &script.statements[0] else {
+            panic!("expected a select statement")
+        };
+        assert!(matches!(select.body.projection[0], Expression::Error(_)));
+        assert_eq!(&sql[select.body.projection[1].span().range()], "b");
+        assert!(matches!(select.body.where_exp, Some(Expression::Error(_))));
+        assert_eq!(select.order_by.len(), 1);
+
+        let Statement::Error(span) = script.statements[1] else {
+            panic!("expected an error statement")
+        };
+        assert_eq!(&sql[span.range()], "DROP t");
+        assert!(matches!(script.statements[2], Statement::Insert(_)));
+
+        let lines = script
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.location.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 1, 2, 3]);
+        assert!(parse_statements(sql).is_err());
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let expr = parse_expression("a > 1").unwrap();
+        assert!(matches!(expr, Expression::BinaryExpr { op: BinOp::Gt, .. }));
+
+        let expr = parse_expression("'héllo'").unwrap();
+        assert_eq!(
+            expr.without_spans(),
+            Expression::Literal(Literal::String("héllo".to_string()), Span::default())
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let sql = "SELECT a + 1\n  FROM  t1 JOIN t2 ON x = y ";
+        let Statement::Select(stmt) = parse_statement(sql).unwrap() else {
+            panic!("expected a select statement")
+        };
+        assert_eq!(stmt.span.range(), 0..sql.len() - 1);
+
+        // padding isn't part of a node's span
+        let projection = stmt.body.projection[0].span();
+        assert_eq!(&sql[projection.range()], "a + 1");
+        let from = stmt.body.from.unwrap().span();
+        assert_eq!(&sql[from.range()], "t1 JOIN t2 ON x = y");
+        assert_eq!(from.start, Location::new(21, 2, 9));
+        assert_eq!(from.end, Location::new(40, 2, 28));
+    }
+
+    #[test]
+    fn test_ignore_spans() {
+        let a = parse_expression("a = 1").unwrap();
+        let b = parse_expression(" a  =  1").unwrap();
+        assert_ne!(a, b);
+        assert!(a.eq_ignore_spans(&b));
+        assert_eq!(a.without_spans(), b.without_spans());
+        assert_eq!(a, a.clone());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let sqls = [
+            "SELECT DISTINCT a, t.b AS c, s.t.* FROM db.t WHERE a >= 1 AND NOT b <> 'it''s' OR c = TRUE",
+            "SELECT COUNT(*), SUM(a * (b + 1)) FROM t1 LEFT JOIN (t2 JOIN t3 USING (id)) ON t1.id = t2.id, t1.x = 1 GROUP BY ROLLUP(a, (b, c)), 2 HAVING COUNT(*) > $1",
+            "SELECT a FROM (SELECT a FROM t) AS s CROSS JOIN u ON TRUE GROUP BY GROUPING SETS ((a), (a, b), ()), CUBE(a) ORDER BY a DESC NULLS LAST, b LIMIT :n OFFSET 2",
+            "SELECT a FROM t GROUP BY ALL OFFSET 5 ROWS FETCH FIRST 10 ROWS WITH TIES",
+            "SELECT -a, - -1, -(a + b), 2.0, 1.5e300, NULL, 2024-01-13, `order`, [a]]b] FROM t WHERE a NOT IN (1, ?) AND (a IN (2)) = FALSE",
+            "INSERT INTO db.t (a, b) VALUES (1, 'x'), (-2, ?)",
+            "UPDATE t SET a = a + 1, t.b = NULL WHERE (a = 1 OR b = 2) AND c = 3",
+            "DELETE FROM t WHERE NOT (a - (b - c)) * d / (e % f) < 1",
+            "DROP TABLE IF EXISTS s.t",
+            "SELECT COALESCE(a, -1) * 2, now(), s.`f`(a, b), CURRENT_DATE FROM t",
+            "CREATE TABLE s.t (a INT PRIMARY KEY, b VARCHAR(20) DEFAULT 'x', UNIQUE (b))",
+            "ALTER TABLE t ADD COLUMN c INT NOT NULL, RENAME TO u",
+            "WITH a AS (SELECT x FROM t), b (y) AS (SELECT x FROM a AS s) SELECT b.y FROM b JOIN db.t u ON b.y = u.x",
+            "SELECT a FROM t UNION SELECT DISTINCT b FROM u UNION ALL SELECT 1 ORDER BY a LIMIT 3",
+        ];
+        for sql in sqls {
+            let stmt = parse_statement(sql).unwrap();
+            let printed = stmt.to_string();
+            let reparsed = parse_statement(&printed).unwrap();
+            assert!(reparsed.eq_ignore_spans(&stmt), "{}", printed);
+        }
+    }
+
+    #[test]
+    fn test_display_minimal_parentheses() {
+        let cases = [
+            ("((a + b)) * c", "(a + b) * c"),
+            ("a + (b * c)", "a + b * c"),
+            ("(a - b) - c", "a - b - c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a OR b) AND NOT (c AND d)", "(a OR b) AND NOT (c AND d)"),
+            ("a = 1 AND (b = 2 OR c)", "a = 1 AND (b = 2 OR c)"),
+            ("!a = b", "(NOT a) = b"),
+            ("NOT NOT a", "NOT NOT a"),
+            ("- (-a)", "- -a"),
+            ("(a + 1) IN (1, 2 + 3)", "a + 1 IN (1, 2 + 3)"),
+            ("(a = 1) NOT IN (1, 2)", "a = 1 NOT IN (1, 2)"),
+            ("a = (b IN (1))", "a = (b IN (1))"),
+        ];
+        for (sql, expected) in cases {
+            let expr = parse_expression(sql).unwrap();
+            assert_eq!(expr.to_string(), expected);
+            assert_eq!(
+                parse_expression(expected).unwrap().without_spans(),
+                expr.without_spans()
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let sqls = [
+            "SELECT DISTINCT a, t.* AS b FROM db.t JOIN (SELECT c FROM u) AS s USING (c) \
+             WHERE NOT a IN (1, 2.5, 'x', NULL, TRUE, ?, $2, :p) GROUP BY ROLLUP(a, (b, c)), 2 \
+             HAVING COUNT(*) > 1 ORDER BY a DESC NULLS LAST LIMIT 10 OFFSET 5",
+            "SELECT a FROM t FETCH FIRST 3 ROWS WITH TIES",
+            "INSERT INTO t (a, b) VALUES (1, -2), (2024-01-13, `b c`)",
+            "UPDATE t SET a = a * 2 WHERE b <> 1 OR c >= 2",
+            "DELETE FROM t WHERE a = 1",
+            "DROP TABLE IF EXISTS t",
+            "WITH a (x) AS (SELECT 1) SELECT s.x FROM a AS s",
+            "SELECT a FROM t UNION ALL SELECT b FROM u ORDER BY 1",
+        ];
+        for sql in sqls {
+            let stmt = parse_statement(sql).unwrap();
+            let json = serde_json::to_string(&stmt).unwrap();
+            let back: Statement = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, stmt, "{}", json);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_shape() {
+        let expr = parse_expression("-1").unwrap();
+        let json = serde_json::to_value(&expr).unwrap();
+        let span = |start: usize, end: usize| {
+            serde_json::json!({
+                "start": { "offset": start, "line": 1, "column": start + 1 },
+                "end": { "offset": end, "line": 1, "column": end + 1 },
+            })
+        };
+        let expected = serde_json::json!({
+            "UnaryExpr": {
+                "op": "Minus",
+                "expr": { "Literal": [{ "UnsignedInteger": 1 }, span(1, 2)] },
+                "span": span(0, 2),
+            }
+        });
+        assert_eq!(json, expected);
+
+        let script = parse_script_with(
+            "SELECT 1 -- one",
+            &ParseOptions {
+                preserve_comments: true,
+                ..ParseOptions::default()
+            },
+        );
+        let json = serde_json::to_string(&script.comments).unwrap();
+        let back: Comments = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, script.comments);
+    }
+
+    #[test]
+    fn test_object_name_isnt_empty() {
+        assert_eq!(ObjectName::new([]), None);
+        let name = ObjectName::qualified([Identifier::new("db")], Identifier::new("t"));
+        assert_eq!(ObjectName::new(name.clone().into_parts()), Some(name));
+
+        #[cfg(feature = "serde")]
+        {
+            let stmt = parse_statement("SELECT a FROM db.t").unwrap();
+            let json = serde_json::to_value(&stmt).unwrap();
+            assert_eq!(
+                json["Select"]["body"]["from"]["BaseRelation"]["table"]["name"]
+                    .as_array()
+                    .unwrap()
+                    .len(),
+                2
+            );
+            assert!(serde_json::from_str::<ObjectName>("[]").is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_statement("SELECT a FROM where").unwrap_err();
+        assert_eq!(
+            error.message(),
+            "`where` is a reserved keyword and cannot be used as an identifier unless quoted"
+        );
+        assert_eq!(error.span(), 14..19);
+
+        // spans are byte offsets, `é` takes two bytes
+        let error = parse_statement("SELECT é FROM where").unwrap_err();
+        assert_eq!(error.span(), 15..20);
+
+        let error = parse_statement("SELECT 1.5e400 FROM t").unwrap_err();
+        assert_eq!(error.message(), "numeric literal is out of range");
+        assert_eq!(error.span(), 7..14);
+    }
+}
@@ -1,6 +1,397 @@
-mod ast;
-mod parser;
+//! The `sql-parser` command line tool, it validates, formats and inspects `.sql` files.
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() {
-    println!("Hello, world!");
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use walkdir::WalkDir;
+
+use sql_parser::ast::Statement;
+use sql_parser::dialect::{
+    AnsiDialect, Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SqliteDialect,
+};
+use sql_parser::format::{format, CommaStyle, FormatOptions, KeywordCase};
+use sql_parser::references::references;
+use sql_parser::transpile::transpile;
+use sql_parser::{parse_script_with, ParseOptions, Script};
+
+#[derive(Parser)]
+#[command(version, about = "Validate, format and inspect SQL")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// The SQL flavour of the input
+    #[arg(long, global = true, value_enum, default_value_t = DialectArg::Generic)]
+    dialect: DialectArg,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that the input parses, reporting every problem
+    Parse(Inputs),
+    /// Format the input, files are rewritten in place
+    Fmt {
+        #[command(flatten)]
+        inputs: Inputs,
+        /// Only report the files that aren't formatted, failing if there are any
+        #[arg(long)]
+        check: bool,
+        #[command(flatten)]
+        options: FormatArgs,
+    },
+    /// Print the syntax tree of the input
+    Ast {
+        #[command(flatten)]
+        inputs: Inputs,
+        #[arg(long, value_enum, default_value_t = AstFormat::Debug)]
+        format: AstFormat,
+    },
+    /// List the tables the input refers to
+    Tables(Inputs),
+    /// Rewrite the input, written in `--dialect`, for another dialect
+    Transpile {
+        #[command(flatten)]
+        inputs: Inputs,
+        /// The dialect to write
+        #[arg(long, value_enum)]
+        to: DialectArg,
+    },
+}
+
+#[derive(Args)]
+struct Inputs {
+    /// Files, or directories searched for `.sql` files, standard input when none or `-`
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+struct FormatArgs {
+    #[arg(long, value_enum, default_value_t = Case::Upper)]
+    keyword_case: Case,
+    /// Spaces per level of indentation
+    #[arg(long, default_value_t = 4)]
+    indent: usize,
+    /// Wrap clauses longer than this
+    #[arg(long, default_value_t = 80)]
+    line_width: usize,
+    /// Put the comma at the start of a wrapped list item
+    #[arg(long)]
+    leading_commas: bool,
+    /// Line up aliases and join conditions
+    #[arg(long)]
+    align: bool,
+    /// Drop comments rather than keeping them
+    #[arg(long)]
+    strip_comments: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DialectArg {
+    Generic,
+    Ansi,
+    Postgres,
+    Mysql,
+    Sqlite,
+}
+
+impl DialectArg {
+    fn dialect(self) -> &'static dyn Dialect {
+        match self {
+            DialectArg::Generic => &GenericDialect,
+            DialectArg::Ansi => &AnsiDialect,
+            DialectArg::Postgres => &PostgreSqlDialect,
+            DialectArg::Mysql => &MySqlDialect,
+            DialectArg::Sqlite => &SqliteDialect,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AstFormat {
+    /// Rust's pretty printed `Debug` tree
+    Debug,
+    /// The statements as JSON, see the `ast` module of the library for the shape
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+impl FormatArgs {
+    fn options(&self, dialect: &'static dyn Dialect) -> FormatOptions {
+        FormatOptions {
+            keyword_case: match self.keyword_case {
+                Case::Upper => KeywordCase::Upper,
+                Case::Lower => KeywordCase::Lower,
+            },
+            indent: self.indent,
+            line_width: self.line_width,
+            commas: if self.leading_commas {
+                CommaStyle::Leading
+            } else {
+                CommaStyle::Trailing
+            },
+            align_aliases: self.align,
+            align_join_conditions: self.align,
+            preserve_comments: !self.strip_comments,
+            dialect,
+        }
+    }
+}
+
+/// A file, or standard input, to process.
+struct Input {
+    /// The file it was read from, `None` for standard input
+    path: Option<PathBuf>,
+    source: String,
+}
+
+impl Input {
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "<stdin>".to_string(),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let inputs = match &cli.command {
+        Command::Parse(inputs)
+        | Command::Tables(inputs)
+        | Command::Fmt { inputs, .. }
+        | Command::Ast { inputs, .. }
+        | Command::Transpile { inputs, .. } => read_inputs(&inputs.paths),
+    };
+    let inputs = match inputs {
+        Ok(inputs) => inputs,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let dialect = cli.dialect.dialect();
+    let ok = match &cli.command {
+        Command::Parse(_) => inputs.iter().all(|input| parse(input, dialect).is_some()),
+        Command::Fmt { check, options, .. } => fmt(&inputs, *check, &options.options(dialect)),
+        Command::Ast { format, .. } => ast(&inputs, *format, dialect),
+        Command::Tables(_) => tables(&inputs, dialect),
+        Command::Transpile { to, .. } => transpile_inputs(&inputs, dialect, to.dialect()),
+    };
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Reads every file in `paths`, searching directories for `.sql` files, or standard input.
+fn read_inputs(paths: &[PathBuf]) -> std::io::Result<Vec<Input>> {
+    if paths.is_empty() || paths.iter().all(|path| path == Path::new("-")) {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        return Ok(vec![Input { path: None, source }]);
+    }
+
+    let mut inputs = Vec::new();
+    for path in sql_files(paths)? {
+        let source = std::fs::read_to_string(&path)?;
+        inputs.push(Input {
+            path: Some(path),
+            source,
+        });
+    }
+    Ok(inputs)
+}
+
+/// The files in `paths`, directories are searched recursively for `.sql` files in name order.
+fn sql_files(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        for entry in WalkDir::new(path).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|ext| ext == "sql")
+            {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Parses `input`, printing its diagnostics when it has errors.
+fn parse(input: &Input, dialect: &'static dyn Dialect) -> Option<Script> {
+    let options = ParseOptions {
+        dialect,
+        ..ParseOptions::default()
+    };
+    let script = parse_script_with(&input.source, &options);
+    if !script.has_errors() {
+        return Some(script);
+    }
+    for diagnostic in &script.diagnostics {
+        eprintln!("{}", diagnostic.render_named(&input.name(), &input.source));
+    }
+    None
+}
+
+fn fmt(inputs: &[Input], check: bool, options: &FormatOptions) -> bool {
+    let mut ok = true;
+    for input in inputs {
+        let formatted = match format(&input.source, options) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                for diagnostic in error.diagnostics() {
+                    eprintln!("{}", diagnostic.render_named(&input.name(), &input.source));
+                }
+                ok = false;
+                continue;
+            }
+        };
+
+        match (&input.path, check) {
+            (_, true) if formatted != input.source => {
+                println!("{}", input.name());
+                ok = false;
+            }
+            (_, true) => {}
+            (None, false) => print!("{}", formatted),
+            (Some(path), false) if formatted != input.source => {
+                if let Err(error) = std::fs::write(path, formatted) {
+                    eprintln!("error: {}: {}", input.name(), error);
+                    ok = false;
+                }
+            }
+            (Some(_), false) => {}
+        }
+    }
+    ok
+}
+
+fn ast(inputs: &[Input], format: AstFormat, dialect: &'static dyn Dialect) -> bool {
+    let mut ok = true;
+    for input in inputs {
+        let Some(script) = parse(input, dialect) else {
+            ok = false;
+            continue;
+        };
+        match format {
+            AstFormat::Debug => println!("{:#?}", script.statements),
+            #[cfg(feature = "serde")]
+            AstFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&script.statements).expect("the tree serializes")
+            ),
+        }
+    }
+    ok
+}
+
+fn tables(inputs: &[Input], dialect: &'static dyn Dialect) -> bool {
+    let mut ok = true;
+    let mut names = BTreeSet::new();
+    for input in inputs {
+        let Some(script) = parse(input, dialect) else {
+            ok = false;
+            continue;
+        };
+        for stmt in &script.statements {
+            statement_tables(stmt, &mut names);
+        }
+    }
+    for name in names {
+        println!("{}", name);
+    }
+    ok
+}
+
+/// Collects the names of the tables `stmt` refers to, those in subqueries included.
+fn statement_tables(stmt: &Statement, names: &mut BTreeSet<String>) {
+    for table_use in references(stmt).tables {
+        names.insert(table_use.table.to_string());
+    }
+}
+
+fn transpile_inputs(
+    inputs: &[Input],
+    from: &'static dyn Dialect,
+    to: &'static dyn Dialect,
+) -> bool {
+    let mut ok = true;
+    for input in inputs {
+        let (sql, diagnostics) = match transpile(&input.source, from, to) {
+            Ok(transpiled) => (Some(transpiled.sql), transpiled.diagnostics),
+            Err(error) => (None, error.diagnostics().to_vec()),
+        };
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic.render_named(&input.name(), &input.source));
+        }
+        ok &= diagnostics.is_empty();
+        if let Some(sql) = sql {
+            println!("{}", sql);
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cli() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_statement_tables() {
+        let script = sql_parser::parse_script(
+            "SELECT a FROM db.t1 JOIN (SELECT b FROM t2) AS s ON a = b; DELETE FROM t3; DROP TABLE t1",
+        );
+        let mut names = BTreeSet::new();
+        for stmt in &script.statements {
+            statement_tables(stmt, &mut names);
+        }
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec!["db.t1", "t1", "t2", "t3"]
+        );
+    }
+
+    #[test]
+    fn test_sql_files() {
+        let dir = std::env::temp_dir().join(format!("sql-parser-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for file in ["b.sql", "a.sql", "notes.txt", "nested/c.sql"] {
+            std::fs::write(dir.join(file), "SELECT 1").unwrap();
+        }
+
+        let files = sql_files(std::slice::from_ref(&dir)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let names = files
+            .iter()
+            .map(|file| file.strip_prefix(&dir).unwrap().to_path_buf())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("a.sql"),
+                PathBuf::from("b.sql"),
+                PathBuf::from("nested/c.sql")
+            ]
+        );
+    }
 }
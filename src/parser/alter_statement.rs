@@ -0,0 +1,104 @@
+use chumsky::prelude::*;
+
+use crate::ast::alter_statement::{AlterStatement, AlterTableOperation};
+use crate::ast::expression::Expression;
+use crate::lexer::Token;
+
+use super::{
+    common::{object_name, table},
+    create_statement::column_specification,
+    identifier, keyword, punct, SyntaxError,
+};
+
+pub fn alter_statement<E>(
+    expr: E,
+) -> impl Parser<Token, AlterStatement, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    let operation = choice((
+        keyword("add")
+            .then(keyword("column").or_not())
+            .ignore_then(column_specification(expr))
+            .map(AlterTableOperation::AddColumn),
+        keyword("drop")
+            .then(keyword("column").or_not())
+            .ignore_then(keyword("if").then(keyword("exists")).or_not())
+            .then(identifier())
+            .map(|(if_exists, column)| AlterTableOperation::DropColumn {
+                column,
+                if_exists: if_exists.is_some(),
+            }),
+        keyword("rename")
+            .then(keyword("column"))
+            .ignore_then(identifier())
+            .then_ignore(keyword("to"))
+            .then(identifier())
+            .map(|(from, to)| AlterTableOperation::RenameColumn { from, to }),
+        keyword("rename")
+            .then(keyword("to"))
+            .ignore_then(object_name())
+            .map(AlterTableOperation::RenameTable),
+    ));
+
+    keyword("alter")
+        .ignore_then(keyword("table"))
+        .ignore_then(table())
+        .then(operation.separated_by(punct(',')).at_least(1))
+        .map_with_span(|(table, operations), span| AlterStatement {
+            table,
+            operations,
+            span,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::Identifier;
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, stream};
+
+    fn parse(sql: &str) -> Result<AlterStatement, Vec<SyntaxError>> {
+        alter_statement(expression(&GenericDialect))
+            .then_ignore(end())
+            .parse(stream(sql))
+    }
+
+    #[test]
+    fn test_alter_statement() {
+        let actual = parse(
+            "ALTER TABLE users ADD email VARCHAR(100) NOT NULL, DROP COLUMN IF EXISTS age, \
+             RENAME COLUMN name TO full_name, RENAME TO app.people",
+        )
+        .unwrap();
+        assert_eq!(actual.operations.len(), 4);
+        assert!(matches!(
+            &actual.operations[0],
+            AlterTableOperation::AddColumn(spec) if spec.sql_type == "VARCHAR(100)"
+        ));
+        assert_eq!(
+            actual.operations[1].without_spans(),
+            AlterTableOperation::DropColumn {
+                column: Identifier::new("age"),
+                if_exists: true
+            }
+        );
+        assert!(matches!(
+            &actual.operations[3],
+            AlterTableOperation::RenameTable(name) if name.to_string() == "app.people"
+        ));
+        assert_eq!(
+            actual.to_string(),
+            "ALTER TABLE users ADD COLUMN email VARCHAR(100) NOT NULL, DROP COLUMN IF EXISTS age, \
+             RENAME COLUMN name TO full_name, RENAME TO app.people"
+        );
+    }
+
+    #[test]
+    fn test_invalid_alter_statement() {
+        assert!(parse("ALTER TABLE users").is_err());
+        assert!(parse("ALTER TABLE users RENAME name TO full_name").is_err());
+    }
+}
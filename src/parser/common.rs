@@ -1,66 +1,110 @@
 use chumsky::prelude::*;
 
-use crate::ast::{column::Column, table::Table};
+use crate::ast::{column::Column, table::Table, ObjectName};
+use crate::lexer::Token;
 
-use super::identifier;
+use super::{expecting, identifier, punct, SyntaxError};
 
-pub fn column() -> impl Parser<char, Column, Error = Simple<char>> + Clone {
-    let simple_col = identifier().map(|column| Column {
-        column,
-        table: None,
-    });
-
-    let qualified_col =
-        identifier()
-            .then_ignore(just("."))
-            .then(identifier())
-            .map(|(tbl, column)| Column {
-                column,
-                table: Some(tbl),
-            });
-
-    choice((qualified_col, simple_col))
+/// Parses one or more dot separated identifiers e.g. `catalog.schema.tbl`.
+pub fn object_name() -> impl Parser<Token, ObjectName, Error = SyntaxError> + Clone {
+    identifier()
+        .separated_by(punct('.'))
+        .at_least(1)
+        .map(|parts| ObjectName::new(parts).expect("at least one part is parsed"))
 }
 
-pub fn table() -> impl Parser<char, Table, Error = Simple<char>> + Clone {
-    let simple_tbl = identifier().map(|name| Table {
-        name,
-        database: None,
-    });
-
-    let qualified_tbl = identifier()
-        .then_ignore(just("."))
-        .then(identifier())
-        .map(|(db, name)| Table {
-            name,
-            database: Some(db),
-        });
+/// Parses a column with any number of qualifying parts, the last part is the column name.
+pub fn column() -> impl Parser<Token, Column, Error = SyntaxError> + Clone {
+    object_name().map(|name| {
+        let mut parts = name.into_parts();
+        let column = parts.pop().expect("object_name parses at least one part");
+        Column {
+            column,
+            table: ObjectName::new(parts),
+        }
+    })
+}
 
-    choice((qualified_tbl, simple_tbl))
+pub fn table() -> impl Parser<Token, Table, Error = SyntaxError> + Clone {
+    expecting(object_name(), "table name").map_with_span(|name, span| Table { name, span })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::ast::Identifier;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::{Identifier, NameResolution};
+    use crate::parser::stream;
 
     #[test]
     fn test_parse_column() {
         let expected = Column {
-            column: Identifier("colA".to_string()),
+            column: Identifier::new("colA"),
             table: None,
         };
-        let actual = column().parse("colA").unwrap();
-        assert_eq!(actual, expected)
+        let actual = column().parse(stream("colA")).unwrap();
+        assert_eq!(actual.without_spans(), expected)
     }
 
     #[test]
     fn test_qualified_parse_column() {
         let expected = Column {
-            column: Identifier("colA".to_string()),
-            table: Some(Identifier("tbl".to_string())),
+            column: Identifier::new("colA"),
+            table: Some(ObjectName::from(Identifier::new("tbl"))),
+        };
+        let actual = column().parse(stream("tbl.colA")).unwrap();
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_multi_part_column() {
+        let expected = Column {
+            column: Identifier::new("colA"),
+            table: Some(
+                ObjectName::new(vec![
+                    Identifier::new("catalog"),
+                    Identifier::new("schema"),
+                    Identifier::new("tbl"),
+                ])
+                .unwrap(),
+            ),
         };
-        let actual = column().parse("tbl.colA").unwrap();
-        assert_eq!(actual, expected)
+        let actual = column().parse(stream("catalog.schema.tbl.colA")).unwrap();
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_multi_part_table() {
+        let actual = table().parse(stream("catalog.`my schema`.tbl")).unwrap();
+        let expected = Table::new(
+            ObjectName::new(vec![
+                Identifier::new("catalog"),
+                Identifier::quoted('`', "my schema"),
+                Identifier::new("tbl"),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_resolve_table_name() {
+        let tbl = table().parse(stream("catalog.schema.tbl")).unwrap();
+        let resolved = tbl.resolve(NameResolution::CatalogSchemaObject).unwrap();
+        assert_eq!(
+            resolved.catalog.without_spans(),
+            Some(Identifier::new("catalog"))
+        );
+        assert_eq!(
+            resolved.schema.without_spans(),
+            Some(Identifier::new("schema"))
+        );
+        assert_eq!(resolved.object.without_spans(), Identifier::new("tbl"));
+        assert_eq!(tbl.resolve(NameResolution::SchemaObject), None);
+
+        let tbl = table().parse(stream("db.tbl")).unwrap();
+        let resolved = tbl.resolve(NameResolution::SchemaObject).unwrap();
+        assert_eq!(resolved.catalog, None);
+        assert_eq!(resolved.schema.without_spans(), Some(Identifier::new("db")));
     }
 }
@@ -0,0 +1,247 @@
+use chumsky::prelude::*;
+
+use crate::ast::column::{Column, ColumnConstraint, ColumnSpecification};
+use crate::ast::create_statement::{CreateStatement, TableConstraint};
+use crate::ast::expression::Expression;
+use crate::lexer::Token;
+
+use super::{common::table, expecting, identifier, keyword, punct, SyntaxError};
+
+/// Words that end a column type, starting the constraints after it.
+const CONSTRAINT_WORDS: &[&str] = &[
+    "auto_increment",
+    "autoincrement",
+    "comment",
+    "default",
+    "primary",
+    "unique",
+];
+
+pub fn create_statement<E>(
+    expr: E,
+) -> impl Parser<Token, CreateStatement, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    let element = table_constraint()
+        .map(Err)
+        .or(column_specification(expr).map(Ok));
+
+    keyword("create")
+        .ignore_then(keyword("table"))
+        .ignore_then(
+            keyword("if")
+                .then(keyword("not"))
+                .then(keyword("exists"))
+                .or_not(),
+        )
+        .then(table())
+        .then(
+            element
+                .separated_by(punct(','))
+                .at_least(1)
+                .delimited_by(punct('('), punct(')')),
+        )
+        .map_with_span(|((if_not_exists, table), elements), span| {
+            let (columns, constraints) = elements.into_iter().partition::<Vec<_>, _>(Result::is_ok);
+            CreateStatement {
+                table,
+                if_not_exists: if_not_exists.is_some(),
+                columns: columns.into_iter().filter_map(Result::ok).collect(),
+                constraints: constraints.into_iter().filter_map(Result::err).collect(),
+                span,
+            }
+        })
+}
+
+/// Parses a column definition e.g. `name VARCHAR(20) NOT NULL DEFAULT ''`.
+pub fn column_specification<E>(
+    expr: E,
+) -> impl Parser<Token, ColumnSpecification, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    let constraint = choice((
+        keyword("not")
+            .then(keyword("null"))
+            .to(ColumnConstraint::NotNull),
+        keyword("null").to(ColumnConstraint::Nullable),
+        keyword("primary")
+            .then(keyword("key"))
+            .to(ColumnConstraint::PrimaryKey),
+        keyword("unique")
+            .then(keyword("key").or_not())
+            .to(ColumnConstraint::Unique),
+        keyword("auto_increment")
+            .or(keyword("autoincrement"))
+            .to(ColumnConstraint::AutoIncrement),
+        keyword("default")
+            .ignore_then(expr)
+            .map(ColumnConstraint::Default),
+    ));
+    let comment = keyword("comment").ignore_then(string());
+
+    identifier()
+        .then(sql_type())
+        .then(constraint.map(Ok).or(comment.map(Err)).repeated())
+        .map(|((name, sql_type), items)| {
+            let mut spec = ColumnSpecification {
+                column: Column {
+                    column: name,
+                    table: None,
+                },
+                sql_type,
+                constraint: Vec::new(),
+                comment: None,
+            };
+            for item in items {
+                match item {
+                    Ok(constraint) => spec.constraint.push(constraint),
+                    Err(comment) => spec.comment = Some(comment),
+                }
+            }
+            spec
+        })
+}
+
+/// Parses a column type as written e.g. `INT`, `DOUBLE PRECISION` or `DECIMAL(10, 2) UNSIGNED`.
+fn sql_type() -> impl Parser<Token, String, Error = SyntaxError> + Clone {
+    let word = filter_map(|span, token| match token {
+        Token::Ident(word) if !CONSTRAINT_WORDS.contains(&word.to_ascii_lowercase().as_str()) => {
+            Ok(word)
+        }
+        token => Err(SyntaxError::unexpected(span, token)),
+    });
+    let number = filter_map(|span, token| match token {
+        Token::Number(number) => Ok(number),
+        token => Err(SyntaxError::unexpected(span, token)),
+    });
+    let args = number
+        .separated_by(punct(','))
+        .at_least(1)
+        .delimited_by(punct('('), punct(')'));
+
+    let sql_type = word
+        .repeated()
+        .at_least(1)
+        .then(args.or_not())
+        .then(word.repeated())
+        .map(|((words, args), suffix)| {
+            let mut sql_type = words.join(" ");
+            if let Some(args) = args {
+                sql_type = format!("{}({})", sql_type, args.join(", "));
+            }
+            for word in suffix {
+                sql_type = format!("{} {}", sql_type, word);
+            }
+            sql_type
+        });
+
+    expecting(sql_type, "column type")
+}
+
+/// Parses `PRIMARY KEY (a, b)` or `UNIQUE (a, b)`, optionally named by `CONSTRAINT name`.
+fn table_constraint() -> impl Parser<Token, TableConstraint, Error = SyntaxError> + Clone {
+    let columns = identifier()
+        .separated_by(punct(','))
+        .at_least(1)
+        .delimited_by(punct('('), punct(')'));
+
+    keyword("constraint")
+        .then(identifier())
+        .or_not()
+        .ignore_then(choice((
+            keyword("primary")
+                .then(keyword("key"))
+                .ignore_then(columns.clone())
+                .map(TableConstraint::PrimaryKey),
+            keyword("unique")
+                .then(keyword("key").or_not())
+                .ignore_then(columns)
+                .map(TableConstraint::Unique),
+        )))
+}
+
+fn string() -> impl Parser<Token, String, Error = SyntaxError> + Clone {
+    filter_map(|span, token| match token {
+        Token::String(value) => Ok(value),
+        token => Err(SyntaxError::unexpected(span, token)),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::expression::Literal;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::Identifier;
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, stream};
+
+    fn parse(sql: &str) -> Result<CreateStatement, Vec<SyntaxError>> {
+        create_statement(expression(&GenericDialect))
+            .then_ignore(end())
+            .parse(stream(sql))
+    }
+
+    #[test]
+    fn test_create_statement() {
+        let actual = parse(
+            "CREATE TABLE IF NOT EXISTS shop.orders (
+                id INT PRIMARY KEY AUTO_INCREMENT,
+                total DECIMAL(10, 2) UNSIGNED NOT NULL DEFAULT 0,
+                note character varying(200) COMMENT 'free text',
+                CONSTRAINT u UNIQUE (total, note)
+            )",
+        )
+        .unwrap();
+        assert!(actual.if_not_exists);
+        assert_eq!(actual.table.to_string(), "shop.orders");
+
+        let types = actual
+            .columns
+            .iter()
+            .map(|c| c.sql_type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            ["INT", "DECIMAL(10, 2) UNSIGNED", "character varying(200)"]
+        );
+        assert_eq!(
+            actual.columns[0].constraint,
+            [
+                ColumnConstraint::PrimaryKey,
+                ColumnConstraint::AutoIncrement
+            ]
+        );
+        assert!(matches!(
+            &actual.columns[1].constraint[..],
+            [
+                ColumnConstraint::NotNull,
+                ColumnConstraint::Default(Expression::Literal(Literal::UnsignedInteger(0), _))
+            ]
+        ));
+        assert_eq!(actual.columns[2].comment.as_deref(), Some("free text"));
+        assert_eq!(
+            actual.constraints.without_spans(),
+            [TableConstraint::Unique(vec![
+                Identifier::new("total"),
+                Identifier::new("note")
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_create_statement_display() {
+        let sql =
+            "CREATE TABLE t (a INT NOT NULL DEFAULT 1, b TEXT COMMENT 'it''s', PRIMARY KEY (a))";
+        assert_eq!(parse(sql).unwrap().to_string(), sql);
+    }
+
+    #[test]
+    fn test_invalid_create_statement() {
+        assert!(parse("CREATE TABLE t ()").is_err());
+        assert!(parse("CREATE TABLE t (a)").is_err());
+        assert!(parse("CREATE TABLE t (a INT NOT)").is_err());
+    }
+}
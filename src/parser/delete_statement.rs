@@ -0,0 +1,27 @@
+use chumsky::prelude::*;
+
+use crate::ast::{delete_statement::DeleteStatement, expression::Expression};
+use crate::lexer::Token;
+
+use super::{common::table, keyword, recovery::clause_expression, SyntaxError};
+
+pub fn delete_statement<E>(
+    expr: E,
+) -> impl Parser<Token, DeleteStatement, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    keyword("delete")
+        .ignore_then(keyword("from"))
+        .ignore_then(table())
+        .then(
+            keyword("where")
+                .ignore_then(clause_expression(expr))
+                .or_not(),
+        )
+        .map_with_span(|(table, where_clause), span| DeleteStatement {
+            table,
+            where_clause,
+            span,
+        })
+}
@@ -0,0 +1,41 @@
+use chumsky::prelude::*;
+
+use crate::ast::drop_statement::DropStatement;
+use crate::lexer::Token;
+
+use super::{common::table, keyword, SyntaxError};
+
+pub fn drop_statement() -> impl Parser<Token, DropStatement, Error = SyntaxError> + Clone {
+    keyword("drop")
+        .ignore_then(keyword("table"))
+        .ignore_then(keyword("if").then(keyword("exists")).or_not())
+        .then(table())
+        .map_with_span(|(if_exists, table), span| DropStatement {
+            table,
+            if_exists: if_exists.is_some(),
+            span,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::Identifier;
+    use crate::parser::stream;
+
+    #[test]
+    fn test_drop_statement() {
+        let actual = drop_statement()
+            .parse(stream("DROP TABLE IF EXISTS users"))
+            .unwrap();
+        assert!(actual.if_exists);
+        assert_eq!(
+            actual.table.name.name().without_spans(),
+            Identifier::new("users")
+        );
+
+        let actual = drop_statement().parse(stream("drop table users")).unwrap();
+        assert!(!actual.if_exists)
+    }
+}
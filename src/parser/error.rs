@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use chumsky::Parser;
+
+use crate::ast::span::Span;
+use crate::lexer::Token;
+
+/// The error produced by every parser in this module.
+///
+/// Unlike chumsky's `Simple` it keeps every label it is given, so merged errors report all the
+/// alternatives that were expected e.g. "expected one of FROM, WHERE or end of input".
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    span: Span,
+    reason: SyntaxErrorReason,
+    expected: BTreeSet<String>,
+    found: Option<String>,
+    labelled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxErrorReason {
+    /// An input was found that doesn't fit the grammar
+    Unexpected,
+    /// A delimiter such as `(` was opened at `span` but never closed
+    Unclosed { span: Span, delimiter: String },
+    /// An error with a hand written message e.g. a reserved word used as an identifier
+    Custom(String),
+}
+
+impl SyntaxError {
+    pub fn custom<M: ToString>(span: Span, message: M) -> Self {
+        SyntaxError {
+            span,
+            reason: SyntaxErrorReason::Custom(message.to_string()),
+            expected: BTreeSet::new(),
+            found: None,
+            labelled: false,
+        }
+    }
+
+    /// An error where `found` doesn't fit the grammar, without saying what was expected instead.
+    pub fn unexpected<F: ToString>(span: Span, found: F) -> Self {
+        SyntaxError {
+            span,
+            reason: SyntaxErrorReason::Unexpected,
+            expected: BTreeSet::new(),
+            found: Some(found.to_string()),
+            labelled: false,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn reason(&self) -> &SyntaxErrorReason {
+        &self.reason
+    }
+
+    /// Descriptions of what would have been accepted, `end of input` included
+    pub fn expected(&self) -> impl Iterator<Item = &str> {
+        self.expected.iter().map(String::as_str)
+    }
+
+    /// The input found instead, `None` at the end of input
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+}
+
+/// Describes the errors `parser` raises before consuming any input as `what` e.g. "expression",
+/// rather than listing every token it could have started with.
+///
+/// Errors raised further into the input are left alone, they already say precisely what was missing.
+pub fn expecting<P, O, W>(parser: P, what: W) -> impl Parser<Token, O, Error = SyntaxError> + Clone
+where
+    P: Parser<Token, O, Error = SyntaxError> + Clone,
+    W: Into<String>,
+{
+    let what = what.into();
+    parser.map_err_with_span(move |mut error, span| {
+        if error.span.start.offset == span.start.offset {
+            error.expected = BTreeSet::from([what.clone()]);
+            error.labelled = true;
+        }
+        error
+    })
+}
+
+/// The inputs [`SyntaxError`] is raised for, the characters read by the lexer and the tokens read
+/// by the parsers.
+pub trait Input: Display + Hash + Eq {
+    /// A message to report when this input is found instead of "unexpected ..." e.g. for a
+    /// malformed token
+    fn error(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Input for char {}
+
+fn describe<I: Display>(token: Option<I>) -> String {
+    match token {
+        Some(token) => format!("`{}`", token),
+        None => "end of input".to_string(),
+    }
+}
+
+impl<I: Input> chumsky::Error<I> for SyntaxError {
+    type Span = Span;
+    type Label = String;
+
+    fn expected_input_found<Iter: IntoIterator<Item = Option<I>>>(
+        span: Self::Span,
+        expected: Iter,
+        found: Option<I>,
+    ) -> Self {
+        let reason = match found.as_ref().and_then(Input::error) {
+            Some(message) => SyntaxErrorReason::Custom(message),
+            None => SyntaxErrorReason::Unexpected,
+        };
+        SyntaxError {
+            span,
+            reason,
+            expected: expected.into_iter().map(describe).collect(),
+            found: found.map(|token| token.to_string()),
+            labelled: false,
+        }
+    }
+
+    fn unclosed_delimiter(
+        unclosed_span: Self::Span,
+        unclosed: I,
+        span: Self::Span,
+        expected: I,
+        found: Option<I>,
+    ) -> Self {
+        SyntaxError {
+            span,
+            reason: SyntaxErrorReason::Unclosed {
+                span: unclosed_span,
+                delimiter: unclosed.to_string(),
+            },
+            expected: BTreeSet::from([describe(Some(expected))]),
+            found: found.map(|token| token.to_string()),
+            labelled: false,
+        }
+    }
+
+    /// The innermost label wins, it replaces the individual tokens the labelled parser expected.
+    fn with_label(mut self, label: Self::Label) -> Self {
+        if !self.labelled {
+            self.expected = BTreeSet::from([label]);
+            self.labelled = true;
+        }
+        self
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        if let SyntaxErrorReason::Unexpected = self.reason {
+            self.reason = other.reason;
+        }
+        self.expected.extend(other.expected);
+        self.labelled |= other.labelled;
+        self
+    }
+}
@@ -1,195 +1,230 @@
-use super::{common::column, identifier};
+use super::{
+    common::{column, object_name},
+    dialect_op, expecting, keyword, op, punct,
+    recovery::expression_list,
+    SyntaxError,
+};
 use crate::ast::expression::{
     AggregationFunction, BinOp, ColumnReference,
     Expression::{self, *},
-    Literal, UnaryOp,
+    Literal, NiladicFunction, Parameter, UnaryOp,
 };
+use crate::ast::span::Spanned;
+use crate::dialect::Dialect;
+use crate::lexer::Token;
 use chumsky::prelude::*;
 
-pub fn expression() -> impl Parser<char, Expression, Error = Simple<char>> + Clone {
-    let op = |c| just(c).padded();
+/// Parses an expression.
+///
+/// Operators bind tighter the later they are in this list, each level is left associative:
+/// `OR`, `AND`, prefix `NOT`, comparisons and `[NOT] IN`, `+ -`, `* / %` then prefix `-` and `!`.
+/// Parentheses group an expression without adding a node to the tree. Operators `dialect`
+/// doesn't support, e.g. `!=` in ANSI SQL, are reported.
+pub fn expression(
+    dialect: &'static dyn Dialect,
+) -> impl Parser<Token, Expression, Error = SyntaxError> + Clone {
+    let operator = move |op| dialect_op(dialect, op);
 
-    let unary_op = choice((
-        op("!").map(|_| UnaryOp::LogicalNot),
-        op("-").map(|_| UnaryOp::Minus),
-    ));
+    recursive(|expr| {
+        // ColumnReference, Literal and Parameter
+        let atom = literal()
+            .map_with_span(Literal)
+            .or(parameter().map_with_span(Parameter))
+            .or(column_ref());
 
-    let bin_op = choice((
-        op("+").map(|_| BinOp::Addition),
-        op("*").map(|_| BinOp::Multiplication),
-        op(">").map(|_| BinOp::Gt),
-        op("<").map(|_| BinOp::Lt),
-        op("=").map(|_| BinOp::Eq),
-    ));
+        // function expression
+        let fn_expr = function_op()
+            .then(expression_list(expr.clone()))
+            .map_with_span(|(func, args), span| FunctionExpression { func, args, span });
 
-    let expr = recursive(|expr| {
-        // ColumnReference and Literal
-        let atom = literal().map(Literal).or(column_ref());
+        // any other function, tried before a column reference of the same name
+        let call = object_name()
+            .then(expression_list(expr.clone()))
+            .map_with_span(|(name, args), span| Function { name, args, span });
 
-        let aliased_expr = atom
+        // the span is widened to cover the parentheses
+        let nested = expr
             .clone()
-            .or(expr.clone())
-            .then_ignore(just("AS").padded())
-            .then(identifier())
-            .map(|(exp, alias)| Alias {
-                expr: Box::new(exp),
-                alias,
-            });
+            .delimited_by(punct('('), punct(')'))
+            .map_with_span(Expression::with_span);
 
-        let unary_expr = unary_op.then(expr.clone()).map(|(op, exp)| UnaryExpr {
-            op,
-            expr: Box::new(exp),
-        });
+        // e.g. `CURRENT_DATE`, after calls so that `current_date()` stays one
+        let niladic = niladic_function().map_with_span(Niladic);
 
-        let bin_expr = atom
-            .clone()
-            .or(unary_expr.clone())
-            .or(aliased_expr.clone())
-            .then(bin_op)
-            .then(atom.clone().or(unary_expr.clone()).or(aliased_expr.clone()))
-            .map(|((lhs, op), rhs)| BinaryExpr {
-                left: Box::new(lhs),
-                op,
-                right: Box::new(rhs),
-            });
+        let primary = choice((fn_expr, call, nested, niladic, atom));
+
+        let unary_op = operator("-")
+            .to(UnaryOp::Minus)
+            .or(operator("!").to(UnaryOp::LogicalNot));
+        let unary = expecting(prefix(unary_op, primary), "expression").boxed();
+
+        let product = binary(
+            unary,
+            choice((
+                operator("*").to(BinOp::Multiplication),
+                operator("/").to(BinOp::Division),
+                operator("%").to(BinOp::Mod),
+            )),
+        );
 
-        // IN / NOT IN expression
-        let in_notin_expr = atom
+        let sum = binary(
+            product,
+            operator("+")
+                .to(BinOp::Addition)
+                .or(operator("-").to(BinOp::Subtraction)),
+        )
+        .boxed();
+
+        // `[NOT] IN (...)` ranks with the comparisons, the list being its right operand
+        let in_list = keyword("not")
+            .or_not()
+            .then_ignore(keyword("in"))
+            .then(expression_list(expr))
+            .map_with_span(|(not, list), span| (not.is_some(), list, span));
+
+        let comparison = sum
             .clone()
-            .or(unary_expr.clone())
-            .or(aliased_expr.clone())
-            .or(bin_expr.clone())
-            .then(op("or").or(op("NOT")).or_not())
-            .then_ignore(op("in").or(op("IN")))
             .then(
-                atom.clone()
-                    .or(unary_expr.clone())
-                    .or(aliased_expr.clone())
-                    .or(bin_expr.clone())
-                    .separated_by(op(","))
-                    .delimited_by(op("("), op(")")),
+                choice((
+                    operator("=").to(BinOp::Eq),
+                    operator("<>").to(BinOp::NotEq),
+                    operator("!=").to(BinOp::NotEq),
+                    operator("<").to(BinOp::Lt),
+                    operator(">").to(BinOp::Gt),
+                    operator("<=").to(BinOp::LtEq),
+                    operator(">=").to(BinOp::GtEq),
+                ))
+                .then(sum)
+                .map(Ok)
+                .or(in_list.map(Err))
+                .repeated(),
             )
-            .map(|((exp, nt_kw), right)| {
-                let not_in = nt_kw.is_some();
-                In {
-                    left: Box::new(exp),
+            .foldl(|lhs, operation| match operation {
+                Ok((op, rhs)) => BinaryExpr {
+                    span: lhs.span().union(rhs.span()),
+                    left: Box::new(lhs),
+                    op,
+                    right: Box::new(rhs),
+                },
+                Err((not_in, right, span)) => In {
+                    span: lhs.span().union(span),
+                    left: Box::new(lhs),
                     right,
                     not_in,
-                }
+                },
             });
 
-        // function expression
-        let fn_expr = function_op()
-            .then_ignore(just("("))
-            .then(
-                atom.clone()
-                    .or(unary_expr.clone())
-                    .or(aliased_expr.clone())
-                    .or(bin_expr.clone())
-                    .or(in_notin_expr.clone())
-                    .separated_by(op(",")),
-            )
-            .then_ignore(op(")"))
-            .map(|(func, args)| FunctionExpression { func, args });
-
-        fn_expr
-            .or(in_notin_expr.clone())
-            .or(bin_expr.clone())
-            .or(aliased_expr)
-            .or(unary_expr.clone())
-            .or(atom)
-            .padded()
-    });
+        let not = expecting(
+            prefix(keyword("not").to(UnaryOp::LogicalNot), comparison),
+            "expression",
+        )
+        .boxed();
 
-    expr
-}
+        let and = binary(not, keyword("and").to(BinOp::And));
 
-pub fn column_ref() -> impl Parser<char, Expression, Error = Simple<char>> + Clone {
-    let star = just("*").map(|_| ColumnReference(ColumnReference::Wildcard));
-    let qualified_star = identifier()
-        .then_ignore(just("."))
-        .then_ignore(just("*"))
-        .map(|id| ColumnReference(ColumnReference::QualifiedWildcard(id)));
+        binary(and, keyword("or").to(BinOp::Or)).boxed()
+    })
+}
 
-    qualified_star
-        .or(star)
-        .or(column().map(|col| ColumnReference(ColumnReference::Column(col))))
+/// Applies any number of prefix operators to an `operand`, the innermost operator is the last.
+fn prefix<O, P>(
+    operator: O,
+    operand: P,
+) -> impl Parser<Token, Expression, Error = SyntaxError> + Clone
+where
+    O: Parser<Token, UnaryOp, Error = SyntaxError> + Clone,
+    P: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    operator
+        .map_with_span(|op, span| (op, span))
+        .repeated()
+        .then(operand)
+        .foldr(|(op, span), exp| UnaryExpr {
+            span: span.union(exp.span()),
+            op,
+            expr: Box::new(exp),
+        })
 }
 
-pub fn literal() -> impl Parser<char, Literal, Error = Simple<char>> + Clone {
-    let exp = one_of("eE").chain(one_of("+-").or_not().chain::<char, _, _>(text::digits(10)));
+/// Parses `operand`s separated by a left associative binary `operator`.
+fn binary<P, O>(
+    operand: P,
+    operator: O,
+) -> impl Parser<Token, Expression, Error = SyntaxError> + Clone
+where
+    P: Parser<Token, Expression, Error = SyntaxError> + Clone,
+    O: Parser<Token, BinOp, Error = SyntaxError> + Clone,
+{
+    operand
+        .clone()
+        .then(operator.then(operand).repeated())
+        .foldl(|lhs, (op, rhs)| BinaryExpr {
+            span: lhs.span().union(rhs.span()),
+            left: Box::new(lhs),
+            op,
+            right: Box::new(rhs),
+        })
+}
 
-    let integer = filter(|c: &char| c.is_ascii_digit() && *c != '0')
-        .chain::<_, Vec<char>, _>(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated())
-        .or(just('0').map(|c| vec![c]));
+pub fn column_ref() -> impl Parser<Token, Expression, Error = SyntaxError> + Clone {
+    let star = op("*").to(ColumnReference::Wildcard);
+    let qualified_star = object_name()
+        .then_ignore(punct('.'))
+        .then_ignore(op("*"))
+        .map(ColumnReference::QualifiedWildcard);
 
-    let frac = just('.')
-        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit()))
-        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated());
+    qualified_star
+        .or(star)
+        .or(column().map(ColumnReference::Column))
+        .map_with_span(ColumnReference)
+}
 
-    let number = integer
-        .chain::<char, _, _>(frac.or_not().flatten())
-        .chain::<char, _, _>(exp.or_not().flatten())
-        .try_map(|chars, span| {
-            let str = chars.into_iter().filter(|c| *c != '_').collect::<String>();
+pub fn literal() -> impl Parser<Token, Literal, Error = SyntaxError> + Clone {
+    let null_literal = keyword("null").to(Literal::Null);
+    let boolean_literal = keyword("true")
+        .to(Literal::Boolean(true))
+        .or(keyword("false").to(Literal::Boolean(false)));
 
-            if let Ok(i) = str.parse::<i64>() {
+    let value = filter_map(|span, token| match token {
+        Token::Number(number) => {
+            let digits = number.replace('_', "");
+            if let Ok(i) = digits.parse::<i64>() {
                 Ok(Literal::UnsignedInteger(i))
-            } else if let Ok(f) = str.parse::<f64>() {
+            } else if let Ok(f) = digits.parse::<f64>() {
                 Ok(Literal::UnsignedFloat(f))
             } else {
-                Err(Simple::expected_input_found(span, None, None))
+                Err(SyntaxError::unexpected(span, number))
             }
-        });
+        }
+        Token::String(value) => Ok(Literal::String(value)),
+        Token::Date(date) => Ok(Literal::Date(date)),
+        token => Err(SyntaxError::unexpected(span, token)),
+    });
 
-    let date_literal = filter(|c: &char| c.is_ascii_digit())
-        .repeated()
-        .exactly(4)
-        .collect::<String>()
-        .then_ignore(just::<_, _, Simple<char>>("-"))
-        .then(
-            filter(|c: &char| c.is_ascii_digit())
-                .repeated()
-                .exactly(2)
-                .collect::<String>(),
-        )
-        .then_ignore(just("-"))
-        .then(
-            filter(|c: &char| c.is_ascii_digit())
-                .repeated()
-                .exactly(2)
-                .collect::<String>(),
-        )
-        .map(|((year, month), day)| Literal::Date(format!("{}-{}-{}", year, month, day)));
-
-    let null_literal = text::keyword("null")
-        .or(text::keyword("NULL"))
-        .ignored()
-        .map(|_| Literal::Null);
-    let string_literal = just('"')
-        .ignore_then(text::ident())
-        .then_ignore(just('"'))
-        .map(Literal::String);
-
-    choice((null_literal, date_literal, number, string_literal))
+    choice((null_literal, boolean_literal, value))
+}
+
+pub fn parameter() -> impl Parser<Token, Parameter, Error = SyntaxError> + Clone {
+    filter_map(|span, token| match token {
+        Token::Parameter(parameter) => Ok(parameter),
+        token => Err(SyntaxError::unexpected(span, token)),
+    })
+}
+
+pub fn niladic_function() -> impl Parser<Token, NiladicFunction, Error = SyntaxError> + Clone {
+    choice((
+        keyword("current_date").to(NiladicFunction::CurrentDate),
+        keyword("current_time").to(NiladicFunction::CurrentTime),
+        keyword("current_timestamp").to(NiladicFunction::CurrentTimestamp),
+    ))
 }
 
-pub fn function_op() -> impl Parser<char, AggregationFunction, Error = Simple<char>> + Clone {
-    let count_kw = text::keyword("count")
-        .or(text::keyword("COUNT"))
-        .to(AggregationFunction::Count);
-    let sum_kw = text::keyword("sum")
-        .or(text::keyword("SUM"))
-        .to(AggregationFunction::Sum);
-    let avg_kw = text::keyword("avg")
-        .or(text::keyword("AVG"))
-        .to(AggregationFunction::Avg);
-    let max_kw = text::keyword("max")
-        .or(text::keyword("MAX"))
-        .to(AggregationFunction::Max);
-    let min_kw = text::keyword("min")
-        .or(text::keyword("MIN"))
-        .to(AggregationFunction::Min);
+pub fn function_op() -> impl Parser<Token, AggregationFunction, Error = SyntaxError> + Clone {
+    let count_kw = keyword("count").to(AggregationFunction::Count);
+    let sum_kw = keyword("sum").to(AggregationFunction::Sum);
+    let avg_kw = keyword("avg").to(AggregationFunction::Avg);
+    let max_kw = keyword("max").to(AggregationFunction::Max);
+    let min_kw = keyword("min").to(AggregationFunction::Min);
 
     choice((count_kw, sum_kw, avg_kw, max_kw, min_kw))
 }
@@ -197,17 +232,199 @@ pub fn function_op() -> impl Parser<char, AggregationFunction, Error = Simple<ch
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ast::column::Column;
     use crate::ast::expression::*;
+    use crate::ast::span::{IgnoreSpans, Span};
+    use crate::ast::{Identifier, ObjectName};
+    use crate::dialect::GenericDialect;
+    use crate::parser::stream;
 
     #[test]
     fn test_literal() {
-        let actual = literal().parse(r#""col""#).unwrap();
+        let actual = literal().parse(stream("'col'")).unwrap();
         assert_eq!(actual, Literal::String("col".to_string()));
 
-        let actual_ufloat = literal().parse("20.24").unwrap();
+        let actual_escaped = literal().parse(stream("'it''s a string'")).unwrap();
+        assert_eq!(actual_escaped, Literal::String("it's a string".to_string()));
+
+        let actual_ufloat = literal().parse(stream("20.24")).unwrap();
         assert_eq!(actual_ufloat, Literal::UnsignedFloat(20.24));
 
-        let actual_date = literal().parse("2024-01-13").unwrap();
-        assert_eq!(actual_date, Literal::Date("2024-01-13".to_string()))
+        let actual_date = literal().parse(stream("2024-01-13")).unwrap();
+        assert_eq!(actual_date, Literal::Date("2024-01-13".to_string()));
+
+        let actual_bool = literal().parse(stream("TRUE")).unwrap();
+        assert_eq!(actual_bool, Literal::Boolean(true))
+    }
+
+    #[test]
+    fn test_function_op_any_case() {
+        for func in ["count", "COUNT", "Count"] {
+            assert_eq!(
+                function_op().parse(stream(func)),
+                Ok(AggregationFunction::Count)
+            );
+        }
+    }
+
+    #[test]
+    fn test_qualified_wildcard() {
+        let actual = column_ref().parse(stream("schema.tbl.*")).unwrap();
+        let expected = Expression::ColumnReference(
+            ColumnReference::QualifiedWildcard(
+                ObjectName::new(vec![Identifier::new("schema"), Identifier::new("tbl")]).unwrap(),
+            ),
+            Span::default(),
+        );
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    fn num(n: i64) -> Expression {
+        Expression::Literal(Literal::UnsignedInteger(n), Span::default())
+    }
+
+    fn col(name: &str) -> Expression {
+        Expression::ColumnReference(
+            ColumnReference::Column(Column {
+                column: Identifier::new(name),
+                table: None,
+            }),
+            Span::default(),
+        )
+    }
+
+    fn bin(left: Expression, op: BinOp, right: Expression) -> Expression {
+        Expression::BinaryExpr {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+            span: Span::default(),
+        }
+    }
+
+    fn not(expr: Expression) -> Expression {
+        Expression::UnaryExpr {
+            op: UnaryOp::LogicalNot,
+            expr: Box::new(expr),
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        let actual = expression(&GenericDialect)
+            .parse(stream("1 + 2 * 3 - 4 % 5"))
+            .unwrap();
+        let expected = bin(
+            bin(
+                num(1),
+                BinOp::Addition,
+                bin(num(2), BinOp::Multiplication, num(3)),
+            ),
+            BinOp::Subtraction,
+            bin(num(4), BinOp::Mod, num(5)),
+        );
+        assert_eq!(actual.without_spans(), expected);
+
+        let actual = expression(&GenericDialect)
+            .parse(stream("(1 + 2) * 3"))
+            .unwrap();
+        let expected = bin(
+            bin(num(1), BinOp::Addition, num(2)),
+            BinOp::Multiplication,
+            num(3),
+        );
+        assert_eq!(actual.without_spans(), expected);
+        assert_eq!(actual.span().range(), 0..11);
+    }
+
+    #[test]
+    fn test_logical_precedence() {
+        let actual = expression(&GenericDialect)
+            .parse(stream("NOT a >= 1 AND b <> 2 OR c"))
+            .unwrap();
+        let expected = bin(
+            bin(
+                not(bin(col("a"), BinOp::GtEq, num(1))),
+                BinOp::And,
+                bin(col("b"), BinOp::NotEq, num(2)),
+            ),
+            BinOp::Or,
+            col("c"),
+        );
+        assert_eq!(actual.without_spans(), expected);
+
+        let actual = expression(&GenericDialect)
+            .parse(stream("a = 1 NOT IN (1, 2)"))
+            .unwrap();
+        assert!(matches!(
+            actual,
+            Expression::In { left, not_in: true, .. } if matches!(*left, Expression::BinaryExpr { op: BinOp::Eq, .. })
+        ));
+    }
+
+    #[test]
+    fn test_alias_belongs_to_select_item() {
+        assert!(expression(&GenericDialect)
+            .then_ignore(end())
+            .parse(stream("a AS b"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_function_call() {
+        let actual = expression(&GenericDialect)
+            .parse(stream("COALESCE(a, 1) + pg_catalog.now()"))
+            .unwrap();
+        let expected = bin(
+            Expression::Function {
+                name: ObjectName::new(vec![Identifier::new("COALESCE")]).unwrap(),
+                args: vec![col("a"), num(1)],
+                span: Span::default(),
+            },
+            BinOp::Addition,
+            Expression::Function {
+                name: ObjectName::new(vec![Identifier::new("pg_catalog"), Identifier::new("now")])
+                    .unwrap(),
+                args: vec![],
+                span: Span::default(),
+            },
+        );
+        assert_eq!(actual.without_spans(), expected);
+        assert_eq!(actual.to_string(), "COALESCE(a, 1) + pg_catalog.now()");
+    }
+
+    #[test]
+    fn test_niladic_function() {
+        let parse = |sql| {
+            expression(&GenericDialect)
+                .parse(stream(sql))
+                .unwrap()
+                .without_spans()
+        };
+        assert_eq!(
+            parse("current_timestamp"),
+            Niladic(NiladicFunction::CurrentTimestamp, Span::default())
+        );
+        assert_eq!(parse("Current_Date").to_string(), "CURRENT_DATE");
+        // with parentheses it's an ordinary call, quoted it's a column
+        assert!(matches!(parse("current_date()"), Function { .. }));
+        assert!(matches!(parse("\"current_time\""), ColumnReference(..)));
+    }
+
+    #[test]
+    fn test_parameter() {
+        assert_eq!(
+            parameter().parse(stream("?")).unwrap(),
+            Parameter::Anonymous
+        );
+        assert_eq!(
+            parameter().parse(stream("$2")).unwrap(),
+            Parameter::Positional(2)
+        );
+        assert_eq!(
+            parameter().parse(stream(":limit")).unwrap(),
+            Parameter::Named("limit".to_string())
+        );
     }
 }
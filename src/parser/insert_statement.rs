@@ -0,0 +1,92 @@
+use chumsky::prelude::*;
+
+use crate::ast::{expression::Expression, insert_statement::InsertStatement};
+use crate::lexer::Token;
+
+use super::{
+    common::{column, table},
+    keyword, punct, SyntaxError,
+};
+
+pub fn insert_statement<E>(
+    expr: E,
+) -> impl Parser<Token, InsertStatement, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    let fields = column()
+        .separated_by(punct(','))
+        .at_least(1)
+        .delimited_by(punct('('), punct(')'))
+        .or_not();
+
+    let row = expr
+        .separated_by(punct(','))
+        .at_least(1)
+        .delimited_by(punct('('), punct(')'))
+        .recover_with(nested_delimiters(
+            Token::Punct('('),
+            Token::Punct(')'),
+            [],
+            |span| vec![Expression::Error(span)],
+        ));
+
+    keyword("insert")
+        .ignore_then(keyword("into"))
+        .ignore_then(table())
+        .then(fields)
+        .then_ignore(keyword("values"))
+        .then(row.separated_by(punct(',')).at_least(1))
+        .map_with_span(|((table, fields), data), span| InsertStatement {
+            table,
+            fields,
+            data,
+            span,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::{
+        column::Column,
+        expression::{Literal, Parameter},
+        span::Span,
+        table::Table,
+        Identifier, ObjectName,
+    };
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, stream};
+
+    #[test]
+    fn test_insert_statement() {
+        let actual = insert_statement(expression(&GenericDialect))
+            .parse(stream(
+                "INSERT INTO db.users (id, name) VALUES (1, 'ann'), (2, ?)",
+            ))
+            .unwrap();
+        let column = |name| Column {
+            column: Identifier::new(name),
+            table: None,
+        };
+        let expected = InsertStatement {
+            table: Table::new(
+                ObjectName::new(vec![Identifier::new("db"), Identifier::new("users")]).unwrap(),
+            ),
+            fields: Some(vec![column("id"), column("name")]),
+            data: vec![
+                vec![
+                    Expression::Literal(Literal::UnsignedInteger(1), Span::default()),
+                    Expression::Literal(Literal::String("ann".to_string()), Span::default()),
+                ],
+                vec![
+                    Expression::Literal(Literal::UnsignedInteger(2), Span::default()),
+                    Expression::Parameter(Parameter::Anonymous, Span::default()),
+                ],
+            ],
+            span: Span::default(),
+        };
+        assert_eq!(actual.without_spans(), expected)
+    }
+}
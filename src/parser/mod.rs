@@ -1,74 +1,330 @@
-use crate::ast::Identifier;
-use chumsky::prelude::*;
+//! The chumsky combinators behind the top level `parse_*` functions.
+//!
+//! These are exposed for embedding the grammar into other parsers, most users want
+//! [`crate::parse_statement`] and friends instead.
 
+// chumsky hands errors around by value, boxing them in `try_map` closures would gain nothing
+#![allow(clippy::result_large_err)]
+use crate::ast::span::{Location, Span};
+use crate::ast::{Identifier, Statement};
+use crate::dialect::Dialect;
+use crate::lexer::{tokenize, Token};
+use chumsky::{prelude::*, Stream};
+
+pub mod alter_statement;
 pub mod common;
+pub mod create_statement;
+pub mod delete_statement;
+pub mod drop_statement;
+mod error;
 pub mod expression;
+pub mod insert_statement;
+pub mod recovery;
 pub mod select_statement;
+pub mod update_statement;
 
-pub fn identifier() -> impl Parser<char, Identifier, Error = Simple<char>> + Clone {
-    let ident = text::ident().padded();
+pub use error::{expecting, Input, SyntaxError, SyntaxErrorReason};
 
-    let quoted_ident = just("`")
-        .ignore_then(ident)
-        .then_ignore(just("`"))
-        .map(Identifier);
+/// Tokenizes `source` for the parsers, leaving out whitespace and comments.
+pub fn stream(source: &str) -> Stream<'static, Token, Span, std::vec::IntoIter<(Token, Span)>> {
+    token_stream(source, tokenize(source))
+}
 
-    ident.map(Identifier).or(quoted_ident)
+/// Feeds the already tokenized `source` to the parsers, leaving out whitespace and comments.
+pub fn token_stream(
+    source: &str,
+    tokens: Vec<(Token, Span)>,
+) -> Stream<'static, Token, Span, std::vec::IntoIter<(Token, Span)>> {
+    let eoi = Location::of(source, source.len());
+    let tokens = tokens
+        .into_iter()
+        .filter(|(token, _)| !token.is_trivia())
+        .collect::<Vec<_>>();
+    Stream::from_iter(Span::new(eoi, eoi), tokens.into_iter())
 }
 
-/// Contains a list of reserved words to be matched against. These words cannot be used as an identifier.
-pub fn is_reserved_word(name: &str) -> bool {
-    match name {
-        "create" | "select" | "drop" | "rename" | "case" | "else" | "if" | "instanceof"
-        | "where" | "truncate" | "merge" | "new" | "escape" | "raw" | "fetch" | "insert"
-        | "while" | "const" | "alter" | "exists" | "with" | "for" | "switch" | "yield"
-        | "throw" | "delete" | "index" | "true" | "false" | "let" => true,
-        _ => false,
-    }
+/// Parses any statement `dialect` supports, without a trailing `;`.
+pub fn statement(
+    dialect: &'static dyn Dialect,
+) -> impl Parser<Token, Statement, Error = SyntaxError> + Clone {
+    let expr = expression::expression(dialect);
+
+    let stmt = choice((
+        select_statement::select_statement(expr.clone(), dialect)
+            .map(|stmt| Statement::Select(Box::new(stmt))),
+        insert_statement::insert_statement(expr.clone()).map(Statement::Insert),
+        update_statement::update_statement(expr.clone()).map(Statement::Update),
+        delete_statement::delete_statement(expr.clone()).map(Statement::Delete),
+        drop_statement::drop_statement().map(Statement::Drop),
+        create_statement::create_statement(expr.clone()).map(Statement::Create),
+        alter_statement::alter_statement(expr).map(Statement::Alter),
+    ))
+    .validate(move |stmt, span, emit| {
+        if !dialect.supports_statement(&stmt) {
+            emit(SyntaxError::custom(
+                span,
+                format!("this statement isn't supported by {}", dialect.name()),
+            ))
+        }
+        stmt
+    });
+
+    expecting(stmt, "statement")
 }
 
-/// Comments are meant to be ignored
-pub fn comments() -> impl Parser<char, (), Error = Simple<char>> {
-    let multi_line = just("/*").padded().then(take_until(just("*/"))).ignored();
-    let single_line = just("--")
-        .padded()
-        .then(take_until(text::newline()))
-        .ignored();
-    single_line.or(multi_line)
+/// Parses a bare or quoted identifier.
+///
+/// Bare identifiers must not be a reserved word, quoting lifts that restriction e.g. `` `order` ``.
+/// Identifiers may be quoted with backticks, double quotes or brackets, and a closing quote is
+/// escaped by doubling it e.g. `` `a``b` `` or `[a]]b]`.
+pub fn identifier() -> impl Parser<Token, Identifier, Error = SyntaxError> + Clone {
+    let ident = filter_map(|span, token| match token {
+        Token::Ident(name) => Ok(Identifier::new(name).with_span(span)),
+        Token::QuotedIdent(quote, value) => Ok(Identifier::quoted(quote, value).with_span(span)),
+        Token::Keyword(name) => Err(SyntaxError::custom(
+            span,
+            format!(
+                "`{}` is a reserved keyword and cannot be used as an identifier unless quoted",
+                name
+            ),
+        )),
+        token => Err(SyntaxError::unexpected(span, token)),
+    });
+
+    expecting(ident, "identifier")
+}
+
+/// Matches `kw` in any letter case, e.g. `keyword("order")` accepts `ORDER` and `Order`.
+///
+/// Non-reserved keywords such as `ROLLUP` are matched too, they are lexed as identifiers.
+pub fn keyword(kw: &'static str) -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    let kw_parser = filter_map(move |span, token| match &token {
+        Token::Keyword(word) | Token::Ident(word) if word.eq_ignore_ascii_case(kw) => Ok(()),
+        _ => Err(SyntaxError::unexpected(span, token)),
+    });
+
+    expecting(kw_parser, kw.to_ascii_uppercase())
+}
+
+/// Matches one of the punctuation tokens `(`, `)`, `,`, `;` or `.`.
+pub fn punct(c: char) -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    just(Token::Punct(c)).ignored()
+}
+
+/// Matches an operator token e.g. `op("<=")`.
+pub fn op(op: &'static str) -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    just(Token::Op(op)).ignored()
+}
+
+/// Matches an operator token like [`op`], reporting it when `dialect` doesn't support it.
+pub fn dialect_op(
+    dialect: &'static dyn Dialect,
+    op: &'static str,
+) -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    just(Token::Op(op))
+        .ignored()
+        .validate(move |(), span, emit| {
+            if !dialect.supports_operator(op) {
+                emit(SyntaxError::custom(
+                    span,
+                    format!("`{}` isn't supported by {}", op, dialect.name()),
+                ))
+            }
+        })
+}
+
+/// Reports what `parser` matched as `feature` e.g. `` `LIMIT` `` unless `dialect` `supports` it.
+pub fn dialect_feature<P, O>(
+    parser: P,
+    dialect: &'static dyn Dialect,
+    supports: fn(&dyn Dialect) -> bool,
+    feature: &'static str,
+) -> impl Parser<Token, O, Error = SyntaxError> + Clone
+where
+    P: Parser<Token, O, Error = SyntaxError> + Clone,
+{
+    parser.validate(move |output, span, emit| {
+        if !supports(dialect) {
+            emit(SyntaxError::custom(
+                span,
+                format!("{} isn't supported by {}", feature, dialect.name()),
+            ))
+        }
+        output
+    })
+}
+
+/// Contains a list of reserved words to be matched against, in any letter case. These words cannot be used as an identifier.
+pub fn is_reserved_word(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "all"
+            | "alter"
+            | "and"
+            | "as"
+            | "asc"
+            | "between"
+            | "by"
+            | "case"
+            | "create"
+            | "cross"
+            | "delete"
+            | "desc"
+            | "distinct"
+            | "drop"
+            | "else"
+            | "end"
+            | "escape"
+            | "except"
+            | "exists"
+            | "false"
+            | "fetch"
+            | "for"
+            | "from"
+            | "full"
+            | "group"
+            | "having"
+            | "if"
+            | "in"
+            | "index"
+            | "inner"
+            | "insert"
+            | "intersect"
+            | "into"
+            | "is"
+            | "join"
+            | "left"
+            | "like"
+            | "limit"
+            | "merge"
+            | "natural"
+            | "not"
+            | "null"
+            | "offset"
+            | "on"
+            | "or"
+            | "order"
+            | "outer"
+            | "rename"
+            | "right"
+            | "select"
+            | "set"
+            | "table"
+            | "then"
+            | "true"
+            | "truncate"
+            | "union"
+            | "update"
+            | "using"
+            | "values"
+            | "when"
+            | "where"
+            | "with"
+    )
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ast::span::IgnoreSpans;
 
     #[test]
     fn test_identifier() {
-        let expected = Identifier("col".to_string());
+        let expected = Identifier::new("col");
 
-        let actual = identifier().parse("col").unwrap();
-        assert_eq!(actual, expected);
+        let actual = identifier().parse(stream("col")).unwrap();
+        assert_eq!(actual.without_spans(), expected);
 
-        let expected2 = Identifier("col_1".to_string());
-        let actual2 = identifier().parse("col_1").unwrap();
-        assert_eq!(actual2, expected2);
+        let expected2 = Identifier::new("col_1");
+        let actual2 = identifier().parse(stream("col_1")).unwrap();
+        assert_eq!(actual2.without_spans(), expected2);
     }
 
     #[test]
     fn test_escaped_identifier() {
-        let expected = Identifier("col".to_string());
-        let actual = identifier().parse("`col`").unwrap();
-        assert_eq!(actual, expected)
+        let expected = Identifier::quoted('`', "col");
+        let actual = identifier().parse(stream("`col`")).unwrap();
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_quoted_identifier_styles() {
+        let actual = identifier().parse(stream("`order date`")).unwrap();
+        assert_eq!(
+            actual.without_spans(),
+            Identifier::quoted('`', "order date")
+        );
+
+        let actual = identifier().parse(stream(r#""User""#)).unwrap();
+        assert_eq!(actual.without_spans(), Identifier::quoted('"', "User"));
+
+        let actual = identifier().parse(stream("[Column Name]")).unwrap();
+        assert_eq!(
+            actual.without_spans(),
+            Identifier::quoted('[', "Column Name")
+        );
+    }
+
+    #[test]
+    fn test_quoted_identifier_escapes() {
+        let actual = identifier().parse(stream("`a``b`")).unwrap();
+        assert_eq!(actual.without_spans(), Identifier::quoted('`', "a`b"));
+        assert_eq!(actual.to_string(), "`a``b`");
+
+        let actual = identifier().parse(stream(r#""say ""hi""""#)).unwrap();
+        assert_eq!(
+            actual.without_spans(),
+            Identifier::quoted('"', r#"say "hi""#)
+        );
+
+        let actual = identifier().parse(stream("[a]]b]")).unwrap();
+        assert_eq!(actual.without_spans(), Identifier::quoted('[', "a]b"));
+        assert_eq!(actual.to_string(), "[a]]b]");
+    }
+
+    #[test]
+    fn test_unicode_identifier() {
+        let actual = identifier().parse(stream("straße_1")).unwrap();
+        assert_eq!(actual.without_spans(), Identifier::new("straße_1"));
+    }
+
+    #[test]
+    fn test_identifier_case_sensitivity() {
+        assert!(Identifier::new("Region").matches(&Identifier::new("REGION")));
+        assert!(!Identifier::quoted('"', "Region").matches(&Identifier::new("region")));
+        assert!(Identifier::quoted('"', "region").matches(&Identifier::new("REGION")));
+    }
+
+    #[test]
+    fn test_reserved_identifier() {
+        let errors = identifier().parse(stream("where")).unwrap_err();
+        assert_eq!(
+            errors[0].reason(),
+            &SyntaxErrorReason::Custom(
+                "`where` is a reserved keyword and cannot be used as an identifier unless quoted"
+                    .to_string()
+            )
+        );
+        assert!(identifier().parse(stream("FROM")).is_err());
+
+        let actual = identifier().parse(stream("`order`")).unwrap();
+        assert_eq!(actual.without_spans(), Identifier::quoted('`', "order"))
     }
 
     #[test]
-    fn test_single_line_comment() {
-        let actual = comments().parse("-- this is a comment\n").unwrap();
-        assert_eq!(actual, ())
+    fn test_keyword_any_case() {
+        for kw in ["select", "SELECT", "Select", "sElEcT"] {
+            assert_eq!(keyword("select").parse(stream(kw)), Ok(()));
+        }
+        assert!(keyword("select").parse(stream("selected")).is_err());
+        assert_eq!(keyword("rollup").parse(stream("ROLLUP")), Ok(()));
     }
 
     #[test]
-    fn test_multi_line_comment() {
-        let actual = comments().parse("/*this is a comment*/").unwrap();
-        assert_eq!(actual, ())
+    fn test_reserved_word_any_case() {
+        assert!(is_reserved_word("from"));
+        assert!(is_reserved_word("From"));
+        assert!(is_reserved_word("WHERE"));
+        assert!(!is_reserved_word("region"));
     }
 }
@@ -0,0 +1,160 @@
+//! Error recovery, it lets parsing carry on past a mistake so every problem in a script is reported
+//! at once rather than only the first.
+//!
+//! The recovered part of the input becomes an [`Expression::Error`] or [`Statement::Error`] node.
+use chumsky::prelude::*;
+
+use crate::ast::{expression::Expression, Statement};
+use crate::lexer::Token;
+
+use super::{expecting, punct, SyntaxError};
+
+/// Whether `token` starts a clause, or a part of one, that recovery should resume at.
+fn is_clause_keyword(token: &Token) -> bool {
+    let (Token::Keyword(word) | Token::Ident(word)) = token else {
+        return false;
+    };
+    matches!(
+        word.to_ascii_lowercase().as_str(),
+        "select"
+            | "from"
+            | "where"
+            | "group"
+            | "having"
+            | "order"
+            | "limit"
+            | "offset"
+            | "fetch"
+            | "union"
+            | "join"
+            | "inner"
+            | "left"
+            | "right"
+            | "full"
+            | "cross"
+            | "on"
+            | "using"
+            | "set"
+            | "values"
+    )
+}
+
+/// Balanced parentheses, skipped as a whole so the `;`, `,` or keywords inside them aren't taken
+/// for a boundary.
+fn group() -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    recursive(|group| {
+        punct('(')
+            .then(
+                group
+                    .or(none_of([Token::Punct('('), Token::Punct(')')]).ignored())
+                    .repeated(),
+            )
+            .then(punct(')'))
+            .ignored()
+    })
+}
+
+/// Succeeds without consuming anything where a clause may end: before a clause keyword, `,`,
+/// `;`, an unbalanced `)` or the end of input.
+pub fn clause_end() -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    filter(|token| {
+        is_clause_keyword(token)
+            || matches!(
+                token,
+                Token::Punct(',') | Token::Punct(';') | Token::Punct(')')
+            )
+    })
+    .ignored()
+    .or(end())
+    .rewind()
+}
+
+/// Skips to the end of the clause, see [`clause_end`].
+pub fn skip_clause() -> impl Parser<Token, (), Error = SyntaxError> + Clone {
+    // an unclosed `(` is skipped too, it is most likely the mistake being recovered from
+    let other = filter(|token| {
+        !(is_clause_keyword(token)
+            || matches!(
+                token,
+                Token::Punct(',') | Token::Punct(';') | Token::Punct(')')
+            ))
+    });
+
+    group().or(other.ignored()).repeated().at_least(1).ignored()
+}
+
+/// Parses an expression that ends its clause, or on error skips the rest of the clause and
+/// yields an [`Expression::Error`] e.g. for a projection item or a `WHERE` condition.
+pub fn clause_expression<E>(expr: E) -> impl Parser<Token, Expression, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    // there is nothing to skip when the expression is missing, report that rather than the
+    // tokens the recovery would have skipped
+    let skip = expecting(skip_clause(), "expression");
+
+    expr.then_ignore(clause_end()).recover_with(skip_parser(
+        skip.map_with_span(|_, span| Expression::Error(span)),
+    ))
+}
+
+/// Parses a parenthesized list of expressions, on error the whole list becomes a single
+/// [`Expression::Error`] e.g. function arguments or a `VALUES` row.
+pub fn expression_list<E>(
+    expr: E,
+) -> impl Parser<Token, Vec<Expression>, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    expr.separated_by(punct(','))
+        .delimited_by(punct('('), punct(')'))
+        .recover_with(nested_delimiters(
+            Token::Punct('('),
+            Token::Punct(')'),
+            [],
+            |span| vec![Expression::Error(span)],
+        ))
+}
+
+/// Parses `stmt` up to its terminating `;` or the end of input, on error everything up to the
+/// next `;` becomes a [`Statement::Error`].
+pub fn recover_statement<S>(stmt: S) -> impl Parser<Token, Statement, Error = SyntaxError> + Clone
+where
+    S: Parser<Token, Statement, Error = SyntaxError> + Clone,
+{
+    let statement_end = punct(';').or(end()).rewind();
+    let skip_statement = group()
+        .or(none_of([Token::Punct(';')]).ignored())
+        .repeated()
+        .at_least(1);
+
+    let statement = stmt.then_ignore(statement_end).recover_with(skip_parser(
+        skip_statement.map_with_span(|_, span| Statement::Error(span)),
+    ));
+
+    // only try, and so recover, when there is a statement left e.g. not after a trailing `;`
+    none_of([Token::Punct(';')]).rewind().ignore_then(statement)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, keyword, stream};
+
+    #[test]
+    fn test_clause_expression_recovery() {
+        let parser = clause_expression(expression(&GenericDialect)).then_ignore(keyword("from"));
+        let (expr, errors) = parser.parse_recovery(stream("a = = (1, ')') FROM"));
+        assert!(matches!(expr, Some(Expression::Error(span)) if span.range() == (0..14)));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_expression_list_recovery() {
+        let (list, errors) =
+            expression_list(expression(&GenericDialect)).parse_recovery(stream("(1, , (2))"));
+        assert!(matches!(list.as_deref(), Some([Expression::Error(_)])));
+        assert_eq!(errors.len(), 1);
+    }
+}
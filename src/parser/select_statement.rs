@@ -1,77 +1,225 @@
 use chumsky::prelude::*;
 
-use crate::ast::{expression::Expression, join::*, select_statement::*};
-
-use super::{common::table, identifier};
-
+use crate::ast::{
+    expression::{Expression, Literal},
+    join::*,
+    select_statement::*,
+    span::Spanned,
+    Identifier,
+};
+use crate::dialect::Dialect;
+use crate::lexer::Token;
+
+use super::{
+    common::table, dialect_feature, identifier, keyword, punct, recovery::clause_expression,
+    SyntaxError,
+};
+
+/// Parses a query, the row limiting clauses are those `dialect` supports.
 pub fn select_statement<E>(
     expr: E,
-) -> impl Parser<char, SelectStatement, Error = Simple<char>> + Clone
+    dialect: &'static dyn Dialect,
+) -> impl Parser<Token, SelectStatement, Error = SyntaxError> + Clone
 where
-    E: Parser<char, Expression, Error = Simple<char>> + Clone + 'static,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone + 'static,
 {
-    let op = |c| text::keyword(c).padded();
-
-    let num = text::digits(10).map(|s: String| s.parse::<i64>().unwrap());
-
-    let order_expr = op("order").or(op("ORDER")).then(op("by").or(op("BY")));
-    let limit_expr = op("limit").or(op("LIMIT")).ignore_then(num).or_not();
-    let offset_expr = op("offset").or(op("OFFSET")).ignore_then(num).or_not();
+    let order_by_clause = keyword("order")
+        .ignore_then(keyword("by"))
+        .ignore_then(order_by(expr.clone()).separated_by(punct(',')).at_least(1))
+        .or_not()
+        .map(|order_by| order_by.unwrap_or_default());
 
     recursive(|stmt| {
+        let with_clause = keyword("with")
+            .ignore_then(
+                common_table_expression(stmt.clone())
+                    .separated_by(punct(','))
+                    .at_least(1),
+            )
+            .or_not()
+            .map(Option::unwrap_or_default);
         let sl_clause = select_clause(stmt, expr.clone());
-        let order_by_expr = order_by(expr.clone()).separated_by(just(","));
-        sl_clause
-            .then(order_expr.ignore_then(order_by_expr))
-            .then(limit_expr)
-            .then(offset_expr)
-            .map(|(((body, order_by), limit), offset)| SelectStatement {
-                body,
-                order_by,
-                limit,
-                offset,
-            })
+        let union = keyword("union")
+            .ignore_then(keyword("all").or_not().map(|all| all.is_some()))
+            .then(sl_clause.clone())
+            .map_with_span(|(all, clause), span| Union { all, clause, span });
+        with_clause
+            .then(sl_clause)
+            .then(union.repeated())
+            .then(order_by_clause)
+            .then(limit_clause(expr.clone(), dialect))
+            .map_with_span(
+                |((((with, body), unions), order_by), (limit, offset, fetch)), span| {
+                    SelectStatement {
+                        with,
+                        body,
+                        unions,
+                        order_by,
+                        limit,
+                        offset,
+                        fetch,
+                        span,
+                    }
+                },
+            )
     })
     .boxed()
 }
 
-pub fn select_clause<S, E>(
+/// Parses a query of `WITH` e.g. `recent (id) AS (SELECT ...)`.
+pub fn common_table_expression<S>(
     stmt: S,
+) -> impl Parser<Token, CommonTableExpression, Error = SyntaxError> + Clone
+where
+    S: Parser<Token, SelectStatement, Error = SyntaxError> + Clone + 'static,
+{
+    let columns = identifier()
+        .separated_by(punct(','))
+        .at_least(1)
+        .delimited_by(punct('('), punct(')'))
+        .or_not()
+        .map(Option::unwrap_or_default);
+
+    identifier()
+        .then(columns)
+        .then_ignore(keyword("as"))
+        .then(stmt.delimited_by(punct('('), punct(')')))
+        .map_with_span(|((name, columns), query), span| CommonTableExpression {
+            name,
+            columns,
+            query: Box::new(query),
+            span,
+        })
+}
+
+/// Parses the row limiting clauses following `ORDER BY` into `(limit, offset, fetch)`.
+///
+/// Accepts `LIMIT n [OFFSET m]`, MySQL's `LIMIT m, n` and the ANSI
+/// `[OFFSET m {ROW | ROWS}] [FETCH {FIRST | NEXT} [n] {ROW | ROWS} {ONLY | WITH TIES}]`,
+/// reporting the forms `dialect` doesn't support.
+#[allow(clippy::type_complexity)]
+pub fn limit_clause<E>(
     expr: E,
-) -> impl Parser<char, SelectClause, Error = Simple<char>>
+    dialect: &'static dyn Dialect,
+) -> impl Parser<Token, (Option<Expression>, Option<Expression>, Option<Fetch>), Error = SyntaxError>
+       + Clone
 where
-    S: Parser<char, SelectStatement, Error = Simple<char>> + Clone + 'static,
-    E: Parser<char, Expression, Error = Simple<char>> + Clone + 'static,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone + 'static,
 {
-    let op = |c| text::keyword(c).padded();
+    let rows = keyword("rows").or(keyword("row"));
 
-    let select_keyword = op("select").or(op("SELECT"));
+    let offset = keyword("offset")
+        .ignore_then(expr.clone())
+        .then_ignore(rows.clone().or_not());
 
-    let distinct_or_not = op("distinct")
-        .or(op("DISTINCT"))
+    let limit = dialect_feature(keyword("limit"), dialect, |d| d.supports_limit(), "`LIMIT`")
+        .ignore_then(expr.clone())
+        .then(
+            dialect_feature(
+                punct(','),
+                dialect,
+                |d| d.supports_limit_comma(),
+                "`LIMIT offset, count`",
+            )
+            .ignore_then(expr.clone())
+            .map(Err)
+            .or(offset.clone().map(Ok))
+            .or_not(),
+        )
+        .map(|(first, rest)| match rest {
+            // `LIMIT offset, count`
+            Some(Err(count)) => (Some(count), Some(first), None),
+            Some(Ok(offset)) => (Some(first), Some(offset), None),
+            None => (Some(first), None, None),
+        });
+
+    let fetch = dialect_feature(keyword("fetch"), dialect, |d| d.supports_fetch(), "`FETCH`")
+        .ignore_then(keyword("first").or(keyword("next")))
+        .ignore_then(
+            // without a count `ROW` would otherwise be taken for a column name
+            rows.clone().to(None).or(expr.then_ignore(rows).map(Some)),
+        )
+        .then(
+            keyword("only")
+                .to(false)
+                .or(keyword("with").ignore_then(keyword("ties")).to(true)),
+        )
+        .map(|(quantity, with_ties)| Fetch {
+            quantity,
+            with_ties,
+        });
+
+    // neither alternative matches nothing, chumsky would otherwise prefer an empty match over a
+    // `LIMIT` reported as unsupported by the dialect
+    let offset_fetch = offset
+        .map(Some)
+        .then(fetch.clone().or_not())
+        .validate(move |(offset, fetch), span, emit| {
+            if fetch.is_none() && !dialect.supports_offset_without_limit() {
+                emit(SyntaxError::custom(
+                    span,
+                    format!(
+                        "`OFFSET` without `LIMIT` isn't supported by {}",
+                        dialect.name()
+                    ),
+                ))
+            }
+            (offset, fetch)
+        })
+        .or(fetch.map(|fetch| (None, Some(fetch))))
+        .map(|(offset, fetch)| (None, offset, fetch));
+
+    limit
+        .or(offset_fetch)
         .or_not()
-        .map(|dist| dist.is_some());
+        .map(Option::unwrap_or_default)
+}
+
+/// Parses an item of the select list, an expression with an optional `AS` alias.
+pub fn select_item<E>(expr: E) -> impl Parser<Token, Expression, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    expr.then(keyword("as").ignore_then(identifier()).or_not())
+        .map_with_span(|(expr, alias), span| match alias {
+            Some(alias) => Expression::Alias {
+                expr: Box::new(expr),
+                alias,
+                span,
+            },
+            None => expr,
+        })
+}
 
-    let projection_clause = expr.clone().separated_by(just(","));
+pub fn select_clause<S, E>(
+    stmt: S,
+    expr: E,
+) -> impl Parser<Token, SelectClause, Error = SyntaxError> + Clone
+where
+    S: Parser<Token, SelectStatement, Error = SyntaxError> + Clone + 'static,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone + 'static,
+{
+    let select_keyword = keyword("select");
+
+    let distinct_or_not = keyword("distinct").or_not().map(|dist| dist.is_some());
+
+    let projection_clause = clause_expression(select_item(expr.clone())).separated_by(punct(','));
 
     let table_refrence = table_ref(stmt, expr.clone());
 
-    let from_clause = op("from").or(op("FROM")).ignore_then(table_refrence);
+    let from_clause = keyword("from").ignore_then(table_refrence).or_not();
 
-    let where_expr = op("where")
-        .or(op("WHERE"))
-        .ignore_then(expr.clone())
+    let where_expr = keyword("where")
+        .ignore_then(clause_expression(expr.clone()))
         .or_not();
 
-    let group_by = op("group")
-        .or(op("GROUP"))
-        .ignore_then(op("by").or(op("BY")))
-        .ignore_then(expr.clone().separated_by(just(",")))
+    let group_by = keyword("group")
+        .ignore_then(keyword("by"))
+        .ignore_then(group_by(expr.clone()))
         .or_not();
 
-    let having_expr = op("having")
-        .or(op("HAVING"))
-        .ignore_then(expr.clone())
+    let having_expr = keyword("having")
+        .ignore_then(clause_expression(expr.clone()))
         .or_not();
 
     select_keyword
@@ -81,83 +229,152 @@ where
         .then(where_expr)
         .then(group_by)
         .then(having_expr)
-        .map(
-            |(((((dist, projection), frm), where_exp), group_by), having)| {
-                let group_by_expression = match group_by {
-                    Some(grp) => grp,
-                    None => vec![],
-                };
-                SelectClause {
-                    distinct: dist,
-                    projection,
-                    from: frm,
-                    where_exp,
-                    group_by: group_by_expression,
-                    having,
-                }
+        .map_with_span(
+            |(((((dist, projection), frm), where_exp), group_by), having), span| SelectClause {
+                distinct: dist,
+                projection,
+                from: frm,
+                where_exp,
+                group_by,
+                having,
+                span,
             },
         )
 }
 
+/// Parses the body of a `GROUP BY` clause, i.e. everything after the `BY` keyword.
+pub fn group_by<E>(expr: E) -> impl Parser<Token, GroupBy, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone + 'static,
+{
+    // `(a, b)` or `()` is a composite column, a bare expression is a set of one
+    let grouping_set = expr
+        .clone()
+        .separated_by(punct(','))
+        .delimited_by(punct('('), punct(')'))
+        .or(expr.clone().map(|e| vec![e]));
+
+    let grouping_sets_list = grouping_set
+        .clone()
+        .separated_by(punct(','))
+        .delimited_by(punct('('), punct(')'));
+
+    let rollup = keyword("rollup")
+        .ignore_then(grouping_sets_list.clone())
+        .map(GroupingElement::Rollup);
+
+    let cube = keyword("cube")
+        .ignore_then(grouping_sets_list.clone())
+        .map(GroupingElement::Cube);
+
+    let grouping_sets = keyword("grouping")
+        .ignore_then(keyword("sets"))
+        .ignore_then(grouping_sets_list)
+        .map(GroupingElement::GroupingSets);
+
+    let plain = expr.map(|e| match e {
+        Expression::Literal(Literal::UnsignedInteger(n), span) if n > 0 => {
+            GroupingElement::Ordinal(n as u64, span)
+        }
+        e => GroupingElement::Expr(e),
+    });
+
+    let all = keyword("all").to(GroupBy::All);
+
+    let elements = choice((rollup, cube, grouping_sets, plain))
+        .separated_by(punct(','))
+        .at_least(1)
+        .map(GroupBy::Elements);
+
+    all.or(elements)
+}
+
+/// Parses the alias of a relation, which `AS` requires. A bare word starting a clause that may
+/// follow `FROM`, such as `OFFSET` where it isn't reserved, isn't taken for one.
+fn table_alias() -> impl Parser<Token, Option<Identifier>, Error = SyntaxError> + Clone {
+    let bare = identifier().try_map(|name: Identifier, span| {
+        let starts_clause = name.quote_style.is_none()
+            && matches!(
+                name.value.to_ascii_lowercase().as_str(),
+                "limit" | "offset" | "fetch"
+            );
+        match starts_clause {
+            true => Err(SyntaxError::custom(
+                span,
+                format!("`{}` starts a clause, quote it to use it as an alias", name),
+            )),
+            false => Ok(name),
+        }
+    });
+    keyword("as").ignore_then(identifier()).or(bare).or_not()
+}
+
 pub fn table_ref<S, E>(
     stmt: S,
     expr: E,
-) -> impl Parser<char, Option<TableReference>, Error = Simple<char>> + Clone
+) -> impl Parser<Token, TableReference, Error = SyntaxError> + Clone
 where
-    S: Parser<char, SelectStatement, Error = Simple<char>> + Clone + 'static,
-    E: Parser<char, Expression, Error = Simple<char>> + 'static,
+    S: Parser<Token, SelectStatement, Error = SyntaxError> + Clone + 'static,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone + 'static,
 {
-    let table_reference = recursive(|table_ref| {
-        let base_rel = table().map(TableReference::BaseRelation);
+    recursive(|table_ref| {
+        let base_rel = table()
+            .then(table_alias())
+            .map_with_span(|(table, alias), span| TableReference::BaseRelation {
+                table,
+                alias,
+                span,
+            });
 
         let subquery = stmt
-            .delimited_by(just("("), just(")"))
-            .then_ignore(text::keyword("as").or(text::keyword("AS")).or_not())
-            .padded()
-            .then(identifier().or_not())
-            .map(|(subquery, alias)| TableReference::SubQuery {
+            .delimited_by(punct('('), punct(')'))
+            .then(table_alias())
+            .map_with_span(|(subquery, alias), span| TableReference::SubQuery {
                 subquery: Box::new(subquery),
                 alias,
+                span,
             });
 
-        let join_ref = base_rel
+        // e.g. `(t1 JOIN t2 ON ...)`
+        let nested = table_ref.delimited_by(punct('('), punct(')'));
+
+        let relation = subquery.or(nested).or(base_rel);
+
+        // joins are left associative, `t1 JOIN t2 JOIN t3` joins `t3` to the result of `t1 JOIN t2`
+        relation
             .clone()
-            .or(subquery.clone())
-            .or(table_ref.clone())
-            .then(join_type())
-            .then(base_rel.clone().or(subquery.clone()).or(table_ref.clone()))
-            .then(join_condition(expr))
-            .map(|(((left, op), right), cond)| TableReference::Join {
+            .then(
+                join_type()
+                    .then(relation)
+                    .then(join_condition(expr))
+                    .map_with_span(|join, span| (join, span))
+                    .repeated(),
+            )
+            .foldl(|left, (((op, right), cond), span)| TableReference::Join {
+                span: left.span().union(span),
                 left: Box::new(left),
                 op,
                 right: Box::new(right),
                 cond,
-            });
-
-        subquery.clone().or(join_ref).or(base_rel)
+            })
     })
-    .boxed();
-
-    table_reference.or_not()
+    .boxed()
 }
 
-pub fn join_type() -> impl Parser<char, JoinType, Error = Simple<char>> {
-    let op = |c| text::keyword(c).padded();
+pub fn join_type() -> impl Parser<Token, JoinType, Error = SyntaxError> {
+    let join_keyword = keyword("join");
 
-    let join_keyword = op("join").or(op("JOIN"));
+    let inner_join = keyword("inner").map(|_| JoinType::Inner);
 
-    let inner_join = op("inner").or(op("INNER")).map(|_| JoinType::Inner);
+    let left_join = keyword("left").map(|_| JoinType::LeftJoin);
 
-    let left_join = op("left").or(op("LEFT")).map(|_| JoinType::LeftJoin);
+    let right_join = keyword("right").map(|_| JoinType::RightJoin);
 
-    let right_join = op("right").or(op("RIGHT")).map(|_| JoinType::RightJoin);
-
-    let full_join = op("full")
-        .or(op("FULL"))
-        .then(op("outer").or(op("OUTER")).or_not())
+    let full_join = keyword("full")
+        .then(keyword("outer").or_not())
         .map(|_| JoinType::FullOuterJoin);
 
-    let cross_join = op("cross").or(op("CROSS")).map(|_| JoinType::CrossJoin);
+    let cross_join = keyword("cross").map(|_| JoinType::CrossJoin);
 
     inner_join
         .or(left_join)
@@ -168,40 +385,400 @@ pub fn join_type() -> impl Parser<char, JoinType, Error = Simple<char>> {
         .or(join_keyword.clone().map(|_| JoinType::Inner))
 }
 
-pub fn join_condition<E>(expr: E) -> impl Parser<char, JoinCondition, Error = Simple<char>>
+pub fn join_condition<E>(expr: E) -> impl Parser<Token, JoinCondition, Error = SyntaxError>
 where
-    E: Parser<char, Expression, Error = Simple<char>>,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
 {
-    let op = |c| text::keyword(c).padded();
-
-    let using_cond = op("using")
-        .or(op("USING"))
-        .ignore_then(identifier().separated_by(just(",")))
+    let columns = identifier().separated_by(punct(',')).at_least(1);
+
+    let using_cond = keyword("using")
+        .ignore_then(
+            columns
+                .clone()
+                .delimited_by(punct('('), punct(')'))
+                .or(columns),
+        )
         .map(JoinCondition::Using);
 
-    let on_cond = op("on")
-        .or(op("ON"))
-        .ignore_then(expr.separated_by(just(",")))
+    let on_cond = keyword("on")
+        .ignore_then(clause_expression(expr).separated_by(punct(',')))
         .map(JoinCondition::On);
 
     using_cond.or(on_cond)
 }
 
-pub fn order_by<E>(expr: E) -> impl Parser<char, OrderBy, Error = Simple<char>>
+pub fn order_by<E>(expr: E) -> impl Parser<Token, OrderBy, Error = SyntaxError> + Clone
 where
-    E: Parser<char, Expression, Error = Simple<char>>,
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
 {
-    expr.separated_by(just(","))
-        .then(order_dir().or_not())
-        .padded()
-        .map(|(expr, direction)| OrderBy { expr, direction })
+    expr.then(order_dir().or_not())
+        .then(nulls_order().or_not())
+        .map(|((expr, direction), nulls)| OrderBy {
+            expr,
+            direction,
+            nulls,
+        })
 }
 
-pub fn order_dir() -> impl Parser<char, Order, Error = Simple<char>> {
-    let op = |c| text::keyword(c).padded();
-
-    let asc_order = op("asc").or(op("ASC")).ignored().map(|_| Order::Asc);
-    let desc_order = op("desc").or(op("DESC")).ignored().map(|_| Order::Desc);
+pub fn order_dir() -> impl Parser<Token, Order, Error = SyntaxError> + Clone {
+    let asc_order = keyword("asc").to(Order::Asc);
+    let desc_order = keyword("desc").to(Order::Desc);
 
     asc_order.or(desc_order)
 }
+
+pub fn nulls_order() -> impl Parser<Token, NullsOrder, Error = SyntaxError> + Clone {
+    let first = keyword("first").to(NullsOrder::First);
+    let last = keyword("last").to(NullsOrder::Last);
+
+    keyword("nulls").ignore_then(first.or(last))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::span::IgnoreSpans;
+    use crate::ast::{
+        column::Column,
+        expression::{ColumnReference, Parameter},
+        span::Span,
+        Identifier,
+    };
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, stream};
+
+    fn col(name: &str) -> Expression {
+        Expression::ColumnReference(
+            ColumnReference::Column(Column {
+                column: Identifier::new(name),
+                table: None,
+            }),
+            Span::default(),
+        )
+    }
+
+    #[test]
+    fn test_group_by_expressions() {
+        let actual = group_by(expression(&GenericDialect))
+            .parse(stream("region, 2"))
+            .unwrap();
+        let expected = GroupBy::Elements(vec![
+            GroupingElement::Expr(col("region")),
+            GroupingElement::Ordinal(2, Span::default()),
+        ]);
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_group_by_all() {
+        let actual = group_by(expression(&GenericDialect))
+            .parse(stream("ALL"))
+            .unwrap();
+        assert_eq!(actual, GroupBy::All)
+    }
+
+    #[test]
+    fn test_group_by_rollup_and_cube() {
+        let actual = group_by(expression(&GenericDialect))
+            .parse(stream("ROLLUP(region, (city, street)), cube(a)"))
+            .unwrap();
+        let expected = GroupBy::Elements(vec![
+            GroupingElement::Rollup(vec![vec![col("region")], vec![col("city"), col("street")]]),
+            GroupingElement::Cube(vec![vec![col("a")]]),
+        ]);
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_group_by_grouping_sets() {
+        let actual = group_by(expression(&GenericDialect))
+            .parse(stream("GROUPING SETS ((a), (a, b), ())"))
+            .unwrap();
+        let expected = GroupBy::Elements(vec![GroupingElement::GroupingSets(vec![
+            vec![col("a")],
+            vec![col("a"), col("b")],
+            vec![],
+        ])]);
+        assert_eq!(actual.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_select_with_group_by() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT region, count(city) FROM sales GROUP BY ROLLUP(region) ORDER BY region",
+            ))
+            .unwrap();
+        let expected = Some(GroupBy::Elements(vec![GroupingElement::Rollup(vec![
+            vec![col("region")],
+        ])]));
+        assert_eq!(actual.body.group_by.without_spans(), expected)
+    }
+
+    #[test]
+    fn test_mixed_case_keywords() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "Select Distinct a From t Where a = 1 Group By a Order By a Desc Limit 1",
+            ))
+            .unwrap();
+        assert!(actual.body.distinct);
+        assert_eq!(actual.order_by[0].direction, Some(Order::Desc));
+        assert_eq!(
+            actual.limit.without_spans(),
+            Some(Expression::Literal(
+                Literal::UnsignedInteger(1),
+                Span::default()
+            ))
+        )
+    }
+
+    #[test]
+    fn test_keyword_ends_subquery() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream("SELECT a FROM (SELECT a FROM t) WHERE a = 1"))
+            .unwrap();
+        assert!(matches!(
+            actual.body.from,
+            Some(TableReference::SubQuery { alias: None, .. })
+        ));
+        assert!(actual.body.where_exp.is_some())
+    }
+
+    #[test]
+    fn test_table_aliases() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT u.a FROM users AS u JOIN db.teams t ON u.b = t.b WHERE u.a = 1",
+            ))
+            .unwrap();
+        let Some(TableReference::Join { left, right, .. }) = actual.body.from else {
+            panic!("expected a join")
+        };
+        let alias = |relation: &TableReference| match relation {
+            TableReference::BaseRelation { alias, .. } => alias.clone().without_spans(),
+            other => panic!("expected a table, found {:?}", other),
+        };
+        assert_eq!(alias(&left), Some(Identifier::new("u")));
+        assert_eq!(alias(&right), Some(Identifier::new("t")));
+        assert!(actual.body.where_exp.is_some());
+
+        for sql in [
+            "SELECT a FROM t AS",
+            "SELECT a FROM (SELECT a FROM t) AS WHERE a = 1",
+        ] {
+            let parser = select_statement(expression(&GenericDialect), &GenericDialect);
+            assert!(
+                parser.then_ignore(end()).parse(stream(sql)).is_err(),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_aliases_only_in_select_list() {
+        let parser = select_statement(expression(&GenericDialect), &GenericDialect);
+        let actual = parser.parse(stream("SELECT a AS b FROM t")).unwrap();
+        assert!(matches!(
+            actual.body.projection[0],
+            Expression::Alias { .. }
+        ));
+        for sql in [
+            "SELECT a FROM t WHERE a AS b",
+            "SELECT a FROM t LIMIT 1 AS x",
+        ] {
+            let parser = select_statement(expression(&GenericDialect), &GenericDialect);
+            assert!(
+                parser.then_ignore(end()).parse(stream(sql)).is_err(),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_with() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "WITH a AS (SELECT x FROM t), b (y, z) AS (SELECT x, 1 FROM a) SELECT y FROM b",
+            ))
+            .unwrap();
+        let names = actual
+            .with
+            .iter()
+            .map(|cte| cte.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b"]);
+        assert!(actual.with[0].columns.is_empty());
+        assert_eq!(
+            actual.with[1].columns.clone().without_spans(),
+            [Identifier::new("y"), Identifier::new("z")]
+        );
+        assert_eq!(actual.with[1].query.to_string(), "SELECT x, 1 FROM a");
+
+        // in a subquery too
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT s.x FROM (WITH a AS (SELECT 1 AS x) SELECT x FROM a) s",
+            ))
+            .unwrap();
+        let Some(TableReference::SubQuery { subquery, .. }) = actual.body.from else {
+            panic!("expected a subquery")
+        };
+        assert_eq!(subquery.with.len(), 1);
+    }
+
+    #[test]
+    fn test_union() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT a FROM t UNION SELECT b FROM u union all SELECT c FROM v ORDER BY 1 LIMIT 5",
+            ))
+            .unwrap();
+        assert_eq!(actual.body.to_string(), "SELECT a FROM t");
+        let unions = actual
+            .unions
+            .iter()
+            .map(|union| (union.all, union.clause.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unions,
+            [
+                (false, "SELECT b FROM u".to_string()),
+                (true, "SELECT c FROM v".to_string())
+            ]
+        );
+        assert_eq!(actual.order_by.len(), 1);
+        assert!(actual.limit.is_some());
+    }
+
+    #[test]
+    fn test_from_is_optional() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream("SELECT 1"))
+            .unwrap();
+        assert_eq!(actual.body.from, None)
+    }
+
+    #[test]
+    fn test_join_chain() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT a FROM t1 JOIN t2 ON a = b LEFT JOIN t3 USING (c)",
+            ))
+            .unwrap();
+        match actual.body.from {
+            Some(TableReference::Join { left, op, .. }) => {
+                assert_eq!(op, JoinType::LeftJoin);
+                assert!(matches!(*left, TableReference::Join { .. }))
+            }
+            other => panic!("expected a join, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_order_by_is_optional() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream("SELECT a FROM t"))
+            .unwrap();
+        assert_eq!(actual.order_by, vec![]);
+        assert_eq!(actual.limit, None)
+    }
+
+    #[test]
+    fn test_order_by_per_item_direction() {
+        let actual = select_statement(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(
+                "SELECT a FROM t ORDER BY a DESC NULLS LAST, b ASC, c",
+            ))
+            .unwrap();
+        let expected = vec![
+            OrderBy {
+                expr: col("a"),
+                direction: Some(Order::Desc),
+                nulls: Some(NullsOrder::Last),
+            },
+            OrderBy {
+                expr: col("b"),
+                direction: Some(Order::Asc),
+                nulls: None,
+            },
+            OrderBy {
+                expr: col("c"),
+                direction: None,
+                nulls: None,
+            },
+        ];
+        assert_eq!(actual.order_by.without_spans(), expected)
+    }
+
+    /// The parts of the limit clause in `sql`, without their spans
+    fn limit(sql: &str) -> (Option<Expression>, Option<Expression>, Option<Fetch>) {
+        let (limit, offset, fetch) = limit_clause(expression(&GenericDialect), &GenericDialect)
+            .parse(stream(sql))
+            .unwrap();
+        (
+            limit.without_spans(),
+            offset.without_spans(),
+            fetch.without_spans(),
+        )
+    }
+
+    #[test]
+    fn test_limit_offset() {
+        let num = |n| {
+            Some(Expression::Literal(
+                Literal::UnsignedInteger(n),
+                Span::default(),
+            ))
+        };
+
+        let actual = limit("LIMIT 10 OFFSET 5");
+        assert_eq!(actual, (num(10), num(5), None));
+
+        let actual = limit("LIMIT 5, 10");
+        assert_eq!(actual, (num(10), num(5), None));
+
+        let actual = limit("limit ?");
+        assert_eq!(
+            actual,
+            (
+                Some(Expression::Parameter(Parameter::Anonymous, Span::default())),
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_offset_fetch() {
+        let actual = limit("OFFSET 5 ROWS FETCH FIRST 10 ROWS WITH TIES");
+        let expected = (
+            None,
+            Some(Expression::Literal(
+                Literal::UnsignedInteger(5),
+                Span::default(),
+            )),
+            Some(Fetch {
+                quantity: Some(Expression::Literal(
+                    Literal::UnsignedInteger(10),
+                    Span::default(),
+                )),
+                with_ties: true,
+            }),
+        );
+        assert_eq!(actual, expected);
+
+        let actual = limit("FETCH NEXT ROW ONLY");
+        let expected = (
+            None,
+            None,
+            Some(Fetch {
+                quantity: None,
+                with_ties: false,
+            }),
+        );
+        assert_eq!(actual, expected)
+    }
+}
@@ -0,0 +1,77 @@
+use chumsky::prelude::*;
+
+use crate::ast::{
+    expression::Expression,
+    update_statement::{Assignment, UpdateStatement},
+};
+use crate::lexer::Token;
+
+use super::{
+    common::{column, table},
+    keyword, op, punct,
+    recovery::clause_expression,
+    SyntaxError,
+};
+
+pub fn update_statement<E>(
+    expr: E,
+) -> impl Parser<Token, UpdateStatement, Error = SyntaxError> + Clone
+where
+    E: Parser<Token, Expression, Error = SyntaxError> + Clone,
+{
+    let assignment = column()
+        .then_ignore(op("="))
+        .then(clause_expression(expr.clone()))
+        .map(|(column, value)| Assignment { column, value });
+
+    keyword("update")
+        .ignore_then(table())
+        .then_ignore(keyword("set"))
+        .then(assignment.separated_by(punct(',')).at_least(1))
+        .then(
+            keyword("where")
+                .ignore_then(clause_expression(expr))
+                .or_not(),
+        )
+        .map_with_span(|((table, fields), where_clause), span| UpdateStatement {
+            table,
+            fields,
+            where_clause,
+            span,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::{
+        expression::Literal,
+        span::{IgnoreSpans, Span},
+        Identifier,
+    };
+    use crate::dialect::GenericDialect;
+    use crate::parser::{expression::expression, stream};
+
+    #[test]
+    fn test_update_statement() {
+        let actual = update_statement(expression(&GenericDialect))
+            .parse(stream(
+                "UPDATE users SET name = 'bob', age = 30 WHERE id = 1",
+            ))
+            .unwrap();
+        assert_eq!(
+            actual.table.name.name().without_spans(),
+            Identifier::new("users")
+        );
+        assert_eq!(actual.fields.len(), 2);
+        assert_eq!(
+            actual.fields[1].column.column.without_spans(),
+            Identifier::new("age")
+        );
+        assert_eq!(
+            actual.fields[1].value.without_spans(),
+            Expression::Literal(Literal::UnsignedInteger(30), Span::default())
+        );
+        assert!(actual.where_clause.is_some())
+    }
+}
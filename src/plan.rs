@@ -0,0 +1,579 @@
+//! Logical query plans, the relational algebra a bound query stands for, see [`crate::binder`].
+//!
+//! A select clause becomes, from the leaves up, scans and joins of its `FROM`, a filter for
+//! `WHERE`, an aggregate for `GROUP BY` and the aggregates it computes, a filter for `HAVING`, a
+//! sort for `ORDER BY` below the projection of the select list with `*` expanded, then
+//! `DISTINCT` and the limit.
+//! A plan prints as an indented tree, like `EXPLAIN`.
+//!
+//! ```
+//! use sql_parser::ast::Statement;
+//! use sql_parser::binder::bind;
+//! use sql_parser::catalog::Catalog;
+//! use sql_parser::plan::plan;
+//! use sql_parser::{parse_statement, ParseOptions};
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.load_script("CREATE TABLE t (a INT, b INT)", &ParseOptions::default());
+//!
+//! let Statement::Select(query) = parse_statement("SELECT a FROM t WHERE b > 1").unwrap()
+//! else { unreachable!() };
+//! let plan = plan(&query, &bind(&query, &catalog));
+//! assert_eq!(plan.to_string(), "Project: a\n  Filter: b > 1\n    Scan: t\n");
+//! ```
+use std::fmt::{Display, Formatter};
+
+use crate::ast::column::Column;
+use crate::ast::expression::{ColumnReference, Expression, Literal};
+use crate::ast::join::{JoinCondition, JoinType};
+use crate::ast::select_statement::{
+    GroupBy, GroupingElement, OrderBy, SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::{IgnoreSpans, Spanned};
+use crate::ast::{Identifier, ObjectName, Separated};
+use crate::binder::{Binding, Bound};
+use crate::grouping::{collect_aggregates, has_aggregate, unalias};
+use crate::visitor::walk_expressions_mut;
+
+/// A node of a logical plan, with the plans it reads from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Plan {
+    /// Reads every row of a table
+    Scan { table: ObjectName },
+    /// A single row without columns, read by a query without `FROM`
+    Empty,
+    /// Keeps the rows for which `predicate` holds
+    Filter {
+        predicate: Expression,
+        input: Box<Plan>,
+    },
+    /// Computes the result columns
+    Project {
+        exprs: Vec<Expression>,
+        input: Box<Plan>,
+    },
+    Join {
+        op: JoinType,
+        cond: JoinCondition,
+        left: Box<Plan>,
+        right: Box<Plan>,
+    },
+    /// Groups the rows by `group_by`, ordinals and `GROUP BY ALL` being resolved, and computes
+    /// `aggregates` for each group, or for all rows as one group when `group_by` is empty
+    Aggregate {
+        group_by: Vec<GroupingElement>,
+        aggregates: Vec<Expression>,
+        input: Box<Plan>,
+    },
+    Sort {
+        order_by: Vec<OrderBy>,
+        input: Box<Plan>,
+    },
+    /// `LIMIT` and `OFFSET`, or `FETCH FIRST`
+    Limit {
+        limit: Option<Expression>,
+        offset: Option<Expression>,
+        /// Rows that tie with the last one are kept too
+        with_ties: bool,
+        input: Box<Plan>,
+    },
+    /// Removes duplicate rows
+    Distinct { input: Box<Plan> },
+    /// The rows of all inputs, without duplicates unless `all`
+    Union { all: bool, inputs: Vec<Plan> },
+    /// Names the result of a subquery
+    SubqueryAlias { alias: Identifier, input: Box<Plan> },
+}
+
+impl Plan {
+    /// The plans this one reads from.
+    pub fn inputs(&self) -> Vec<&Plan> {
+        match self {
+            Plan::Scan { .. } | Plan::Empty => vec![],
+            Plan::Filter { input, .. }
+            | Plan::Project { input, .. }
+            | Plan::Aggregate { input, .. }
+            | Plan::Sort { input, .. }
+            | Plan::Limit { input, .. }
+            | Plan::Distinct { input }
+            | Plan::SubqueryAlias { input, .. } => vec![input],
+            Plan::Join { left, right, .. } => vec![left, right],
+            Plan::Union { inputs, .. } => inputs.iter().collect(),
+        }
+    }
+
+    fn fmt_node(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Plan::Scan { table } => write!(f, "Scan: {}", table),
+            Plan::Empty => write!(f, "Empty"),
+            Plan::Filter { predicate, .. } => write!(f, "Filter: {}", predicate),
+            Plan::Project { exprs, .. } => write!(f, "Project: {}", Separated(exprs, ", ")),
+            Plan::Join { op, cond, .. } => match cond {
+                JoinCondition::On(conditions) if conditions.is_empty() => {
+                    write!(f, "Join: {}", op)
+                }
+                cond => write!(f, "Join: {} {}", op, cond),
+            },
+            Plan::Aggregate {
+                group_by,
+                aggregates,
+                ..
+            } => write!(
+                f,
+                "Aggregate: group by [{}], aggregates [{}]",
+                Separated(group_by, ", "),
+                Separated(aggregates, ", ")
+            ),
+            Plan::Sort { order_by, .. } => write!(f, "Sort: {}", Separated(order_by, ", ")),
+            Plan::Limit {
+                limit,
+                offset,
+                with_ties,
+                ..
+            } => {
+                write!(f, "Limit:")?;
+                if let Some(limit) = limit {
+                    write!(f, " {}", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET {}", offset)?;
+                }
+                if *with_ties {
+                    write!(f, " WITH TIES")?;
+                }
+                Ok(())
+            }
+            Plan::Distinct { .. } => write!(f, "Distinct"),
+            Plan::Union { all: true, .. } => write!(f, "Union: ALL"),
+            Plan::Union { all: false, .. } => write!(f, "Union"),
+            Plan::SubqueryAlias { alias, .. } => write!(f, "SubqueryAlias: {}", alias),
+        }
+    }
+
+    fn fmt_tree(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        self.fmt_node(f)?;
+        writeln!(f)?;
+        for input in self.inputs() {
+            input.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+/// Builds the plan of a select clause sorted by `order_by`, which may aggregate too.
+///
+/// The sort runs below the projection so that it can use columns the select list leaves out, with
+/// the aliases and ordinals of the select list replaced by the items they name. With `DISTINCT` it
+/// runs last instead when the select list has every key, as rows that are made distinct keep
+/// only the selected columns.
+fn select_clause(body: &SelectClause, order_by: &[OrderBy], bound: &Bound<'_>) -> Plan {
+    let mut plan = match &body.from {
+        Some(from) => table_ref(from, bound),
+        None => Plan::Empty,
+    };
+    if let Some(predicate) = &body.where_exp {
+        plan = Plan::Filter {
+            predicate: predicate.clone(),
+            input: Box::new(plan),
+        };
+    }
+
+    let mut aggregates = vec![];
+    let items = body
+        .projection
+        .iter()
+        .chain(&body.having)
+        .chain(order_by.iter().map(|order_by| &order_by.expr));
+    for item in items {
+        collect_aggregates(item, &mut aggregates);
+    }
+    let projection = body
+        .projection
+        .iter()
+        .flat_map(|item| expand(item, bound))
+        .collect::<Vec<_>>();
+    let group_by = match &body.group_by {
+        Some(GroupBy::All) => projection
+            .iter()
+            .filter(|item| !has_aggregate(item))
+            .map(|item| GroupingElement::Expr(unalias(item).clone()))
+            .collect(),
+        Some(GroupBy::Elements(elements)) => elements
+            .iter()
+            .map(|element| match element {
+                GroupingElement::Ordinal(ordinal, _) => (*ordinal as usize)
+                    .checked_sub(1)
+                    .and_then(|index| body.projection.get(index))
+                    .map_or(element.clone(), |item| {
+                        GroupingElement::Expr(unalias(item).clone())
+                    }),
+                element => element.clone(),
+            })
+            .collect(),
+        None => vec![],
+    };
+    if body.group_by.is_some() || body.having.is_some() || !aggregates.is_empty() {
+        plan = Plan::Aggregate {
+            group_by,
+            aggregates,
+            input: Box::new(plan),
+        };
+    }
+    if let Some(predicate) = &body.having {
+        plan = Plan::Filter {
+            predicate: predicate.clone(),
+            input: Box::new(plan),
+        };
+    }
+
+    let sort_last = body.distinct
+        && order_by
+            .iter()
+            .all(|order_by| is_selected(&order_by.expr, &projection));
+    if !order_by.is_empty() && !sort_last {
+        plan = Plan::Sort {
+            order_by: order_by
+                .iter()
+                .map(|order_by| OrderBy {
+                    expr: sort_key(&order_by.expr, &projection),
+                    ..order_by.clone()
+                })
+                .collect(),
+            input: Box::new(plan),
+        };
+    }
+    plan = Plan::Project {
+        exprs: projection,
+        input: Box::new(plan),
+    };
+    if body.distinct {
+        plan = Plan::Distinct {
+            input: Box::new(plan),
+        };
+    }
+    if !order_by.is_empty() && sort_last {
+        plan = Plan::Sort {
+            order_by: order_by.to_vec(),
+            input: Box::new(plan),
+        };
+    }
+    plan
+}
+
+/// Builds the logical plan of `query`, `bound` being its bindings.
+pub fn plan(query: &SelectStatement, bound: &Bound<'_>) -> Plan {
+    if query.unions.is_empty() {
+        let plan = select_clause(&query.body, &query.order_by, bound);
+        return limit(query, plan);
+    }
+    let mut plan = select_clause(&query.body, &[], bound);
+    for union in &query.unions {
+        let input = select_clause(&union.clause, &[], bound);
+        // `a UNION b UNION c` is one union, `a UNION b UNION ALL c` keeps the duplicates of `c`
+        plan = match plan {
+            Plan::Union { all, mut inputs } if all == union.all => {
+                inputs.push(input);
+                Plan::Union { all, inputs }
+            }
+            plan => Plan::Union {
+                all: union.all,
+                inputs: vec![plan, input],
+            },
+        };
+    }
+    // the keys of a union are its result columns
+    if !query.order_by.is_empty() {
+        plan = Plan::Sort {
+            order_by: query.order_by.clone(),
+            input: Box::new(plan),
+        };
+    }
+    limit(query, plan)
+}
+
+/// Adds the `LIMIT`, `OFFSET` or `FETCH` of `query` over `plan`.
+fn limit(query: &SelectStatement, mut plan: Plan) -> Plan {
+    let (limit, with_ties) = match &query.fetch {
+        Some(fetch) => (fetch.quantity.clone(), fetch.with_ties),
+        None => (query.limit.clone(), false),
+    };
+    if limit.is_some() || query.offset.is_some() {
+        plan = Plan::Limit {
+            limit,
+            offset: query.offset.clone(),
+            with_ties,
+            input: Box::new(plan),
+        };
+    }
+    plan
+}
+
+fn table_ref(table_ref: &TableReference, bound: &Bound<'_>) -> Plan {
+    match table_ref {
+        TableReference::BaseRelation { table, alias, .. } => {
+            let (input, name) = match bound.common_table(table_ref) {
+                Some(cte) => (plan(&cte.query, bound), Some(cte.name.clone())),
+                None => (
+                    Plan::Scan {
+                        table: table.name.clone(),
+                    },
+                    None,
+                ),
+            };
+            match alias.clone().or(name) {
+                Some(alias) => Plan::SubqueryAlias {
+                    alias,
+                    input: Box::new(input),
+                },
+                None => input,
+            }
+        }
+        TableReference::SubQuery {
+            subquery, alias, ..
+        } => {
+            let input = plan(subquery, bound);
+            match alias {
+                Some(alias) => Plan::SubqueryAlias {
+                    alias: alias.clone(),
+                    input: Box::new(input),
+                },
+                None => input,
+            }
+        }
+        TableReference::Join {
+            left,
+            op,
+            right,
+            cond,
+            ..
+        } => Plan::Join {
+            op: op.clone(),
+            cond: cond.clone(),
+            left: Box::new(self::table_ref(left, bound)),
+            right: Box::new(self::table_ref(right, bound)),
+        },
+    }
+}
+
+/// The columns a select list item stands for, `*` and `tbl.*` being expanded. Columns of tables
+/// are qualified, those of subqueries and queries of `WITH` are named by their output name.
+fn expand(item: &Expression, bound: &Bound<'_>) -> Vec<Expression> {
+    let Some(columns) = bound.wildcard(item) else {
+        return vec![item.clone()];
+    };
+    columns
+        .iter()
+        .map(|output| match (&output.binding, &output.name) {
+            (Binding::Column { column, .. }, _) if output.relation.is_some() => {
+                Expression::ColumnReference(
+                    ColumnReference::Column(Column {
+                        column: column.name().clone(),
+                        table: output.relation.clone(),
+                    }),
+                    item.span(),
+                )
+            }
+            (_, Some(name)) => Expression::ColumnReference(
+                ColumnReference::Column(Column {
+                    column: name.clone(),
+                    table: None,
+                }),
+                item.span(),
+            ),
+            (Binding::Column { column, .. }, None) => Expression::ColumnReference(
+                ColumnReference::Column(Column {
+                    column: column.name().clone(),
+                    table: None,
+                }),
+                item.span(),
+            ),
+            (Binding::Expression { expr, .. }, None) => (*expr).clone(),
+        })
+        .collect()
+}
+
+/// The item of the select list an `ORDER BY` key names, by ordinal or by alias.
+fn selected<'a>(key: &Expression, projection: &'a [Expression]) -> Option<&'a Expression> {
+    match key {
+        Expression::Literal(Literal::UnsignedInteger(n), _) => {
+            (*n as usize).checked_sub(1).and_then(|index| projection.get(index))
+        }
+        Expression::ColumnReference(ColumnReference::Column(column), _) if column.table.is_none() => {
+            projection.iter().find(|item| {
+                matches!(item, Expression::Alias { alias, .. } if alias.matches(&column.column))
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether the rows of the select list are enough to sort by `key`.
+fn is_selected(key: &Expression, projection: &[Expression]) -> bool {
+    selected(key, projection).is_some()
+        || projection
+            .iter()
+            .any(|item| unalias(item).eq_ignore_spans(key))
+}
+
+/// An `ORDER BY` key as evaluated below the projection, the aliases it uses replaced by what they
+/// stand for.
+fn sort_key(key: &Expression, projection: &[Expression]) -> Expression {
+    if let Some(item) = selected(key, projection) {
+        return unalias(item).clone();
+    }
+    let mut key = key.clone();
+    walk_expressions_mut(&mut key, |expr| {
+        if let Some(item) = selected(expr, projection) {
+            if !matches!(expr, Expression::Literal(..)) {
+                *expr = unalias(item).clone();
+            }
+        }
+    });
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binder::bind;
+    use crate::testing::{catalog, query};
+
+    fn explain(sql: &str) -> String {
+        let catalog = catalog(
+            "CREATE TABLE t (id INT PRIMARY KEY, a INT, b INT);
+             CREATE TABLE u (t_id INT, c INT)",
+        );
+        let query = query(sql);
+        let bound = bind(&query, &catalog);
+        assert!(!bound.has_errors(), "{:?}", bound.diagnostics);
+        plan(&query, &bound).to_string()
+    }
+
+    #[test]
+    fn test_plan_query() {
+        assert_eq!(
+            explain(
+                "SELECT DISTINCT t.a, SUM(c) AS total FROM t LEFT JOIN u ON u.t_id = t.id \
+                 WHERE b > 1 GROUP BY 1 HAVING COUNT(*) > 2 ORDER BY total DESC LIMIT 10 OFFSET 5"
+            ),
+            "\
+Limit: 10 OFFSET 5
+  Sort: total DESC
+    Distinct
+      Project: t.a, SUM(c) AS total
+        Filter: COUNT(*) > 2
+          Aggregate: group by [t.a], aggregates [SUM(c), COUNT(*)]
+            Filter: b > 1
+              Join: LEFT JOIN ON u.t_id = t.id
+                Scan: t
+                Scan: u
+"
+        );
+    }
+
+    #[test]
+    fn test_plan_sort() {
+        assert_eq!(
+            explain("SELECT a FROM t ORDER BY b"),
+            "Project: a\n  Sort: b\n    Scan: t\n"
+        );
+        assert_eq!(
+            explain("SELECT a + 1 AS x, b FROM t ORDER BY x * 2, 2 DESC"),
+            "Project: a + 1 AS x, b\n  Sort: (a + 1) * 2, b DESC\n    Scan: t\n"
+        );
+        assert_eq!(
+            explain("SELECT DISTINCT a FROM t ORDER BY a"),
+            "Sort: a\n  Distinct\n    Project: a\n      Scan: t\n"
+        );
+        assert_eq!(
+            explain("SELECT DISTINCT a FROM t ORDER BY b"),
+            "Distinct\n  Project: a\n    Sort: b\n      Scan: t\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_subquery_and_wildcard() {
+        assert_eq!(
+            explain(
+                "SELECT * FROM (SELECT a, b + 1 AS d FROM t) AS s JOIN u ON u.t_id = s.a \
+                 FETCH FIRST 3 ROWS WITH TIES"
+            ),
+            "\
+Limit: 3 WITH TIES
+  Project: a, d, u.t_id, u.c
+    Join: JOIN ON u.t_id = s.a
+      SubqueryAlias: s
+        Project: a, b + 1 AS d
+          Scan: t
+      Scan: u
+"
+        );
+    }
+
+    #[test]
+    fn test_plan_aliases_and_common_tables() {
+        assert_eq!(
+            explain(
+                "WITH big AS (SELECT id FROM t WHERE a > 1) \
+                 SELECT * FROM big JOIN u AS v ON v.t_id = big.id JOIN t ON t.id = v.c"
+            ),
+            "\
+Project: id, v.t_id, v.c, t.id, t.a, t.b
+  Join: JOIN ON t.id = v.c
+    Join: JOIN ON v.t_id = big.id
+      SubqueryAlias: big
+        Project: id
+          Filter: a > 1
+            Scan: t
+      SubqueryAlias: v
+        Scan: u
+    Scan: t
+"
+        );
+    }
+
+    #[test]
+    fn test_plan_unions() {
+        assert_eq!(
+            explain(
+                "SELECT id FROM t UNION SELECT t_id FROM u UNION SELECT 1 \
+                 UNION ALL SELECT DISTINCT c FROM u ORDER BY id LIMIT 5"
+            ),
+            "\
+Limit: 5
+  Sort: id
+    Union: ALL
+      Union
+        Project: id
+          Scan: t
+        Project: t_id
+          Scan: u
+        Project: 1
+          Empty
+      Distinct
+        Project: c
+          Scan: u
+"
+        );
+    }
+
+    #[test]
+    fn test_plan_without_from() {
+        assert_eq!(explain("SELECT 1 + 1"), "Project: 1 + 1\n  Empty\n");
+        assert_eq!(
+            explain("SELECT a, b + 1 AS d, MAX(id) FROM t GROUP BY ALL"),
+            "Project: a, b + 1 AS d, MAX(id)\n  Aggregate: group by [a, b + 1], aggregates [MAX(id)]\n    Scan: t\n"
+        );
+        assert_eq!(
+            explain("SELECT COUNT(*) FROM t"),
+            "Project: COUNT(*)\n  Aggregate: group by [], aggregates [COUNT(*)]\n    Scan: t\n"
+        );
+    }
+}
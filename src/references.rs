@@ -0,0 +1,638 @@
+//! The tables and columns a statement refers to, e.g. for access reviews.
+//!
+//! Columns are resolved to the relation of their query they belong to where the statement alone
+//! tells: a qualified column by its qualifier, an unqualified one when a single relation in scope
+//! can have it. Without a schema a column of a join of two tables can't be told apart.
+//! An alias of the select list used in `GROUP BY`, `HAVING` or `ORDER BY` stands for the columns
+//! of its expression.
+//!
+//! ```
+//! use sql_parser::parse_statement;
+//! use sql_parser::references::{references, Access};
+//!
+//! let stmt = parse_statement("UPDATE accounts SET balance = 0 WHERE id = 1").unwrap();
+//! let refs = references(&stmt);
+//! assert_eq!(refs.tables[0].table.to_string(), "accounts");
+//! assert_eq!(refs.tables[0].access, Access::Write);
+//! assert_eq!(refs.columns.len(), 2);
+//! ```
+use crate::ast::column::Column;
+use crate::ast::expression::{ColumnReference, Expression};
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{
+    GroupBy, GroupingElement, SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::{Span, Spanned};
+use crate::ast::table::Table;
+use crate::ast::{Identifier, ObjectName, Statement};
+use crate::visitor::{walk_expressions, walk_expressions_mut};
+
+/// How a statement uses a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// Queried
+    Read,
+    /// The target of an `INSERT`, `UPDATE`, `DELETE`, `DROP`, `CREATE` or `ALTER`
+    Write,
+}
+
+/// A table named by a statement.
+///
+/// Subqueries in `FROM` and the queries of `WITH` it refers to by name aren't tables, see
+/// [`References::subqueries`] and [`References::common_tables`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableUse {
+    pub table: Table,
+    pub access: Access,
+}
+
+/// A relation a column belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// A table, named as in `FROM` or the target of the statement
+    Table(ObjectName),
+    /// A subquery in `FROM`
+    SubQuery {
+        alias: Option<Identifier>,
+        span: Span,
+    },
+    /// A query of `WITH`, named as in `FROM`
+    CommonTable(Identifier),
+}
+
+/// A column named by a statement, or a wildcard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnUse {
+    /// The column as written, `None` for `*` and `t.*`
+    pub name: Option<Identifier>,
+    /// The qualifier as written e.g. `t` in `t.a`
+    pub qualifier: Option<ObjectName>,
+    /// The relation the column belongs to, `None` when the statement alone doesn't tell
+    pub source: Option<Source>,
+    pub span: Span,
+}
+
+/// The result of [`references`], everything in source order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct References {
+    pub tables: Vec<TableUse>,
+    /// A `*` is listed once for every relation it stands for
+    pub columns: Vec<ColumnUse>,
+    /// The subqueries in `FROM`, by alias
+    pub subqueries: Vec<Source>,
+    /// The names of the queries of `WITH`
+    pub common_tables: Vec<Identifier>,
+}
+
+impl References {
+    /// The tables read, each one once.
+    pub fn reads(&self) -> Vec<&Table> {
+        self.distinct(Access::Read)
+    }
+
+    /// The tables written, each one once.
+    pub fn writes(&self) -> Vec<&Table> {
+        self.distinct(Access::Write)
+    }
+
+    fn distinct(&self, access: Access) -> Vec<&Table> {
+        let mut tables: Vec<&Table> = Vec::new();
+        for table_use in self.tables.iter().filter(|table| table.access == access) {
+            if !tables
+                .iter()
+                .any(|table| table.name.matches(&table_use.table.name))
+            {
+                tables.push(&table_use.table);
+            }
+        }
+        tables
+    }
+
+    fn table(&mut self, table: &Table, access: Access) {
+        self.tables.push(TableUse {
+            table: table.clone(),
+            access,
+        })
+    }
+
+    /// Adds the references of `query`, `common_tables` being the queries of `WITH` it sees.
+    fn query(&mut self, query: &SelectStatement, common_tables: &[CommonTable]) {
+        let mut common_tables = common_tables.to_vec();
+        for cte in &query.with {
+            self.query(&cte.query, &common_tables);
+            self.common_tables.push(cte.name.clone());
+            let columns = match cte.columns.is_empty() {
+                true => output_columns(&cte.query),
+                false => Some(cte.columns.clone()),
+            };
+            common_tables.push(CommonTable {
+                name: cte.name.clone(),
+                columns,
+            });
+        }
+
+        let scope = self.select_clause(&query.body, &common_tables);
+        for union in &query.unions {
+            self.select_clause(&union.clause, &common_tables);
+        }
+        // the names of `ORDER BY` are looked up as in the first select clause
+        for order_by in &query.order_by {
+            self.expr(&unalias(&order_by.expr, &query.body.projection), &scope);
+        }
+    }
+
+    /// Adds the references of a select clause, returning the relations of its `FROM` clause.
+    fn select_clause(
+        &mut self,
+        body: &SelectClause,
+        common_tables: &[CommonTable],
+    ) -> Vec<Relation> {
+        let mut scope = Vec::new();
+        if let Some(from) = &body.from {
+            self.table_ref(from, common_tables, &mut scope);
+        }
+
+        let group_by = match &body.group_by {
+            Some(GroupBy::Elements(elements)) => {
+                elements.iter().flat_map(GroupingElement::exprs).collect()
+            }
+            _ => vec![],
+        };
+        for expr in body.projection.iter().chain(&body.where_exp) {
+            self.expr(expr, &scope);
+        }
+        for expr in group_by.into_iter().chain(&body.having) {
+            self.expr(&unalias(expr, &body.projection), &scope);
+        }
+        scope
+    }
+
+    /// Adds the relations of `table_ref` to `scope`, along with the columns of its join conditions.
+    fn table_ref(
+        &mut self,
+        table_ref: &TableReference,
+        common_tables: &[CommonTable],
+        scope: &mut Vec<Relation>,
+    ) {
+        match table_ref {
+            TableReference::BaseRelation { table, alias, .. } => {
+                let common_table = match table.name.qualifier() {
+                    [] => common_tables
+                        .iter()
+                        .rev()
+                        .find(|common| common.name.matches(table.name.name())),
+                    _ => None,
+                };
+                if let Some(common) = common_table {
+                    scope.push(Relation {
+                        source: Source::CommonTable(table.name.name().clone()),
+                        alias: alias.clone(),
+                        columns: common.columns.clone(),
+                    });
+                    return;
+                }
+                self.table(table, Access::Read);
+                scope.push(Relation {
+                    source: Source::Table(table.name.clone()),
+                    alias: alias.clone(),
+                    columns: None,
+                });
+            }
+            TableReference::SubQuery {
+                subquery,
+                alias,
+                span,
+            } => {
+                self.query(subquery, common_tables);
+                let source = Source::SubQuery {
+                    alias: alias.clone(),
+                    span: *span,
+                };
+                self.subqueries.push(source.clone());
+                scope.push(Relation {
+                    source,
+                    alias: alias.clone(),
+                    columns: output_columns(subquery),
+                });
+            }
+            TableReference::Join {
+                left, right, cond, ..
+            } => {
+                let mut left_scope = Vec::new();
+                self.table_ref(left, common_tables, &mut left_scope);
+                let mut right_scope = Vec::new();
+                self.table_ref(right, common_tables, &mut right_scope);
+                match cond {
+                    // the column of both sides
+                    JoinCondition::Using(names) => {
+                        for name in names {
+                            let column = Column {
+                                column: name.clone(),
+                                table: None,
+                            };
+                            self.column(&column, name.span, &left_scope);
+                            self.column(&column, name.span, &right_scope);
+                        }
+                        scope.append(&mut left_scope);
+                        scope.append(&mut right_scope);
+                    }
+                    JoinCondition::On(conditions) => {
+                        scope.append(&mut left_scope);
+                        scope.append(&mut right_scope);
+                        for condition in conditions {
+                            self.expr(condition, scope);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression, scope: &[Relation]) {
+        walk_expressions(expr, |expr| match expr {
+            Expression::ColumnReference(ColumnReference::Column(column), span) => {
+                self.column(column, *span, scope)
+            }
+            Expression::ColumnReference(ColumnReference::QualifiedWildcard(qualifier), span) => {
+                self.columns.push(ColumnUse {
+                    name: None,
+                    qualifier: Some(qualifier.clone()),
+                    source: resolve_qualifier(qualifier, scope),
+                    span: *span,
+                })
+            }
+            Expression::ColumnReference(ColumnReference::Wildcard, span) => {
+                let sources = scope.iter().map(|relation| Some(relation.source.clone()));
+                let sources = if scope.is_empty() {
+                    vec![None]
+                } else {
+                    sources.collect()
+                };
+                for source in sources {
+                    self.columns.push(ColumnUse {
+                        name: None,
+                        qualifier: None,
+                        source,
+                        span: *span,
+                    })
+                }
+            }
+            _ => {}
+        });
+    }
+
+    fn column(&mut self, column: &Column, span: Span, scope: &[Relation]) {
+        let source = match &column.table {
+            Some(qualifier) => resolve_qualifier(qualifier, scope),
+            None => {
+                let mut candidates = scope.iter().filter(|relation| {
+                    relation.columns.as_ref().is_none_or(|columns| {
+                        columns.iter().any(|name| name.matches(&column.column))
+                    })
+                });
+                match (candidates.next(), candidates.next()) {
+                    (Some(relation), None) => Some(relation.source.clone()),
+                    _ => None,
+                }
+            }
+        };
+        self.columns.push(ColumnUse {
+            name: Some(column.column.clone()),
+            qualifier: column.table.clone(),
+            source,
+            span,
+        })
+    }
+}
+
+/// A query of `WITH` in scope.
+#[derive(Clone)]
+struct CommonTable {
+    name: Identifier,
+    /// The names of its columns, `None` when its query has a wildcard
+    columns: Option<Vec<Identifier>>,
+}
+
+/// A relation of a query's `FROM` clause.
+struct Relation {
+    source: Source,
+    /// The name the query gives it, which hides the name of a table
+    alias: Option<Identifier>,
+    /// The names of its columns, `None` when unknown e.g. for a table
+    columns: Option<Vec<Identifier>>,
+}
+
+/// Returns the tables and columns `stmt` refers to.
+pub fn references(stmt: &Statement) -> References {
+    let mut refs = References::default();
+    match stmt {
+        Statement::Select(query) => refs.query(query, &[]),
+        Statement::Insert(insert) => {
+            refs.table(&insert.table, Access::Write);
+            let scope = target_scope(&insert.table);
+            for field in insert.fields.iter().flatten() {
+                refs.column(field, field.column.span, &scope);
+            }
+            // the values don't read the target
+            for expr in insert.data.iter().flatten() {
+                refs.expr(expr, &[]);
+            }
+        }
+        Statement::Update(update) => {
+            refs.table(&update.table, Access::Write);
+            let scope = target_scope(&update.table);
+            for assignment in &update.fields {
+                let column = &assignment.column;
+                refs.column(column, column.column.span, &scope);
+                refs.expr(&assignment.value, &scope);
+            }
+            if let Some(where_clause) = &update.where_clause {
+                refs.expr(where_clause, &scope);
+            }
+        }
+        Statement::Delete(delete) => {
+            refs.table(&delete.table, Access::Write);
+            if let Some(where_clause) = &delete.where_clause {
+                refs.expr(where_clause, &target_scope(&delete.table));
+            }
+        }
+        Statement::Drop(drop) => refs.table(&drop.table, Access::Write),
+        Statement::Create(create) => refs.table(&create.table, Access::Write),
+        Statement::Alter(alter) => refs.table(&alter.table, Access::Write),
+        Statement::Error(_) => {}
+    }
+    refs
+}
+
+fn target_scope(table: &Table) -> Vec<Relation> {
+    vec![Relation {
+        source: Source::Table(table.name.clone()),
+        alias: None,
+        columns: None,
+    }]
+}
+
+/// The names of the columns a subquery returns, `None` when it has a wildcard.
+fn output_columns(query: &SelectStatement) -> Option<Vec<Identifier>> {
+    let mut names = Vec::new();
+    for expr in &query.body.projection {
+        match expr {
+            Expression::Alias { alias, .. } => names.push(alias.clone()),
+            Expression::ColumnReference(ColumnReference::Column(column), _) => {
+                names.push(column.column.clone())
+            }
+            Expression::ColumnReference(_, _) => return None,
+            _ => {}
+        }
+    }
+    Some(names)
+}
+
+/// `expr` with the aliases of `projection` it names replaced by the expressions they stand for.
+fn unalias(expr: &Expression, projection: &[Expression]) -> Expression {
+    let mut expr = expr.clone();
+    walk_expressions_mut(&mut expr, |expr| {
+        let Expression::ColumnReference(ColumnReference::Column(column), _) = expr else {
+            return;
+        };
+        if column.table.is_some() {
+            return;
+        }
+        let aliased = projection.iter().find_map(|item| match item {
+            Expression::Alias { expr, alias, .. } if alias.matches(&column.column) => Some(expr),
+            _ => None,
+        });
+        if let Some(aliased) = aliased {
+            *expr = (**aliased).clone();
+        }
+    });
+    expr
+}
+
+/// The relation in `scope` a qualifier such as `t` or `db.t` names.
+fn resolve_qualifier(qualifier: &ObjectName, scope: &[Relation]) -> Option<Source> {
+    scope
+        .iter()
+        .find(|relation| match (&relation.alias, &relation.source) {
+            (Some(alias), _) => qualifier.qualifier().is_empty() && alias.matches(qualifier.name()),
+            // `t.a` may refer to `db.t`
+            (None, Source::Table(name)) => name.ends_with(qualifier),
+            (None, Source::SubQuery { .. }) => false,
+            (None, Source::CommonTable(name)) => {
+                qualifier.qualifier().is_empty() && name.matches(qualifier.name())
+            }
+        })
+        .map(|relation| relation.source.clone())
+}
+
+impl Spanned for Source {
+    fn span(&self) -> Span {
+        match self {
+            Source::Table(name) => name.span(),
+            Source::SubQuery { span, .. } => *span,
+            Source::CommonTable(name) => name.span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_statement;
+
+    /// The columns as `qualifier.name -> source`, `?` for an unresolved one.
+    fn columns(sql: &str) -> Vec<String> {
+        let refs = references(&parse_statement(sql).unwrap());
+        refs.columns
+            .iter()
+            .map(|column| {
+                let name = match &column.name {
+                    Some(name) => name.to_string(),
+                    None => "*".to_string(),
+                };
+                let name = match &column.qualifier {
+                    Some(qualifier) => format!("{}.{}", qualifier, name),
+                    None => name,
+                };
+                let source = match &column.source {
+                    Some(Source::Table(table)) => table.to_string(),
+                    Some(Source::SubQuery { alias, .. }) => match alias {
+                        Some(alias) => format!("({})", alias),
+                        None => "()".to_string(),
+                    },
+                    Some(Source::CommonTable(name)) => format!("with {}", name),
+                    None => "?".to_string(),
+                };
+                format!("{} -> {}", name, source)
+            })
+            .collect()
+    }
+
+    fn tables(sql: &str) -> Vec<(String, Access)> {
+        references(&parse_statement(sql).unwrap())
+            .tables
+            .iter()
+            .map(|table| (table.table.to_string(), table.access))
+            .collect()
+    }
+
+    #[test]
+    fn test_tables() {
+        assert_eq!(
+            tables("SELECT a FROM db.t1 JOIN (SELECT b FROM t2) AS s ON a = b"),
+            vec![
+                ("db.t1".to_string(), Access::Read),
+                ("t2".to_string(), Access::Read)
+            ]
+        );
+        assert_eq!(
+            tables("DELETE FROM t WHERE a = 1"),
+            vec![("t".to_string(), Access::Write)]
+        );
+
+        let refs = references(&parse_statement("SELECT * FROM t JOIN t USING (a)").unwrap());
+        assert_eq!(refs.reads().len(), 1);
+        assert!(refs.writes().is_empty());
+    }
+
+    #[test]
+    fn test_subquery_aliases() {
+        let sql = "SELECT s.x, y FROM t JOIN (SELECT a AS x, b AS y FROM u) AS s ON t.id = s.x";
+        let refs = references(&parse_statement(sql).unwrap());
+        assert!(matches!(
+            &refs.subqueries[..],
+            [Source::SubQuery { alias: Some(alias), .. }] if alias.value == "s"
+        ));
+        assert_eq!(
+            columns(sql),
+            vec![
+                "a -> u",
+                "b -> u",
+                "t.id -> t",
+                "s.x -> (s)",
+                "s.x -> (s)",
+                "y -> ?"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_common_tables_arent_tables() {
+        let sql = "WITH recent (n) AS (SELECT id FROM orders), orders AS (SELECT n FROM recent) \
+                   SELECT r.n, orders.n FROM recent AS r JOIN orders ON r.n = orders.n JOIN db.recent ON TRUE";
+        assert_eq!(
+            tables(sql),
+            vec![
+                ("orders".to_string(), Access::Read),
+                ("db.recent".to_string(), Access::Read)
+            ]
+        );
+        let refs = references(&parse_statement(sql).unwrap());
+        let names = refs.common_tables.iter().map(|name| name.to_string());
+        assert_eq!(names.collect::<Vec<_>>(), ["recent", "orders"]);
+        assert_eq!(
+            columns(sql),
+            vec![
+                "id -> orders",
+                "n -> with recent",
+                "r.n -> with recent",
+                "orders.n -> with orders",
+                "r.n -> with recent",
+                "orders.n -> with orders"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_aliases() {
+        assert_eq!(
+            columns(
+                "SELECT u.a, t.b FROM db.users AS u JOIN users t ON u.id = t.id WHERE users.c = 1"
+            ),
+            vec![
+                "u.id -> db.users",
+                "t.id -> users",
+                "u.a -> db.users",
+                "t.b -> users",
+                "users.c -> ?"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unions() {
+        let sql = "SELECT a FROM t UNION ALL SELECT b FROM u WHERE c = 1 ORDER BY a";
+        assert_eq!(
+            tables(sql),
+            vec![
+                ("t".to_string(), Access::Read),
+                ("u".to_string(), Access::Read)
+            ]
+        );
+        assert_eq!(columns(sql), vec!["a -> t", "b -> u", "c -> u", "a -> t"]);
+    }
+
+    #[test]
+    fn test_columns() {
+        assert_eq!(
+            columns("SELECT a, t.b, COUNT(*) FROM db.t WHERE c = 1 GROUP BY ROLLUP(a, (b, c)) ORDER BY d"),
+            vec![
+                "a -> db.t",
+                "t.b -> db.t",
+                "* -> db.t",
+                "c -> db.t",
+                "a -> db.t",
+                "b -> db.t",
+                "c -> db.t",
+                "d -> db.t"
+            ]
+        );
+        // without a schema either table may have `c`
+        assert_eq!(
+            columns("SELECT c FROM t1 JOIN t2 USING (id)"),
+            vec!["id -> t1", "id -> t2", "c -> ?"]
+        );
+        assert_eq!(columns("SELECT x.* FROM t"), vec!["x.* -> ?"]);
+    }
+
+    #[test]
+    fn test_projection_aliases() {
+        assert_eq!(
+            columns("SELECT a AS x, b + 1 AS y FROM t GROUP BY x, y HAVING y > 2 ORDER BY x, t.x"),
+            vec!["a -> t", "b -> t", "a -> t", "b -> t", "b -> t", "a -> t", "t.x -> t"]
+        );
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert_eq!(
+            columns("SELECT *, s.* FROM t1 JOIN (SELECT a FROM t2) AS s ON TRUE"),
+            vec!["a -> t2", "* -> t1", "* -> (s)", "s.* -> (s)"]
+        );
+        assert_eq!(columns("SELECT *"), vec!["* -> ?"]);
+    }
+
+    #[test]
+    fn test_niladic_functions_arent_columns() {
+        assert_eq!(
+            columns("SELECT a, CURRENT_DATE FROM t WHERE b < current_timestamp"),
+            vec!["a -> t", "b -> t"]
+        );
+    }
+
+    #[test]
+    fn test_write_targets() {
+        assert_eq!(
+            columns("INSERT INTO t (a, b) VALUES (1, c)"),
+            vec!["a -> t", "b -> t", "c -> ?"]
+        );
+        assert_eq!(
+            columns("UPDATE s.t SET a = t.b + 1 WHERE c = 2"),
+            vec!["a -> s.t", "t.b -> s.t", "c -> s.t"]
+        );
+        assert_eq!(
+            tables("DROP TABLE t"),
+            vec![("t".to_string(), Access::Write)]
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! Fixtures shared by the tests of the passes over bound queries.
+use crate::ast::select_statement::SelectStatement;
+use crate::ast::Statement;
+use crate::catalog::Catalog;
+use crate::{parse_statement, ParseOptions};
+
+/// The catalog of the tables `ddl` creates, which must load without errors.
+pub fn catalog(ddl: &str) -> Catalog {
+    let mut catalog = Catalog::new();
+    let diagnostics = catalog.load_script(ddl, &ParseOptions::default());
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    catalog
+}
+
+/// Parses `sql`, which must be a query.
+pub fn query(sql: &str) -> Box<SelectStatement> {
+    match parse_statement(sql).unwrap() {
+        Statement::Select(query) => query,
+        _ => panic!("not a query"),
+    }
+}
@@ -0,0 +1,557 @@
+//! Rewrites SQL written for one [`Dialect`] so that another accepts it.
+//!
+//! The statements are parsed with the source dialect, rewritten in place and printed back:
+//! identifiers are quoted the way the target expects, `LIMIT` and `FETCH` are swapped for one
+//! another, functions are replaced by their equivalent e.g. `IFNULL` by `COALESCE` and integers
+//! used as conditions become booleans. What can't be translated is kept as written and reported.
+//! The comments before a statement are kept, the others are dropped and reported.
+//!
+//! ```
+//! use sql_parser::dialect::{MySqlDialect, PostgreSqlDialect};
+//! use sql_parser::transpile::transpile;
+//!
+//! let sql = "SELECT `id`, IFNULL(name, '') FROM users WHERE 1 LIMIT 20, 10";
+//! let transpiled = transpile(sql, &MySqlDialect, &PostgreSqlDialect).unwrap();
+//! assert_eq!(
+//!     transpiled.sql,
+//!     "SELECT \"id\", COALESCE(name, '') FROM users WHERE TRUE LIMIT 10 OFFSET 20;"
+//! );
+//! assert!(transpiled.diagnostics.is_empty());
+//! ```
+use std::ops::ControlFlow;
+
+use crate::ast::expression::{BinOp, Expression, Literal, NiladicFunction, UnaryOp};
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{Fetch, SelectStatement, TableReference};
+use crate::ast::span::{Span, Spanned};
+use crate::ast::{Identifier, Statement};
+use crate::dialect::Dialect;
+use crate::visitor::{VisitMut, VisitorMut};
+use crate::{parse_script_with, Diagnostic, ParseError, ParseOptions};
+
+/// The result of [`transpile`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transpiled {
+    /// The statements for the target dialect, one per line and each terminated by `;`, after the
+    /// comments before them
+    pub sql: String,
+    /// The constructs that couldn't be translated, they are kept as written in `sql`, and the
+    /// comments dropped
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parses `sql` as `from` and prints it for `to`, failing if it doesn't parse.
+pub fn transpile(
+    sql: &str,
+    from: &'static dyn Dialect,
+    to: &'static dyn Dialect,
+) -> Result<Transpiled, ParseError> {
+    let options = ParseOptions {
+        dialect: from,
+        preserve_comments: true,
+    };
+    let script = parse_script_with(sql, &options);
+    let comments = script.comments.clone();
+    let mut statements = script.into_result()?;
+
+    let mut diagnostics = Vec::new();
+    let mut lines = Vec::new();
+    let mut kept = Vec::new();
+    for stmt in &mut statements {
+        for comment in comments.leading(stmt.span()) {
+            lines.push(comment_for(&comment.text, to));
+            kept.push(comment.span);
+        }
+        diagnostics.extend(transpile_statement(stmt, to));
+        lines.push(format!("{};", stmt));
+    }
+    for comment in comments
+        .iter()
+        .filter(|comment| !kept.contains(&comment.span))
+    {
+        let message = "only the comments before a statement are kept".to_string();
+        diagnostics.push(Diagnostic::new(comment.span, message));
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    let sql = lines.join("\n");
+    Ok(Transpiled { sql, diagnostics })
+}
+
+/// A comment written so that `to` reads it as one, MySQL's `#` becoming `--`.
+fn comment_for(text: &str, to: &dyn Dialect) -> String {
+    match text.strip_prefix('#') {
+        Some(rest) if !to.hash_comments() => format!("-- {}", rest.trim_start()),
+        _ => text.to_string(),
+    }
+}
+
+/// Rewrites `stmt` in place for the dialect `to`, returning what couldn't be translated.
+pub fn transpile_statement(stmt: &mut Statement, to: &'static dyn Dialect) -> Vec<Diagnostic> {
+    let mut rewriter = Rewriter {
+        to,
+        diagnostics: Vec::new(),
+    };
+    let _ = stmt.visit_mut(&mut rewriter);
+    rewriter.diagnostics
+}
+
+/// Functions that can stand in for one another, the first the target supports is used.
+const EQUIVALENT_FUNCTIONS: &[&[&str]] = &[
+    &["coalesce", "ifnull", "nvl"],
+    &["current_timestamp", "now", "getdate"],
+    &["random", "rand"],
+];
+
+struct Rewriter {
+    to: &'static dyn Dialect,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Rewriter {
+    fn report(&mut self, span: Span, message: String) {
+        self.diagnostics.push(Diagnostic::new(span, message))
+    }
+
+    /// Makes `expr`, used as a condition, a boolean where the target doesn't read integers as one,
+    /// reporting a condition that may be an integer.
+    fn condition(&mut self, expr: &mut Expression) {
+        if self.to.integer_booleans() {
+            return;
+        }
+        match expr {
+            Expression::Literal(literal, _) => {
+                if let Literal::UnsignedInteger(n) = literal {
+                    *literal = Literal::Boolean(*n != 0);
+                }
+            }
+            Expression::BinaryExpr {
+                left,
+                op: BinOp::And | BinOp::Or,
+                right,
+                ..
+            } => {
+                self.condition(left);
+                self.condition(right);
+            }
+            Expression::UnaryExpr {
+                op: UnaryOp::LogicalNot,
+                expr,
+                ..
+            } => self.condition(expr),
+            Expression::BinaryExpr {
+                op: BinOp::Eq | BinOp::NotEq | BinOp::Lt | BinOp::LtEq | BinOp::Gt | BinOp::GtEq,
+                ..
+            }
+            | Expression::In { .. } => {}
+            expr => {
+                let message = format!(
+                    "`{}` may be an integer, which {} doesn't read as a condition",
+                    expr,
+                    self.to.name()
+                );
+                self.report(expr.span(), message)
+            }
+        }
+    }
+
+    fn limit(&mut self, query: &mut SelectStatement) {
+        if query.limit.is_some() && !self.to.supports_limit() {
+            if self.to.supports_fetch() && query.fetch.is_none() {
+                query.fetch = Some(Fetch {
+                    quantity: query.limit.take(),
+                    with_ties: false,
+                });
+            } else {
+                self.unsupported(query.span, "`LIMIT`");
+            }
+        }
+
+        if let Some(fetch) = query.fetch.take_if(|_| !self.to.supports_fetch()) {
+            if self.to.supports_limit() && !fetch.with_ties && query.limit.is_none() {
+                // `FETCH FIRST ROW ONLY` is a single row
+                let one = Expression::Literal(Literal::UnsignedInteger(1), query.span);
+                query.limit = Some(fetch.quantity.unwrap_or(one));
+            } else {
+                query.fetch = Some(fetch);
+                self.unsupported(query.span, "`FETCH`");
+            }
+        }
+
+        if query.offset.is_some()
+            && query.limit.is_none()
+            && query.fetch.is_none()
+            && !self.to.supports_offset_without_limit()
+        {
+            self.unsupported(query.span, "`OFFSET` without `LIMIT`");
+        }
+    }
+
+    fn unsupported(&mut self, span: Span, construct: &str) {
+        let message = format!("{} can't be written in {}", construct, self.to.name());
+        self.report(span, message)
+    }
+
+    /// Replaces a call to a function the target lacks by an equivalent one.
+    fn function(&mut self, expr: &mut Expression) {
+        let Expression::Function { name, args, span } = expr else {
+            return;
+        };
+        let ([], function) = (name.qualifier(), name.name()) else {
+            return;
+        };
+        let lower = function.value.to_lowercase();
+        if function.quote_style.is_some() || self.to.supports_function(&lower) {
+            return;
+        }
+
+        let equivalent = EQUIVALENT_FUNCTIONS
+            .iter()
+            .find(|names| names.contains(&lower.as_str()))
+            .and_then(|names| names.iter().find(|name| self.to.supports_function(name)));
+        let Some(equivalent) = equivalent else {
+            let message = format!("`{}` isn't available in {}", function.value, self.to.name());
+            return self.report(*span, message);
+        };
+
+        if let Some(function) = NiladicFunction::from_name(equivalent).filter(|_| args.is_empty()) {
+            *expr = Expression::Niladic(function, *span);
+            return;
+        }
+        // written in the case of the original
+        let equivalent = if function
+            .value
+            .chars()
+            .next()
+            .is_some_and(char::is_uppercase)
+        {
+            equivalent.to_uppercase()
+        } else {
+            equivalent.to_string()
+        };
+        *expr = Expression::Function {
+            name: Identifier::new(equivalent).with_span(function.span).into(),
+            args: std::mem::take(args),
+            span: *span,
+        };
+    }
+
+    /// Replaces `a % b` by `MOD(a, b)` where `%` isn't an operator.
+    fn modulo(&mut self, expr: &mut Expression) {
+        if !matches!(expr, Expression::BinaryExpr { op: BinOp::Mod, .. })
+            || self.to.supports_operator("%")
+        {
+            return;
+        }
+        let span = expr.span();
+        if let Expression::BinaryExpr { left, right, .. } =
+            std::mem::replace(expr, Expression::Error(span))
+        {
+            *expr = Expression::Function {
+                name: Identifier::new("MOD").into(),
+                args: vec![*left, *right],
+                span,
+            };
+        }
+    }
+}
+
+impl VisitorMut for Rewriter {
+    type Break = ();
+
+    fn pre_visit_statement(&mut self, stmt: &mut Statement) -> ControlFlow<()> {
+        if !self.to.supports_statement(stmt) {
+            self.unsupported(stmt.span(), "this statement");
+        }
+        match stmt {
+            Statement::Update(update) => {
+                if let Some(where_clause) = &mut update.where_clause {
+                    self.condition(where_clause);
+                }
+            }
+            Statement::Delete(delete) => {
+                if let Some(where_clause) = &mut delete.where_clause {
+                    self.condition(where_clause);
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut SelectStatement) -> ControlFlow<()> {
+        self.limit(query);
+        let clauses = std::iter::once(&mut query.body)
+            .chain(query.unions.iter_mut().map(|union| &mut union.clause));
+        for clause in clauses {
+            for condition in [&mut clause.where_exp, &mut clause.having]
+                .into_iter()
+                .flatten()
+            {
+                self.condition(condition);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_reference(&mut self, table_ref: &mut TableReference) -> ControlFlow<()> {
+        if let TableReference::Join {
+            cond: JoinCondition::On(conditions),
+            ..
+        } = table_ref
+        {
+            for condition in conditions {
+                self.condition(condition);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expression(&mut self, expr: &mut Expression) -> ControlFlow<()> {
+        self.function(expr);
+        self.modulo(expr);
+        if let Expression::Literal(Literal::String(value), span) = expr {
+            if self.to.backslash_escapes() && value.contains('\\') {
+                let message = format!(
+                    "the backslash in this string is read as an escape by {}",
+                    self.to.name()
+                );
+                self.report(*span, message);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_identifier(&mut self, ident: &mut Identifier) -> ControlFlow<()> {
+        let quote = ['"', '`', '[']
+            .into_iter()
+            .find(|quote| self.to.is_identifier_quote(*quote));
+        match (ident.quote_style, quote) {
+            (Some(style), Some(quote)) if !self.to.is_identifier_quote(style) => {
+                ident.quote_style = Some(quote)
+            }
+            // quoted in its folded case so it still names the same object
+            (None, Some(quote)) if self.to.is_reserved_word(&ident.value) => {
+                ident.value = ident.normalized();
+                ident.quote_style = Some(quote);
+            }
+            (_, None) => self.unsupported(ident.span, "a quoted identifier"),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dialect::{
+        AnsiDialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SqliteDialect,
+    };
+    use crate::parse_statement;
+
+    fn sql(sql: &str, from: &'static dyn Dialect, to: &'static dyn Dialect) -> String {
+        let transpiled = transpile(sql, from, to).unwrap();
+        assert_eq!(transpiled.diagnostics, vec![], "{}", sql);
+        transpiled.sql
+    }
+
+    fn messages(sql: &str, from: &'static dyn Dialect, to: &'static dyn Dialect) -> Vec<String> {
+        transpile(sql, from, to)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(
+            sql(
+                "SELECT `a b`, c FROM `db`.t",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            "SELECT \"a b\", c FROM \"db\".t;"
+        );
+        assert_eq!(
+            sql("SELECT \"a\" FROM [t]", &SqliteDialect, &MySqlDialect),
+            "SELECT `a` FROM `t`;"
+        );
+        // `OFFSET` isn't reserved by MySQL
+        assert_eq!(
+            sql("SELECT Offset FROM t", &MySqlDialect, &PostgreSqlDialect),
+            "SELECT \"offset\" FROM t;"
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(
+            sql(
+                "SELECT a FROM t LIMIT 5, 10",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            "SELECT a FROM t LIMIT 10 OFFSET 5;"
+        );
+        assert_eq!(
+            sql(
+                "SELECT a FROM t LIMIT 10 OFFSET 5",
+                &PostgreSqlDialect,
+                &AnsiDialect
+            ),
+            "SELECT a FROM t OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY;"
+        );
+        assert_eq!(
+            sql(
+                "SELECT a FROM t FETCH FIRST ROW ONLY",
+                &AnsiDialect,
+                &MySqlDialect
+            ),
+            "SELECT a FROM t LIMIT 1;"
+        );
+        assert_eq!(
+            messages(
+                "SELECT a FROM t ORDER BY a FETCH FIRST 3 ROWS WITH TIES",
+                &PostgreSqlDialect,
+                &SqliteDialect
+            ),
+            vec!["`FETCH` can't be written in SQLite"]
+        );
+        assert_eq!(
+            sql(
+                "SELECT a FROM t OFFSET 5 ROWS FETCH NEXT 10 ROWS ONLY",
+                &AnsiDialect,
+                &MySqlDialect
+            ),
+            "SELECT a FROM t LIMIT 10 OFFSET 5;"
+        );
+        assert_eq!(
+            messages(
+                "SELECT a FROM t OFFSET 5",
+                &PostgreSqlDialect,
+                &MySqlDialect
+            ),
+            vec!["`OFFSET` without `LIMIT` can't be written in MySQL"]
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(
+            sql(
+                "SELECT IFNULL(a, 0), now(), RAND() FROM t",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            "SELECT COALESCE(a, 0), now(), RANDOM() FROM t;"
+        );
+        assert_eq!(
+            sql("SELECT NOW() FROM t", &MySqlDialect, &SqliteDialect),
+            "SELECT CURRENT_TIMESTAMP FROM t;"
+        );
+        let mut stmt = parse_statement("SELECT NOW()").unwrap();
+        transpile_statement(&mut stmt, &SqliteDialect);
+        let Statement::Select(query) = stmt else {
+            panic!("expected a select statement")
+        };
+        assert!(matches!(
+            query.body.projection[..],
+            [Expression::Niladic(NiladicFunction::CurrentTimestamp, _)]
+        ));
+        assert_eq!(
+            sql("SELECT a % 2 FROM t", &PostgreSqlDialect, &AnsiDialect),
+            "SELECT MOD(a, 2) FROM t;"
+        );
+        assert_eq!(
+            messages("SELECT RAND() FROM t", &MySqlDialect, &AnsiDialect),
+            vec!["`RAND` isn't available in ANSI SQL"]
+        );
+    }
+
+    #[test]
+    fn test_booleans() {
+        assert_eq!(
+            sql(
+                "SELECT a FROM t JOIN u ON 1 WHERE NOT 0 AND a = 1 OR 2",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            "SELECT a FROM t JOIN u ON TRUE WHERE NOT FALSE AND a = 1 OR TRUE;"
+        );
+        assert_eq!(
+            sql(
+                "SELECT a FROM t WHERE 1 UNION SELECT b FROM u WHERE 0",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            "SELECT a FROM t WHERE TRUE UNION SELECT b FROM u WHERE FALSE;"
+        );
+        assert_eq!(
+            sql("DELETE FROM t WHERE 1", &SqliteDialect, &AnsiDialect),
+            "DELETE FROM t WHERE TRUE;"
+        );
+        assert_eq!(
+            sql("SELECT 1 FROM t WHERE 1", &MySqlDialect, &SqliteDialect),
+            "SELECT 1 FROM t WHERE 1;"
+        );
+        // the type of a column isn't known
+        assert_eq!(
+            messages(
+                "SELECT a FROM t WHERE a IN (1) AND NOT b",
+                &MySqlDialect,
+                &PostgreSqlDialect
+            ),
+            vec!["`b` may be an integer, which PostgreSQL doesn't read as a condition"]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let transpiled = transpile(
+            "# all of them\nSELECT a /* the key */ FROM t;\n/* old */ DELETE FROM t; -- done",
+            &MySqlDialect,
+            &PostgreSqlDialect,
+        )
+        .unwrap();
+        assert_eq!(
+            transpiled.sql,
+            "-- all of them\nSELECT a FROM t;\n/* old */\nDELETE FROM t;"
+        );
+        let dropped = transpiled
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.span.clone());
+        assert_eq!(dropped.collect::<Vec<_>>(), [23..36, 70..77]);
+    }
+
+    #[test]
+    fn test_untranslatable() {
+        let transpiled = transpile(
+            "DROP TABLE IF EXISTS t; SELECT 'C:\\path' FROM t",
+            &GenericDialect,
+            &AnsiDialect,
+        )
+        .unwrap();
+        assert_eq!(
+            transpiled.sql,
+            "DROP TABLE IF EXISTS t;\nSELECT 'C:\\path' FROM t;"
+        );
+        assert_eq!(transpiled.diagnostics.len(), 1);
+        assert_eq!(
+            transpiled.diagnostics[0].message,
+            "this statement can't be written in ANSI SQL"
+        );
+        assert_eq!(transpiled.diagnostics[0].span, 0..22);
+
+        assert_eq!(
+            messages(
+                "SELECT 'C:\\path' FROM t",
+                &PostgreSqlDialect,
+                &MySqlDialect
+            ),
+            vec!["the backslash in this string is read as an escape by MySQL"]
+        );
+    }
+}
@@ -0,0 +1,792 @@
+//! Type inference and checking of the expressions of a bound query, see [`crate::binder`].
+//!
+//! Every expression gets a [`SqlType`] and whether it may be `NULL`. Operands must suit their
+//! operator, e.g. `'abc' + 1` or comparing a `DATE` with a `BOOLEAN` is reported, and the
+//! aggregates and common functions check their arguments. An operand of unknown type, such as a
+//! parameter, `NULL` or a call to a function this module doesn't know, is accepted anywhere.
+//! Whether an integer may stand for a condition, as in `WHERE id`, is up to the dialect, see
+//! [`check_with`].
+//!
+//! ```
+//! use sql_parser::ast::Statement;
+//! use sql_parser::binder::bind;
+//! use sql_parser::catalog::Catalog;
+//! use sql_parser::types::{check, SqlType};
+//! use sql_parser::{parse_statement, ParseOptions};
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.load_script("CREATE TABLE t (a INT NOT NULL, b TEXT)", &ParseOptions::default());
+//!
+//! let Statement::Select(query) = parse_statement("SELECT a * 1.5, SUM(b) FROM t").unwrap()
+//! else { unreachable!() };
+//! let types = check(&query, &bind(&query, &catalog));
+//! let product = types.of(&query.body.projection[0]).unwrap();
+//! assert_eq!(product.sql_type, SqlType::Decimal);
+//! assert!(!product.nullable);
+//! assert_eq!(types.diagnostics[0].message, "`SUM` needs a number, found TEXT");
+//! ```
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+use crate::ast::expression::{
+    AggregationFunction, BinOp, ColumnReference, Expression, Literal, NiladicFunction, UnaryOp,
+};
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{
+    GroupBy, GroupingElement, SelectClause, SelectStatement, TableReference,
+};
+use crate::ast::span::Spanned;
+use crate::binder::{address, Binding, Bound};
+use crate::diagnostic::Diagnostic;
+use crate::dialect::{Dialect, GenericDialect};
+
+/// The type of a value, types that can't be told apart by the checks are merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SqlType {
+    Boolean,
+    Integer,
+    /// Exact numbers with a fraction e.g. `DECIMAL(10, 2)` or the literal `2.5`
+    Decimal,
+    Float,
+    Text,
+    Date,
+    Time,
+    Timestamp,
+    /// Any other or unknown type, e.g. of `NULL` or a parameter, accepted anywhere
+    Unknown,
+}
+
+impl SqlType {
+    /// The type of a column declared as `sql_type` e.g. `VARCHAR(20)` or `DOUBLE PRECISION`.
+    pub fn parse(sql_type: &str) -> SqlType {
+        let name = sql_type
+            .split(['(', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "bool" | "boolean" => SqlType::Boolean,
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2"
+            | "int4" | "int8" | "serial" | "bigserial" => SqlType::Integer,
+            "decimal" | "numeric" | "dec" | "number" => SqlType::Decimal,
+            "float" | "float4" | "float8" | "real" | "double" => SqlType::Float,
+            "char" | "character" | "varchar" | "nchar" | "nvarchar" | "text" | "tinytext"
+            | "mediumtext" | "longtext" | "string" | "clob" => SqlType::Text,
+            "date" => SqlType::Date,
+            "time" => SqlType::Time,
+            "timestamp" | "datetime" | "timestamptz" => SqlType::Timestamp,
+            _ => SqlType::Unknown,
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            SqlType::Integer | SqlType::Decimal | SqlType::Float | SqlType::Unknown
+        )
+    }
+
+    fn is_temporal(self) -> bool {
+        matches!(self, SqlType::Date | SqlType::Time | SqlType::Timestamp)
+    }
+
+    /// Whether values of both types can be compared, text may stand for a date or time.
+    pub fn is_comparable(self, other: SqlType) -> bool {
+        use SqlType::*;
+        match (self, other) {
+            (Unknown, _) | (_, Unknown) => true,
+            (a, b) if a.is_numeric() && b.is_numeric() => true,
+            (a, b) if a.is_temporal() && b.is_temporal() => a == b || a != Time && b != Time,
+            (Text, b) | (b, Text) if b.is_temporal() => true,
+            (a, b) => a == b,
+        }
+    }
+
+    /// The type both can be converted to, `None` when they've nothing in common.
+    fn common(self, other: SqlType) -> Option<SqlType> {
+        use SqlType::*;
+        match (self, other) {
+            (Unknown, t) | (t, Unknown) => Some(t),
+            (a, b) if a == b => Some(a),
+            (Float, b) | (b, Float) if b.is_numeric() => Some(Float),
+            (Decimal, b) | (b, Decimal) if b.is_numeric() => Some(Decimal),
+            (Date, Timestamp) | (Timestamp, Date) => Some(Timestamp),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SqlType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SqlType::Boolean => "BOOLEAN",
+            SqlType::Integer => "INTEGER",
+            SqlType::Decimal => "DECIMAL",
+            SqlType::Float => "FLOAT",
+            SqlType::Text => "TEXT",
+            SqlType::Date => "DATE",
+            SqlType::Time => "TIME",
+            SqlType::Timestamp => "TIMESTAMP",
+            SqlType::Unknown => "unknown type",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The inferred type of an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprType {
+    pub sql_type: SqlType,
+    /// Whether the expression may be `NULL`
+    pub nullable: bool,
+}
+
+impl ExprType {
+    fn new(sql_type: SqlType, nullable: bool) -> Self {
+        ExprType { sql_type, nullable }
+    }
+
+    const UNKNOWN: ExprType = ExprType {
+        sql_type: SqlType::Unknown,
+        nullable: true,
+    };
+}
+
+/// The result of [`check`], borrowing the query as its expressions are told apart by address like
+/// in [`crate::binder`].
+#[derive(Debug, Clone, Default)]
+pub struct Types<'q> {
+    types: HashMap<usize, ExprType>,
+    /// Operands and arguments of the wrong type, in source order
+    pub diagnostics: Vec<Diagnostic>,
+    query: PhantomData<&'q SelectStatement>,
+}
+
+impl Types<'_> {
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+
+    /// The type of an expression of the query, `None` for `*` and `tbl.*`.
+    pub fn of(&self, expr: &Expression) -> Option<&ExprType> {
+        self.types.get(&address(expr))
+    }
+}
+
+/// Infers the type of every expression of `query` and its subqueries, `bound` being its
+/// bindings, as [`GenericDialect`] reads conditions.
+pub fn check<'q>(query: &'q SelectStatement, bound: &Bound<'_>) -> Types<'q> {
+    check_with(query, bound, &GenericDialect)
+}
+
+/// Same as [`check`], an integer being accepted as a condition when `dialect` reads it as a
+/// boolean.
+pub fn check_with<'q>(
+    query: &'q SelectStatement,
+    bound: &Bound<'_>,
+    dialect: &dyn Dialect,
+) -> Types<'q> {
+    let mut checker = Checker {
+        bound,
+        dialect,
+        types: Types::default(),
+    };
+    checker.query(query);
+    let mut types = checker.types;
+    types
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.span.start);
+    types
+}
+
+struct Checker<'a, 'q> {
+    bound: &'a Bound<'a>,
+    dialect: &'a dyn Dialect,
+    types: Types<'q>,
+}
+
+impl Checker<'_, '_> {
+    fn error(&mut self, expr: &Expression, message: String) {
+        self.types
+            .diagnostics
+            .push(Diagnostic::new(expr.span(), message));
+    }
+
+    fn query(&mut self, query: &SelectStatement) {
+        for cte in &query.with {
+            self.query(&cte.query);
+        }
+        self.select_clause(&query.body);
+        for union in &query.unions {
+            self.select_clause(&union.clause);
+            self.union(&query.body, &union.clause);
+        }
+        for order_by in &query.order_by {
+            self.expr(&order_by.expr);
+        }
+        let fetch = query
+            .fetch
+            .as_ref()
+            .and_then(|fetch| fetch.quantity.as_ref());
+        for (count, clause) in [
+            (query.limit.as_ref(), "LIMIT"),
+            (query.offset.as_ref(), "OFFSET"),
+            (fetch, "FETCH"),
+        ] {
+            if let Some(count) = count {
+                let sql_type = self.expr(count).sql_type;
+                if !matches!(sql_type, SqlType::Integer | SqlType::Unknown) {
+                    self.error(
+                        count,
+                        format!("`{}` needs an INTEGER, found {}", clause, sql_type),
+                    );
+                }
+            }
+        }
+    }
+
+    fn select_clause(&mut self, body: &SelectClause) {
+        if let Some(from) = &body.from {
+            self.table_ref(from);
+        }
+        for item in &body.projection {
+            self.expr(item);
+        }
+        if let Some(where_exp) = &body.where_exp {
+            self.condition(where_exp, "WHERE");
+        }
+        if let Some(GroupBy::Elements(elements)) = &body.group_by {
+            for expr in elements.iter().flat_map(GroupingElement::exprs) {
+                self.expr(expr);
+            }
+        }
+        if let Some(having) = &body.having {
+            self.condition(having, "HAVING");
+        }
+    }
+
+    /// Reports the result columns of `clause` that have nothing in common with those of `first`.
+    fn union(&mut self, first: &SelectClause, clause: &SelectClause) {
+        let is_wildcard = |item: &Expression| {
+            matches!(
+                item,
+                Expression::ColumnReference(
+                    ColumnReference::Wildcard | ColumnReference::QualifiedWildcard(_),
+                    _
+                )
+            )
+        };
+        // the columns of `*` aren't typed one by one, nor paired when the counts differ
+        if first.projection.len() != clause.projection.len()
+            || first
+                .projection
+                .iter()
+                .chain(&clause.projection)
+                .any(is_wildcard)
+        {
+            return;
+        }
+        for (left, right) in first.projection.iter().zip(&clause.projection) {
+            let (Some(l), Some(r)) = (self.types.of(left), self.types.of(right)) else {
+                continue;
+            };
+            let (l, r) = (l.sql_type, r.sql_type);
+            if l.common(r).is_none() {
+                self.error(right, format!("UNION can't combine {} with {}", l, r));
+            }
+        }
+    }
+
+    fn table_ref(&mut self, table_ref: &TableReference) {
+        match table_ref {
+            TableReference::BaseRelation { .. } => {}
+            TableReference::SubQuery { subquery, .. } => self.query(subquery),
+            TableReference::Join {
+                left, right, cond, ..
+            } => {
+                self.table_ref(left);
+                self.table_ref(right);
+                if let JoinCondition::On(conditions) = cond {
+                    for condition in conditions {
+                        self.condition(condition, "ON");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a value of `sql_type` can be used as a condition.
+    fn is_condition(&self, sql_type: SqlType) -> bool {
+        match sql_type {
+            SqlType::Boolean | SqlType::Unknown => true,
+            SqlType::Integer => self.dialect.integer_booleans(),
+            _ => false,
+        }
+    }
+
+    fn condition(&mut self, condition: &Expression, clause: &str) {
+        let sql_type = self.expr(condition).sql_type;
+        if !self.is_condition(sql_type) {
+            self.error(
+                condition,
+                format!("`{}` needs a BOOLEAN condition, found {}", clause, sql_type),
+            );
+        }
+    }
+
+    /// Infers the type of `expr`, reporting operands of the wrong type.
+    fn expr(&mut self, expr: &Expression) -> ExprType {
+        let typed = match expr {
+            Expression::ColumnReference(ColumnReference::Column(_), _) => {
+                match self.bound.column(expr) {
+                    Some(Binding::Column {
+                        column,
+                        nullable,
+                        outer,
+                        ..
+                    }) => ExprType::new(SqlType::parse(&column.sql_type), *nullable || *outer),
+                    Some(Binding::Expression { expr, outer, .. }) => {
+                        // a copy of an item checked where it's written, its errors are reported there
+                        let mut checker = Checker {
+                            bound: self.bound,
+                            dialect: self.dialect,
+                            types: Types::default(),
+                        };
+                        let typed = checker.expr(expr);
+                        ExprType::new(typed.sql_type, typed.nullable || *outer)
+                    }
+                    None => ExprType::UNKNOWN,
+                }
+            }
+            // stands for several columns
+            Expression::ColumnReference(_, _) => return ExprType::UNKNOWN,
+            Expression::Alias { expr, .. } => self.expr(expr),
+            Expression::Literal(literal, _) => match literal {
+                Literal::Null => ExprType::UNKNOWN,
+                Literal::Boolean(_) => ExprType::new(SqlType::Boolean, false),
+                Literal::String(_) => ExprType::new(SqlType::Text, false),
+                Literal::UnsignedInteger(_) => ExprType::new(SqlType::Integer, false),
+                Literal::UnsignedFloat(_) => ExprType::new(SqlType::Decimal, false),
+                Literal::Date(_) => ExprType::new(SqlType::Date, false),
+            },
+            Expression::Parameter(..) | Expression::Error(_) => ExprType::UNKNOWN,
+            Expression::Niladic(function, _) => {
+                let sql_type = match function {
+                    NiladicFunction::CurrentDate => SqlType::Date,
+                    NiladicFunction::CurrentTime => SqlType::Time,
+                    NiladicFunction::CurrentTimestamp => SqlType::Timestamp,
+                };
+                ExprType::new(sql_type, false)
+            }
+            Expression::UnaryExpr {
+                op, expr: operand, ..
+            } => {
+                let typed = self.expr(operand);
+                let (ok, expected) = match op {
+                    UnaryOp::Minus => (typed.sql_type.is_numeric(), "a number"),
+                    UnaryOp::LogicalNot => (self.is_condition(typed.sql_type), "a BOOLEAN"),
+                };
+                if !ok {
+                    self.error(
+                        expr,
+                        format!("`{}` needs {}, found {}", op, expected, typed.sql_type),
+                    );
+                }
+                match op {
+                    UnaryOp::Minus => typed,
+                    UnaryOp::LogicalNot => ExprType::new(SqlType::Boolean, typed.nullable),
+                }
+            }
+            Expression::BinaryExpr {
+                left, op, right, ..
+            } => {
+                let left = self.expr(left);
+                let right = self.expr(right);
+                self.binary(expr, op, left, right)
+            }
+            Expression::In { left, right, .. } => {
+                let typed = self.expr(left);
+                let mut nullable = typed.nullable;
+                for item in right {
+                    let item_type = self.expr(item);
+                    nullable |= item_type.nullable;
+                    if !typed.sql_type.is_comparable(item_type.sql_type) {
+                        self.error(
+                            item,
+                            format!(
+                                "can't compare {} with {}",
+                                typed.sql_type, item_type.sql_type
+                            ),
+                        );
+                    }
+                }
+                ExprType::new(SqlType::Boolean, nullable)
+            }
+            Expression::FunctionExpression { func, args, .. } => self.aggregate(expr, func, args),
+            Expression::Function { name, args, .. } => {
+                let args = args.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+                self.function(expr, &name.name().normalized(), &args)
+            }
+        };
+        self.types.types.insert(address(expr), typed);
+        typed
+    }
+
+    fn binary(
+        &mut self,
+        expr: &Expression,
+        op: &BinOp,
+        left: ExprType,
+        right: ExprType,
+    ) -> ExprType {
+        use SqlType::*;
+        let nullable = left.nullable || right.nullable;
+        let (l, r) = (left.sql_type, right.sql_type);
+        let sql_type = match op {
+            BinOp::And | BinOp::Or => {
+                if [l, r].iter().any(|t| !self.is_condition(*t)) {
+                    self.error(
+                        expr,
+                        format!("`{}` needs BOOLEAN operands, found {} and {}", op, l, r),
+                    );
+                }
+                Some(Boolean)
+            }
+            BinOp::Eq | BinOp::NotEq | BinOp::Lt | BinOp::LtEq | BinOp::Gt | BinOp::GtEq => {
+                if !l.is_comparable(r) {
+                    self.error(expr, format!("can't compare {} with {}", l, r));
+                }
+                Some(Boolean)
+            }
+            BinOp::Addition | BinOp::Subtraction => match (l, r) {
+                // days before or after a date
+                (Date | Timestamp, Integer) => Some(l),
+                (Integer, Date | Timestamp) if op == &BinOp::Addition => Some(r),
+                (Date, Date) if op == &BinOp::Subtraction => Some(Integer),
+                _ => numeric(l, r),
+            },
+            BinOp::Multiplication | BinOp::Division | BinOp::Mod => numeric(l, r),
+        };
+        match sql_type {
+            Some(sql_type) => ExprType::new(sql_type, nullable),
+            None => {
+                self.error(expr, format!("can't apply `{}` to {} and {}", op, l, r));
+                ExprType::new(Unknown, nullable)
+            }
+        }
+    }
+
+    fn aggregate(
+        &mut self,
+        expr: &Expression,
+        func: &AggregationFunction,
+        args: &[Expression],
+    ) -> ExprType {
+        let arg = args.first().map_or(ExprType::UNKNOWN, |arg| self.expr(arg));
+        for arg in args.iter().skip(1) {
+            self.expr(arg);
+        }
+        // an aggregate over no rows is `NULL`, except a count
+        match func {
+            AggregationFunction::Count => ExprType::new(SqlType::Integer, false),
+            AggregationFunction::Sum | AggregationFunction::Avg => {
+                if !arg.sql_type.is_numeric() {
+                    self.error(
+                        expr,
+                        format!("`{}` needs a number, found {}", func, arg.sql_type),
+                    );
+                    return ExprType::UNKNOWN;
+                }
+                let sql_type = match (func, arg.sql_type) {
+                    (AggregationFunction::Avg, SqlType::Integer) => SqlType::Decimal,
+                    (_, sql_type) => sql_type,
+                };
+                ExprType::new(sql_type, true)
+            }
+            AggregationFunction::Min | AggregationFunction::Max => {
+                if arg.sql_type == SqlType::Boolean {
+                    self.error(
+                        expr,
+                        format!("`{}` needs comparable values, found BOOLEAN", func),
+                    );
+                }
+                ExprType::new(arg.sql_type, true)
+            }
+        }
+    }
+
+    /// Checks a call to one of the common functions, any other is of unknown type.
+    fn function(&mut self, expr: &Expression, name: &str, args: &[ExprType]) -> ExprType {
+        use SqlType::*;
+        let nullable = args.iter().any(|arg| arg.nullable);
+        let (params, result): (&[SqlType], ExprType) = match name {
+            "coalesce" | "ifnull" | "nvl" => {
+                let mut common = Some(Unknown);
+                for arg in args {
+                    common = common.and_then(|common| common.common(arg.sql_type));
+                }
+                let Some(sql_type) = common else {
+                    let types = args.iter().map(|arg| arg.sql_type.to_string());
+                    self.error(
+                        expr,
+                        format!(
+                            "the arguments of `{}` have no common type: {}",
+                            name,
+                            types.collect::<Vec<_>>().join(", ")
+                        ),
+                    );
+                    return ExprType::UNKNOWN;
+                };
+                let nullable = args.iter().all(|arg| arg.nullable);
+                return ExprType::new(sql_type, nullable);
+            }
+            "abs" | "ceil" | "ceiling" | "floor" | "round" => {
+                let sql_type = args.first().map_or(Unknown, |arg| arg.sql_type);
+                (&[Decimal, Integer], ExprType::new(sql_type, nullable))
+            }
+            "mod" => (&[Integer, Integer], ExprType::new(Integer, nullable)),
+            "upper" | "lower" | "trim" | "ltrim" | "rtrim" => {
+                (&[Text], ExprType::new(Text, nullable))
+            }
+            "length" | "char_length" | "character_length" => {
+                (&[Text], ExprType::new(Integer, nullable))
+            }
+            "substr" | "substring" => (&[Text, Integer, Integer], ExprType::new(Text, nullable)),
+            "concat" => (&[], ExprType::new(Text, nullable)),
+            "now" | "current_timestamp" | "getdate" => (&[], ExprType::new(Timestamp, false)),
+            "current_date" => (&[], ExprType::new(Date, false)),
+            "current_time" => (&[], ExprType::new(Time, false)),
+            "random" | "rand" => (&[], ExprType::new(Float, false)),
+            _ => return ExprType::UNKNOWN,
+        };
+        for (arg, param) in args.iter().zip(params) {
+            let ok = match param {
+                Integer | Decimal => arg.sql_type.is_numeric(),
+                param => arg.sql_type == *param || arg.sql_type == Unknown,
+            };
+            if !ok {
+                let expected = match param {
+                    Integer | Decimal => "a number".to_string(),
+                    param => format!("{}", param),
+                };
+                self.error(
+                    expr,
+                    format!("`{}` needs {}, found {}", name, expected, arg.sql_type),
+                );
+            }
+        }
+        result
+    }
+}
+
+/// The type of arithmetic over `l` and `r`, `None` unless both are numbers.
+fn numeric(l: SqlType, r: SqlType) -> Option<SqlType> {
+    match l.is_numeric() && r.is_numeric() {
+        true => l.common(r),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::binder::bind;
+    use crate::catalog::Catalog;
+    use crate::dialect::{MySqlDialect, PostgreSqlDialect};
+    use crate::testing::query;
+    use crate::visitor::clear_spans;
+
+    fn catalog() -> Catalog {
+        crate::testing::catalog(
+            "CREATE TABLE t (id INT PRIMARY KEY, name VARCHAR(20), price DECIMAL(10, 2) NOT NULL,
+                             born DATE, active BOOLEAN NOT NULL, seen TIMESTAMP);
+             CREATE TABLE u (id INT NOT NULL, t_id INT NOT NULL);
+             CREATE TABLE v (id INT, code TEXT, PRIMARY KEY (id))",
+        )
+    }
+
+    /// The types of the select list as `TYPE` or `TYPE NULL`.
+    fn types(sql: &str) -> Vec<String> {
+        let query = query(sql);
+        let types = check(&query, &bind(&query, &catalog()));
+        assert!(!types.has_errors(), "{:?}", types.diagnostics);
+        query
+            .body
+            .projection
+            .iter()
+            .map(|item| {
+                let typed = types.of(item).unwrap();
+                match typed.nullable {
+                    true => format!("{} NULL", typed.sql_type),
+                    false => typed.sql_type.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// The errors under PostgreSQL, which doesn't read integers as booleans.
+    fn errors(sql: &str) -> Vec<String> {
+        let query = query(sql);
+        let types = check_with(&query, &bind(&query, &catalog()), &PostgreSqlDialect);
+        types.diagnostics.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_parse_sql_type() {
+        assert_eq!(SqlType::parse("VARCHAR(20)"), SqlType::Text);
+        assert_eq!(SqlType::parse("double precision"), SqlType::Float);
+        assert_eq!(SqlType::parse("INT UNSIGNED"), SqlType::Integer);
+        assert_eq!(SqlType::parse("GEOMETRY"), SqlType::Unknown);
+    }
+
+    #[test]
+    fn test_infer_types() {
+        assert_eq!(
+            types(
+                "SELECT id + 1, price * 2.5, -price, name, born + 7, active AND id > 1, \
+                 'a', NULL, ?, id IN (1, 2.5) FROM t"
+            ),
+            [
+                "INTEGER",
+                "DECIMAL",
+                "DECIMAL",
+                "TEXT NULL",
+                "DATE NULL",
+                "BOOLEAN",
+                "TEXT",
+                "unknown type NULL",
+                "unknown type NULL",
+                "BOOLEAN"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_aggregate_and_function_types() {
+        assert_eq!(
+            types(
+                "SELECT COUNT(*), SUM(id), AVG(id), MAX(born), COALESCE(name, 'x'), \
+                 COALESCE(id, price), upper(name), length(name), now(), unknown_fn(id), \
+                 CURRENT_DATE, current_timestamp FROM t"
+            ),
+            [
+                "INTEGER",
+                "INTEGER NULL",
+                "DECIMAL NULL",
+                "DATE NULL",
+                "TEXT",
+                "DECIMAL",
+                "TEXT NULL",
+                "INTEGER NULL",
+                "TIMESTAMP",
+                "unknown type NULL",
+                "DATE",
+                "TIMESTAMP"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nodes_without_spans() {
+        // as if built in code, every node has the empty span
+        let mut query = query("SELECT id + 1, name, price * 2.5, 'a' + 1 FROM t WHERE name = 'x'");
+        clear_spans(&mut query);
+        let bound = bind(&query, &catalog());
+        let types = check(&query, &bound);
+        let projection = query.body.projection.iter().map(|item| {
+            let typed = types.of(item).unwrap();
+            (typed.sql_type, typed.nullable)
+        });
+        assert_eq!(
+            projection.collect::<Vec<_>>(),
+            [
+                (SqlType::Integer, false),
+                (SqlType::Text, true),
+                (SqlType::Decimal, false),
+                (SqlType::Unknown, false)
+            ]
+        );
+        let messages = types.diagnostics.into_iter().map(|d| d.message);
+        assert_eq!(
+            messages.collect::<Vec<_>>(),
+            ["can't apply `+` to TEXT and INTEGER"]
+        );
+    }
+
+    #[test]
+    fn test_table_primary_key_isnt_nullable() {
+        assert_eq!(types("SELECT id, code FROM v"), ["INTEGER", "TEXT NULL"]);
+    }
+
+    #[test]
+    fn test_outer_join_and_subquery_types() {
+        assert_eq!(
+            types(
+                "SELECT u.id, s.total, s.name FROM t LEFT JOIN u ON u.t_id = t.id \
+                 JOIN (SELECT name, SUM(price) AS total FROM t GROUP BY name) AS s ON s.name = t.name"
+            ),
+            ["INTEGER NULL", "DECIMAL NULL", "TEXT NULL"]
+        );
+    }
+
+    #[test]
+    fn test_union_types() {
+        assert_eq!(
+            errors(
+                "SELECT id, name FROM t UNION SELECT price, born FROM t UNION ALL SELECT *, 1 FROM u"
+            ),
+            ["UNION can't combine TEXT with DATE"]
+        );
+        assert_eq!(
+            types("SELECT id FROM u UNION SELECT id + 1 FROM v ORDER BY id"),
+            ["INTEGER"]
+        );
+    }
+
+    #[test]
+    fn test_type_errors() {
+        assert_eq!(
+            errors(
+                "SELECT 'abc' + 1, -name, NOT id, SUM(name), upper(id), COALESCE(born, active) \
+                 FROM t WHERE born = active OR id"
+            ),
+            [
+                "can't apply `+` to TEXT and INTEGER",
+                "`-` needs a number, found TEXT",
+                "`NOT` needs a BOOLEAN, found INTEGER",
+                "`SUM` needs a number, found TEXT",
+                "`upper` needs TEXT, found INTEGER",
+                "the arguments of `coalesce` have no common type: DATE, BOOLEAN",
+                "can't compare DATE with BOOLEAN",
+                "`OR` needs BOOLEAN operands, found BOOLEAN and INTEGER"
+            ]
+        );
+        assert_eq!(
+            errors("SELECT id FROM t JOIN u ON u.id HAVING COUNT(*) LIMIT 'x'"),
+            [
+                "`ON` needs a BOOLEAN condition, found INTEGER",
+                "`HAVING` needs a BOOLEAN condition, found INTEGER",
+                "`LIMIT` needs an INTEGER, found TEXT"
+            ]
+        );
+        // a date may be written as text
+        assert!(errors("SELECT id FROM t WHERE born > '2024-01-01' AND seen < born").is_empty());
+    }
+
+    #[test]
+    fn test_integer_booleans() {
+        let query = query(
+            "SELECT NOT id, NOT active FROM t JOIN u ON u.t_id \
+             WHERE id AND NOT u.id HAVING COUNT(*)",
+        );
+        let types = check_with(&query, &bind(&query, &catalog()), &MySqlDialect);
+        assert!(!types.has_errors(), "{:?}", types.diagnostics);
+        let not = types.of(&query.body.projection[0]).unwrap();
+        assert_eq!(not.sql_type, SqlType::Boolean);
+        assert_eq!(
+            errors("SELECT id FROM t WHERE id"),
+            ["`WHERE` needs a BOOLEAN condition, found INTEGER"]
+        );
+    }
+}
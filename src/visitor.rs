@@ -0,0 +1,1049 @@
+//! Traversal of the syntax tree.
+//!
+//! A [`Visitor`] overrides the hooks it's interested in, each called before (`pre_visit_*`) and
+//! after (`post_visit_*`) the children of a node, and returns [`ControlFlow::Break`] to stop early.
+//! [`VisitorMut`] does the same with mutable access, e.g. to rewrite expressions in place.
+//!
+//! ```
+//! use sql_parser::parse_statement;
+//! use sql_parser::visitor::walk_tables;
+//!
+//! let stmt = parse_statement("SELECT a FROM t JOIN (SELECT b FROM u) AS s ON a = b").unwrap();
+//! let mut tables = Vec::new();
+//! walk_tables(&stmt, |table| tables.push(table.to_string()));
+//! assert_eq!(tables, vec!["t", "u"]);
+//! ```
+use std::ops::ControlFlow;
+
+use crate::ast::alter_statement::{AlterStatement, AlterTableOperation};
+use crate::ast::column::{Column, ColumnConstraint, ColumnSpecification};
+use crate::ast::create_statement::{CreateStatement, TableConstraint};
+use crate::ast::delete_statement::DeleteStatement;
+use crate::ast::drop_statement::DropStatement;
+use crate::ast::expression::{ColumnReference, Expression};
+use crate::ast::insert_statement::InsertStatement;
+use crate::ast::join::JoinCondition;
+use crate::ast::select_statement::{
+    CommonTableExpression, Fetch, GroupBy, GroupingElement, OrderBy, SelectClause, SelectStatement,
+    TableReference, Union,
+};
+use crate::ast::span::Span;
+use crate::ast::table::Table;
+use crate::ast::update_statement::{Assignment, UpdateStatement};
+use crate::ast::{Identifier, ObjectName, Statement};
+
+/// Hooks called while walking the tree with [`Visit::visit`], they all continue by default.
+pub trait Visitor {
+    /// The value the walk stops with
+    type Break;
+
+    fn pre_visit_statement(&mut self, _stmt: &Statement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_statement(&mut self, _stmt: &Statement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// A `SELECT`, either a statement of its own or a subquery
+    fn pre_visit_query(&mut self, _query: &SelectStatement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &SelectStatement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_reference(
+        &mut self,
+        _table_ref: &TableReference,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_table_reference(
+        &mut self,
+        _table_ref: &TableReference,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// A table named in `FROM` or as the target of an `INSERT`, `UPDATE`, `DELETE` or `DROP`
+    fn pre_visit_table(&mut self, _table: &Table) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_table(&mut self, _table: &Table) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expression(&mut self, _expr: &Expression) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expression(&mut self, _expr: &Expression) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// Every name: columns, the parts of qualified names and aliases
+    fn visit_identifier(&mut self, _ident: &Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Hooks called while walking the tree with [`VisitMut::visit_mut`], they all continue by default.
+///
+/// Children are walked after `pre_visit_*` returns, so a node replaced there is walked as replaced.
+pub trait VisitorMut {
+    /// The value the walk stops with
+    type Break;
+
+    fn pre_visit_statement(&mut self, _stmt: &mut Statement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_statement(&mut self, _stmt: &mut Statement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, _query: &mut SelectStatement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut SelectStatement) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_reference(
+        &mut self,
+        _table_ref: &mut TableReference,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_table_reference(
+        &mut self,
+        _table_ref: &mut TableReference,
+    ) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table(&mut self, _table: &mut Table) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_table(&mut self, _table: &mut Table) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expression(&mut self, _expr: &mut Expression) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expression(&mut self, _expr: &mut Expression) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_identifier(&mut self, _ident: &mut Identifier) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// The span of every node, after its `pre_visit_*` hook e.g. to clear it with [`clear_spans`]
+    fn visit_span(&mut self, _span: &mut Span) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// A node of the tree that can be walked by a [`Visitor`].
+pub trait Visit {
+    /// Walks the node and its children in source order.
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break>;
+}
+
+/// A node of the tree that can be walked by a [`VisitorMut`].
+pub trait VisitMut {
+    /// Walks the node and its children in source order.
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break>;
+}
+
+impl<T: Visit> Visit for [T] {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.iter().try_for_each(|node| node.visit(visitor))
+    }
+}
+
+impl<T: VisitMut> VisitMut for [T] {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.iter_mut().try_for_each(|node| node.visit_mut(visitor))
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.as_slice().visit(visitor)
+    }
+}
+
+impl<T: VisitMut> VisitMut for Vec<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.as_mut_slice().visit_mut(visitor)
+    }
+}
+
+impl<T: Visit> Visit for Option<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            Some(node) => node.visit(visitor),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+impl<T: VisitMut> VisitMut for Option<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            Some(node) => node.visit_mut(visitor),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+impl<T: Visit> Visit for Box<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        (**self).visit(visitor)
+    }
+}
+
+impl<T: VisitMut> VisitMut for Box<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        (**self).visit_mut(visitor)
+    }
+}
+
+impl Visit for Statement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_statement(self)?;
+        match self {
+            Statement::Select(stmt) => stmt.visit(visitor)?,
+            Statement::Insert(stmt) => stmt.visit(visitor)?,
+            Statement::Update(stmt) => stmt.visit(visitor)?,
+            Statement::Delete(stmt) => stmt.visit(visitor)?,
+            Statement::Drop(stmt) => stmt.visit(visitor)?,
+            Statement::Create(stmt) => stmt.visit(visitor)?,
+            Statement::Alter(stmt) => stmt.visit(visitor)?,
+            Statement::Error(_) => {}
+        }
+        visitor.post_visit_statement(self)
+    }
+}
+
+impl VisitMut for Statement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_statement(self)?;
+        match self {
+            Statement::Select(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Insert(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Update(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Delete(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Drop(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Create(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Alter(stmt) => stmt.visit_mut(visitor)?,
+            Statement::Error(span) => visitor.visit_span(span)?,
+        }
+        visitor.post_visit_statement(self)
+    }
+}
+
+impl Visit for SelectStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_query(self)?;
+        self.with.visit(visitor)?;
+        self.body.visit(visitor)?;
+        self.unions.visit(visitor)?;
+        self.order_by.visit(visitor)?;
+        self.limit.visit(visitor)?;
+        self.offset.visit(visitor)?;
+        self.fetch.visit(visitor)?;
+        visitor.post_visit_query(self)
+    }
+}
+
+impl VisitMut for SelectStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_query(self)?;
+        visitor.visit_span(&mut self.span)?;
+        self.with.visit_mut(visitor)?;
+        self.body.visit_mut(visitor)?;
+        self.unions.visit_mut(visitor)?;
+        self.order_by.visit_mut(visitor)?;
+        self.limit.visit_mut(visitor)?;
+        self.offset.visit_mut(visitor)?;
+        self.fetch.visit_mut(visitor)?;
+        visitor.post_visit_query(self)
+    }
+}
+
+impl Visit for CommonTableExpression {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.name.visit(visitor)?;
+        self.columns.visit(visitor)?;
+        self.query.visit(visitor)
+    }
+}
+
+impl VisitMut for CommonTableExpression {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.name.visit_mut(visitor)?;
+        self.columns.visit_mut(visitor)?;
+        self.query.visit_mut(visitor)
+    }
+}
+
+impl Visit for Union {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.clause.visit(visitor)
+    }
+}
+
+impl VisitMut for Union {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.clause.visit_mut(visitor)
+    }
+}
+
+impl Visit for SelectClause {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.projection.visit(visitor)?;
+        self.from.visit(visitor)?;
+        self.where_exp.visit(visitor)?;
+        self.group_by.visit(visitor)?;
+        self.having.visit(visitor)
+    }
+}
+
+impl VisitMut for SelectClause {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.projection.visit_mut(visitor)?;
+        self.from.visit_mut(visitor)?;
+        self.where_exp.visit_mut(visitor)?;
+        self.group_by.visit_mut(visitor)?;
+        self.having.visit_mut(visitor)
+    }
+}
+
+impl Visit for GroupBy {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            GroupBy::All => ControlFlow::Continue(()),
+            GroupBy::Elements(elements) => elements.visit(visitor),
+        }
+    }
+}
+
+impl VisitMut for GroupBy {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            GroupBy::All => ControlFlow::Continue(()),
+            GroupBy::Elements(elements) => elements.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for GroupingElement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            GroupingElement::Expr(expr) => expr.visit(visitor),
+            GroupingElement::Ordinal(..) => ControlFlow::Continue(()),
+            GroupingElement::Rollup(sets)
+            | GroupingElement::Cube(sets)
+            | GroupingElement::GroupingSets(sets) => sets.visit(visitor),
+        }
+    }
+}
+
+impl VisitMut for GroupingElement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            GroupingElement::Expr(expr) => expr.visit_mut(visitor),
+            GroupingElement::Ordinal(_, span) => visitor.visit_span(span),
+            GroupingElement::Rollup(sets)
+            | GroupingElement::Cube(sets)
+            | GroupingElement::GroupingSets(sets) => sets.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for OrderBy {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.expr.visit(visitor)
+    }
+}
+
+impl VisitMut for OrderBy {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.expr.visit_mut(visitor)
+    }
+}
+
+impl Visit for Fetch {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.quantity.visit(visitor)
+    }
+}
+
+impl VisitMut for Fetch {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.quantity.visit_mut(visitor)
+    }
+}
+
+impl Visit for TableReference {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_table_reference(self)?;
+        match self {
+            TableReference::BaseRelation { table, alias, .. } => {
+                table.visit(visitor)?;
+                alias.visit(visitor)?;
+            }
+            TableReference::SubQuery {
+                subquery, alias, ..
+            } => {
+                subquery.visit(visitor)?;
+                alias.visit(visitor)?;
+            }
+            TableReference::Join {
+                left, right, cond, ..
+            } => {
+                left.visit(visitor)?;
+                right.visit(visitor)?;
+                cond.visit(visitor)?;
+            }
+        }
+        visitor.post_visit_table_reference(self)
+    }
+}
+
+impl VisitMut for TableReference {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_table_reference(self)?;
+        match self {
+            TableReference::BaseRelation { table, alias, span } => {
+                visitor.visit_span(span)?;
+                table.visit_mut(visitor)?;
+                alias.visit_mut(visitor)?;
+            }
+            TableReference::SubQuery {
+                subquery,
+                alias,
+                span,
+            } => {
+                visitor.visit_span(span)?;
+                subquery.visit_mut(visitor)?;
+                alias.visit_mut(visitor)?;
+            }
+            TableReference::Join {
+                left,
+                right,
+                cond,
+                span,
+                ..
+            } => {
+                visitor.visit_span(span)?;
+                left.visit_mut(visitor)?;
+                right.visit_mut(visitor)?;
+                cond.visit_mut(visitor)?;
+            }
+        }
+        visitor.post_visit_table_reference(self)
+    }
+}
+
+impl Visit for JoinCondition {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            JoinCondition::Using(columns) => columns.visit(visitor),
+            JoinCondition::On(exprs) => exprs.visit(visitor),
+        }
+    }
+}
+
+impl VisitMut for JoinCondition {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            JoinCondition::Using(columns) => columns.visit_mut(visitor),
+            JoinCondition::On(exprs) => exprs.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for Table {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_table(self)?;
+        self.name.visit(visitor)?;
+        visitor.post_visit_table(self)
+    }
+}
+
+impl VisitMut for Table {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_table(self)?;
+        visitor.visit_span(&mut self.span)?;
+        self.name.visit_mut(visitor)?;
+        visitor.post_visit_table(self)
+    }
+}
+
+impl Visit for Expression {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_expression(self)?;
+        match self {
+            Expression::ColumnReference(column_ref, _) => match column_ref {
+                ColumnReference::Wildcard => {}
+                ColumnReference::QualifiedWildcard(name) => name.visit(visitor)?,
+                ColumnReference::Column(column) => column.visit(visitor)?,
+            },
+            Expression::Alias { expr, alias, .. } => {
+                expr.visit(visitor)?;
+                alias.visit(visitor)?;
+            }
+            Expression::UnaryExpr { expr, .. } => expr.visit(visitor)?,
+            Expression::BinaryExpr { left, right, .. } => {
+                left.visit(visitor)?;
+                right.visit(visitor)?;
+            }
+            Expression::FunctionExpression { args, .. } => args.visit(visitor)?,
+            Expression::Function { name, args, .. } => {
+                name.visit(visitor)?;
+                args.visit(visitor)?;
+            }
+            Expression::In { left, right, .. } => {
+                left.visit(visitor)?;
+                right.visit(visitor)?;
+            }
+            Expression::Literal(..)
+            | Expression::Parameter(..)
+            | Expression::Niladic(..)
+            | Expression::Error(_) => {}
+        }
+        visitor.post_visit_expression(self)
+    }
+}
+
+impl VisitMut for Expression {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.pre_visit_expression(self)?;
+        visitor.visit_span(self.span_mut())?;
+        match self {
+            Expression::ColumnReference(column_ref, _) => match column_ref {
+                ColumnReference::Wildcard => {}
+                ColumnReference::QualifiedWildcard(name) => name.visit_mut(visitor)?,
+                ColumnReference::Column(column) => column.visit_mut(visitor)?,
+            },
+            Expression::Alias { expr, alias, .. } => {
+                expr.visit_mut(visitor)?;
+                alias.visit_mut(visitor)?;
+            }
+            Expression::UnaryExpr { expr, .. } => expr.visit_mut(visitor)?,
+            Expression::BinaryExpr { left, right, .. } => {
+                left.visit_mut(visitor)?;
+                right.visit_mut(visitor)?;
+            }
+            Expression::FunctionExpression { args, .. } => args.visit_mut(visitor)?,
+            Expression::Function { name, args, .. } => {
+                name.visit_mut(visitor)?;
+                args.visit_mut(visitor)?;
+            }
+            Expression::In { left, right, .. } => {
+                left.visit_mut(visitor)?;
+                right.visit_mut(visitor)?;
+            }
+            Expression::Literal(..)
+            | Expression::Parameter(..)
+            | Expression::Niladic(..)
+            | Expression::Error(_) => {}
+        }
+        visitor.post_visit_expression(self)
+    }
+}
+
+impl Visit for Column {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.column.visit(visitor)
+    }
+}
+
+impl VisitMut for Column {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit_mut(visitor)?;
+        self.column.visit_mut(visitor)
+    }
+}
+
+impl Visit for ObjectName {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        for part in self.parts() {
+            part.visit(visitor)?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl VisitMut for ObjectName {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        for part in self.parts_mut() {
+            part.visit_mut(visitor)?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Visit for Identifier {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_identifier(self)
+    }
+}
+
+impl VisitMut for Identifier {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_identifier(self)?;
+        visitor.visit_span(&mut self.span)
+    }
+}
+
+impl Visit for InsertStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.fields.visit(visitor)?;
+        self.data.visit(visitor)
+    }
+}
+
+impl VisitMut for InsertStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)?;
+        self.fields.visit_mut(visitor)?;
+        self.data.visit_mut(visitor)
+    }
+}
+
+impl Visit for UpdateStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.fields.visit(visitor)?;
+        self.where_clause.visit(visitor)
+    }
+}
+
+impl VisitMut for UpdateStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)?;
+        self.fields.visit_mut(visitor)?;
+        self.where_clause.visit_mut(visitor)
+    }
+}
+
+impl Visit for Assignment {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.column.visit(visitor)?;
+        self.value.visit(visitor)
+    }
+}
+
+impl VisitMut for Assignment {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.column.visit_mut(visitor)?;
+        self.value.visit_mut(visitor)
+    }
+}
+
+impl Visit for DeleteStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.where_clause.visit(visitor)
+    }
+}
+
+impl VisitMut for DeleteStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)?;
+        self.where_clause.visit_mut(visitor)
+    }
+}
+
+impl Visit for DropStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)
+    }
+}
+
+impl VisitMut for DropStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)
+    }
+}
+
+impl Visit for CreateStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.columns.visit(visitor)?;
+        self.constraints.visit(visitor)
+    }
+}
+
+impl VisitMut for CreateStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)?;
+        self.columns.visit_mut(visitor)?;
+        self.constraints.visit_mut(visitor)
+    }
+}
+
+impl Visit for ColumnSpecification {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.column.visit(visitor)?;
+        self.constraint.visit(visitor)
+    }
+}
+
+impl VisitMut for ColumnSpecification {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.column.visit_mut(visitor)?;
+        self.constraint.visit_mut(visitor)
+    }
+}
+
+impl Visit for ColumnConstraint {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            ColumnConstraint::Default(expr) => expr.visit(visitor),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+impl VisitMut for ColumnConstraint {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            ColumnConstraint::Default(expr) => expr.visit_mut(visitor),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+impl Visit for TableConstraint {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.columns().visit(visitor)
+    }
+}
+
+impl VisitMut for TableConstraint {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.columns_mut().visit_mut(visitor)
+    }
+}
+
+impl Visit for AlterStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        self.table.visit(visitor)?;
+        self.operations.visit(visitor)
+    }
+}
+
+impl VisitMut for AlterStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        visitor.visit_span(&mut self.span)?;
+        self.table.visit_mut(visitor)?;
+        self.operations.visit_mut(visitor)
+    }
+}
+
+impl Visit for AlterTableOperation {
+    fn visit<V: Visitor>(&self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            AlterTableOperation::AddColumn(spec) => spec.visit(visitor),
+            AlterTableOperation::DropColumn { column, .. } => column.visit(visitor),
+            AlterTableOperation::RenameColumn { from, to } => {
+                from.visit(visitor)?;
+                to.visit(visitor)
+            }
+            AlterTableOperation::RenameTable(name) => name.visit(visitor),
+        }
+    }
+}
+
+impl VisitMut for AlterTableOperation {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) -> ControlFlow<V::Break> {
+        match self {
+            AlterTableOperation::AddColumn(spec) => spec.visit_mut(visitor),
+            AlterTableOperation::DropColumn { column, .. } => column.visit_mut(visitor),
+            AlterTableOperation::RenameColumn { from, to } => {
+                from.visit_mut(visitor)?;
+                to.visit_mut(visitor)
+            }
+            AlterTableOperation::RenameTable(name) => name.visit_mut(visitor),
+        }
+    }
+}
+
+/// A [`Visitor`] or [`VisitorMut`] calling `F` on the nodes of one kind.
+struct FnVisitor<F>(F);
+
+impl<F: FnMut(&Expression)> Visitor for FnVisitor<F> {
+    type Break = ();
+
+    fn pre_visit_expression(&mut self, expr: &Expression) -> ControlFlow<()> {
+        (self.0)(expr);
+        ControlFlow::Continue(())
+    }
+}
+
+impl<F: FnMut(&mut Expression)> VisitorMut for FnVisitor<F> {
+    type Break = ();
+
+    fn post_visit_expression(&mut self, expr: &mut Expression) -> ControlFlow<()> {
+        (self.0)(expr);
+        ControlFlow::Continue(())
+    }
+}
+
+/// A [`Visitor`] calling `F` on expressions, skipping the children of those it returns `false` for.
+struct PrunedVisitor<F> {
+    f: F,
+    /// The depth inside a skipped expression, 0 when walking
+    skipping: usize,
+}
+
+impl<F: FnMut(&Expression) -> bool> Visitor for PrunedVisitor<F> {
+    type Break = ();
+
+    fn pre_visit_expression(&mut self, expr: &Expression) -> ControlFlow<()> {
+        if self.skipping > 0 || !(self.f)(expr) {
+            self.skipping += 1;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expression(&mut self, _expr: &Expression) -> ControlFlow<()> {
+        self.skipping = self.skipping.saturating_sub(1);
+        ControlFlow::Continue(())
+    }
+}
+
+/// A [`Visitor`] calling `F` on every table.
+struct TableVisitor<F>(F);
+
+impl<F: FnMut(&Table)> Visitor for TableVisitor<F> {
+    type Break = ();
+
+    fn pre_visit_table(&mut self, table: &Table) -> ControlFlow<()> {
+        (self.0)(table);
+        ControlFlow::Continue(())
+    }
+}
+
+/// A [`Visitor`] calling `F` on every query.
+struct QueryVisitor<F>(F);
+
+impl<F: FnMut(&SelectStatement)> Visitor for QueryVisitor<F> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &SelectStatement) -> ControlFlow<()> {
+        (self.0)(query);
+        ControlFlow::Continue(())
+    }
+}
+
+/// A [`VisitorMut`] resetting every span to the default.
+struct SpanClearer;
+
+impl VisitorMut for SpanClearer {
+    type Break = ();
+
+    fn visit_span(&mut self, span: &mut Span) -> ControlFlow<()> {
+        *span = Span::default();
+        ControlFlow::Continue(())
+    }
+}
+
+/// Calls `f` on every expression in `node`, outer expressions before the ones they contain.
+pub fn walk_expressions<N: Visit + ?Sized>(node: &N, f: impl FnMut(&Expression)) {
+    let _ = node.visit(&mut FnVisitor(f));
+}
+
+/// Calls `f` on every expression in `node`, inner expressions before the ones containing them
+/// so that `f` sees the rewritten children of what it's given.
+pub fn walk_expressions_mut<N: VisitMut + ?Sized>(node: &mut N, f: impl FnMut(&mut Expression)) {
+    let _ = node.visit_mut(&mut FnVisitor(f));
+}
+
+/// Calls `f` on the expressions in `node`, outer expressions first, without going into those `f`
+/// returns `false` for.
+pub fn walk_expressions_pruned<N: Visit + ?Sized>(node: &N, f: impl FnMut(&Expression) -> bool) {
+    let _ = node.visit(&mut PrunedVisitor { f, skipping: 0 });
+}
+
+/// Calls `f` on every table in `node`, those in subqueries included. Names of the queries of
+/// `WITH` are tables to the syntax, [`crate::references`] tells them apart.
+pub fn walk_tables<N: Visit + ?Sized>(node: &N, f: impl FnMut(&Table)) {
+    let _ = node.visit(&mut TableVisitor(f));
+}
+
+/// Calls `f` on every query in `node`, outer queries before their subqueries.
+pub fn walk_queries<N: Visit + ?Sized>(node: &N, f: impl FnMut(&SelectStatement)) {
+    let _ = node.visit(&mut QueryVisitor(f));
+}
+
+/// Resets every span in `node` to the default, as if it was built by hand.
+pub fn clear_spans<N: VisitMut + ?Sized>(node: &mut N) {
+    let _ = node.visit_mut(&mut SpanClearer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::expression::{BinOp, Literal, Parameter};
+    use crate::ast::span::Spanned;
+    use crate::{parse_expression, parse_statement};
+
+    #[test]
+    fn test_walk_expressions() {
+        let stmt = parse_statement("SELECT a + 1 FROM t WHERE b IN (2, c)").unwrap();
+        let mut exprs = Vec::new();
+        walk_expressions(&stmt, |expr| exprs.push(expr.to_string()));
+        assert_eq!(exprs, vec!["a + 1", "a", "1", "b IN (2, c)", "b", "2", "c"]);
+    }
+
+    #[test]
+    fn test_walk_expressions_pruned() {
+        let expr = parse_expression("COUNT(a + 1) + (b * 2) - c").unwrap();
+        let mut exprs = Vec::new();
+        walk_expressions_pruned(&expr, |expr| {
+            exprs.push(expr.to_string());
+            !matches!(
+                expr,
+                Expression::FunctionExpression { .. }
+                    | Expression::BinaryExpr {
+                        op: BinOp::Multiplication,
+                        ..
+                    }
+            )
+        });
+        assert_eq!(
+            exprs,
+            vec![
+                "COUNT(a + 1) + b * 2 - c",
+                "COUNT(a + 1) + b * 2",
+                "COUNT(a + 1)",
+                "b * 2",
+                "c"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_expressions_mut() {
+        let mut stmt = parse_statement("UPDATE t SET a = 1 WHERE b = 2 AND c = 3").unwrap();
+        let mut n = 0;
+        walk_expressions_mut(&mut stmt, |expr| {
+            if let Expression::Literal(_, span) = expr {
+                n += 1;
+                *expr = Expression::Parameter(Parameter::Positional(n), *span);
+            }
+        });
+        assert_eq!(
+            stmt.to_string(),
+            "UPDATE t SET a = $1 WHERE b = $2 AND c = $3"
+        );
+    }
+
+    #[test]
+    fn test_walk_queries_and_tables() {
+        let stmt =
+            parse_statement("SELECT a FROM t1 JOIN (SELECT b FROM s.t2) AS x ON a = b ORDER BY a")
+                .unwrap();
+        let mut queries = 0;
+        walk_queries(&stmt, |_| queries += 1);
+        assert_eq!(queries, 2);
+
+        let mut tables = Vec::new();
+        walk_tables(&stmt, |table| tables.push(table.to_string()));
+        assert_eq!(tables, vec!["t1", "s.t2"]);
+    }
+
+    /// Collects the identifiers, stopping at the first one named `stop`.
+    struct Identifiers(Vec<String>);
+
+    impl Visitor for Identifiers {
+        type Break = Span;
+
+        fn visit_identifier(&mut self, ident: &Identifier) -> ControlFlow<Span> {
+            if ident.value == "stop" {
+                return ControlFlow::Break(ident.span);
+            }
+            self.0.push(ident.value.clone());
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn test_visitor_break() {
+        let stmt = parse_statement("SELECT t.a AS x FROM t WHERE stop = b").unwrap();
+        let mut visitor = Identifiers(Vec::new());
+        let result = stmt.visit(&mut visitor);
+        assert_eq!(visitor.0, vec!["t", "a", "x", "t"]);
+        assert!(matches!(result, ControlFlow::Break(span) if span.range() == (29..33)));
+    }
+
+    #[test]
+    fn test_clear_spans() {
+        let mut stmt = parse_statement("INSERT INTO t (a) VALUES (pg_catalog.now() + 1)").unwrap();
+        clear_spans(&mut stmt);
+        let mut visitor = Identifiers(Vec::new());
+        let _ = stmt.visit(&mut visitor);
+        assert_eq!(visitor.0, vec!["t", "a", "pg_catalog", "now"]);
+        assert!(!stmt.span().is_known());
+        walk_expressions(&stmt, |expr| assert!(!expr.span().is_known()));
+    }
+
+    /// Folds additions of integer literals, bottom up.
+    struct Fold;
+
+    impl VisitorMut for Fold {
+        type Break = ();
+
+        fn post_visit_expression(&mut self, expr: &mut Expression) -> ControlFlow<()> {
+            if let Expression::BinaryExpr {
+                left,
+                op: BinOp::Addition,
+                right,
+                span,
+            } = expr
+            {
+                if let (
+                    Expression::Literal(Literal::UnsignedInteger(l), _),
+                    Expression::Literal(Literal::UnsignedInteger(r), _),
+                ) = (&**left, &**right)
+                {
+                    *expr = Expression::Literal(Literal::UnsignedInteger(l + r), *span);
+                }
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        let mut expr = parse_expression("1 + 2 + 3 + a").unwrap();
+        let _ = expr.visit_mut(&mut Fold);
+        assert_eq!(expr.to_string(), "6 + a");
+    }
+}